@@ -0,0 +1,78 @@
+//! Kernel backtraces through the frame pointer chain
+//!
+//! The kernel is built with `-Cforce-frame-pointers=yes`, so every function saves
+//! its return address at `fp - 8` and the frame pointer of its caller at `fp - 16`.
+//! The chain is only followed inside the kernel stack it starts in, whose bounds are
+//! checked before every load, so a corrupted frame ends the walk instead of faulting.
+
+use crate::task::kernel_stack_containing;
+
+/// Most frames printed
+const MAX_FRAMES: usize = 32;
+
+/// Bounds `(bottom, top)` of the kernel stack holding `sp`
+fn stack_bounds(sp: usize) -> Option<(usize, usize)> {
+    extern "C" {
+        fn boot_stack();
+        fn boot_stack_top();
+    }
+    let boot = boot_stack as usize..boot_stack_top as usize;
+    if boot.contains(&sp) {
+        return Some((boot.start, boot.end));
+    }
+    #[cfg(feature = "smp")]
+    for stack in unsafe { crate::SECONDARY_STACKS.0.iter() } {
+        let range = stack.as_ptr_range();
+        if (range.start as usize..range.end as usize).contains(&sp) {
+            return Some((range.start as usize, range.end as usize));
+        }
+    }
+    kernel_stack_containing(sp)
+}
+
+/// Whether `addr` is in the kernel code
+fn in_kernel_text(addr: usize) -> bool {
+    extern "C" {
+        fn stext();
+        fn etext();
+    }
+    (stext as usize..etext as usize).contains(&addr)
+}
+
+/// Print the return addresses of the calls leading here, innermost first,
+/// to be looked up with `addr2line -e os`
+pub fn backtrace() {
+    let (mut fp, sp): (usize, usize);
+    unsafe {
+        core::arch::asm!("mv {}, s0", out(reg) fp);
+        core::arch::asm!("mv {}, sp", out(reg) sp);
+    }
+    let (bottom, top) = match stack_bounds(sp) {
+        Some(bounds) => bounds,
+        None => {
+            println!(
+                "[kernel] no backtrace, sp = {:#x} is on no kernel stack",
+                sp
+            );
+            return;
+        }
+    };
+    println!("[kernel] backtrace:");
+    for depth in 0..MAX_FRAMES {
+        if fp % 8 != 0 || fp < bottom + 16 || fp > top {
+            break;
+        }
+        let (ra, caller_fp) =
+            unsafe { (*((fp - 8) as *const usize), *((fp - 16) as *const usize)) };
+        // the outermost frame of a task stack returns to user space
+        if !in_kernel_text(ra) {
+            break;
+        }
+        println!("  #{} {:#x}", depth, ra);
+        // the frame of a caller lies above those of its callees
+        if caller_fp <= fp {
+            break;
+        }
+        fp = caller_fp;
+    }
+}
@@ -1,13 +1,57 @@
 //! Constants used in rCore
 
-pub const USER_STACK_SIZE: usize = 4096 * 2;
+pub const USER_STACK_SIZE: usize = 4096 * 4;
+/// The user stack grows on demand up to this size, its guard page lies right below
+pub const USER_STACK_LIMIT: usize = 1024 * 1024;
+/// Faults at most this far below the user stack grow it
+pub const USER_STACK_GROWTH: usize = 256 * 1024;
 pub const KERNEL_STACK_SIZE: usize = 4096 * 20;
+/// PIDs are taken from `0..MAX_PID`
+pub const MAX_PID: usize = 4096;
+/// Maximum number of live processes, zombies not reaped yet included
+pub const NPROC: usize = 64;
+/// Maximum number of file descriptors of a process
+pub const MAX_FDS: usize = 64;
+/// Default soft limit of the bytes a process may map through mmap and its heap
+pub const DEFAULT_AS_LIMIT: usize = 1024 * PAGE_SIZE;
 pub const KERNEL_HEAP_SIZE: usize = 0x20_0000;
 pub const MEMORY_END: usize = 0x88000000;
 pub const PAGE_SIZE: usize = 0x1000;
 pub const PAGE_SIZE_BITS: usize = 0xc;
 pub const MAX_SYSCALL_NUM: usize = 500;
+pub const BIG_STRIDE: usize = 0x100000;
+pub const DEFAULT_PRIORITY: usize = 16;
+/// Upper bound of the bytes taken by the argument strings and pointers of a new program
+pub const MAX_ARGS_SIZE: usize = 4096;
+/// Upper bound of the bytes taken by the environment strings and pointers of a new program
+pub const MAX_ENV_SIZE: usize = 4096;
+/// End of the lower half of the Sv39 address space, user mappings stay below it
+/// while `TRAP_CONTEXT` and `TRAMPOLINE` live in the upper half
+pub const USER_SPACE_END: usize = 1 << 38;
+/// Refuse pages both writable and executable, from ELF segments, mmap and mprotect
+pub const DENY_WX: bool = !cfg!(feature = "allow_wx");
+/// Window where mmap with start 0 picks a free range, far above the program and its heap
+pub const MMAP_BASE: usize = 0x10_0000_0000;
+pub const MMAP_TOP: usize = 0x20_0000_0000;
 
 pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
 pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
 pub const CLOCK_FREQ: usize = 12500000;
+
+/// Harts running the scheduler, the ones above are left parked in the SBI
+#[cfg(feature = "smp")]
+pub const MAX_HARTS: usize = 4;
+#[cfg(not(feature = "smp"))]
+pub const MAX_HARTS: usize = 1;
+/// Size of the boot stack of each hart, see `entry.asm`
+#[cfg(feature = "smp")]
+pub const BOOT_STACK_SIZE: usize = 4096 * 16;
+
+/// Base address of the PLIC of the QEMU virt machine
+pub const PLIC_BASE: usize = 0x0c00_0000;
+/// Base address of the 16550 UART of the QEMU virt machine
+pub const UART_BASE: usize = 0x1000_0000;
+/// Interrupt source of the UART at the PLIC
+pub const UART_IRQ: u32 = 10;
+/// Device registers as `(start, len)`, identically mapped into the kernel space
+pub const MMIO: &[(usize, usize)] = &[(PLIC_BASE, 0x40_0000), (UART_BASE, 0x1000)];
@@ -18,6 +18,14 @@ pub fn print(args: fmt::Arguments) {
     Stdout.write_fmt(args).unwrap();
 }
 
+#[macro_export]
+/// println that also keeps the line in the kernel log ring, see [`crate::logging`]
+macro_rules! kprintln {
+    ($fmt: literal $(, $($arg: tt)+)?) => {
+        $crate::logging::print_and_keep(format_args!($fmt $(, $($arg)+)?));
+    }
+}
+
 #[macro_export]
 /// print string macro
 macro_rules! print {
@@ -0,0 +1,34 @@
+//! Device drivers
+//!
+//! Only the UART is driven by interrupts, which reach the kernel through the PLIC
+//! as supervisor external interrupts.
+
+mod plic;
+mod uart;
+
+use crate::config::UART_IRQ;
+use crate::fs::push_console_input;
+use riscv::register::sie;
+
+/// Let the UART interrupt the kernel whenever it receives a byte
+pub fn init() {
+    uart::init();
+    plic::enable(UART_IRQ);
+    unsafe {
+        sie::set_sext();
+    }
+}
+
+/// Serve the pending external interrupts
+pub fn handle_external_interrupt() {
+    while let Some(irq) = plic::claim() {
+        if irq == UART_IRQ {
+            while let Some(byte) = uart::read_byte() {
+                push_console_input(byte);
+            }
+        } else {
+            warn!("[kernel] Unexpected external interrupt {}", irq);
+        }
+        plic::complete(irq);
+    }
+}
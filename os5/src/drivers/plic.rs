@@ -0,0 +1,50 @@
+//! Platform-Level Interrupt Controller
+//!
+//! The interrupts are routed to the supervisor mode of hart 0, which is the
+//! context 1 of the PLIC.
+
+use crate::config::PLIC_BASE;
+
+/// The context of the supervisor mode of hart 0
+const CONTEXT: usize = 1;
+
+fn priority_reg(irq: u32) -> *mut u32 {
+    (PLIC_BASE + 4 * irq as usize) as *mut u32
+}
+
+fn enable_reg(irq: u32) -> *mut u32 {
+    (PLIC_BASE + 0x2000 + 0x80 * CONTEXT + 4 * (irq as usize / 32)) as *mut u32
+}
+
+fn threshold_reg() -> *mut u32 {
+    (PLIC_BASE + 0x20_0000 + 0x1000 * CONTEXT) as *mut u32
+}
+
+fn claim_reg() -> *mut u32 {
+    (PLIC_BASE + 0x20_0004 + 0x1000 * CONTEXT) as *mut u32
+}
+
+/// Forward the interrupt source `irq` to the kernel
+pub fn enable(irq: u32) {
+    unsafe {
+        priority_reg(irq).write_volatile(1);
+        let enable = enable_reg(irq);
+        enable.write_volatile(enable.read_volatile() | 1 << (irq % 32));
+        threshold_reg().write_volatile(0);
+    }
+}
+
+/// Take the pending interrupt source with the highest priority, if any
+pub fn claim() -> Option<u32> {
+    match unsafe { claim_reg().read_volatile() } {
+        0 => None,
+        irq => Some(irq),
+    }
+}
+
+/// Tell the PLIC that the claimed `irq` has been served
+pub fn complete(irq: u32) {
+    unsafe {
+        claim_reg().write_volatile(irq);
+    }
+}
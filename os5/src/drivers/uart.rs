@@ -0,0 +1,49 @@
+//! Receiving side of the 16550 UART
+//!
+//! Output still goes through the SBI, which only polls the transmitter.
+
+use crate::config::UART_BASE;
+
+/// Receiver buffer register
+const RBR: usize = 0;
+/// Interrupt enable register
+const IER: usize = 1;
+/// FIFO control register
+const FCR: usize = 2;
+/// Modem control register
+const MCR: usize = 4;
+/// Line status register
+const LSR: usize = 5;
+
+/// Interrupt when received data is available
+const IER_RX_AVAILABLE: u8 = 1;
+/// Enable and clear the FIFOs
+const FCR_FIFO_ENABLE: u8 = 0b111;
+/// Connect the interrupt line of the UART
+const MCR_OUT2: u8 = 1 << 3;
+/// The receiver buffer holds a byte
+const LSR_DATA_READY: u8 = 1;
+
+fn reg(offset: usize) -> *mut u8 {
+    (UART_BASE + offset) as *mut u8
+}
+
+/// Raise an interrupt for each byte received
+pub fn init() {
+    unsafe {
+        reg(FCR).write_volatile(FCR_FIFO_ENABLE);
+        reg(MCR).write_volatile(reg(MCR).read_volatile() | MCR_OUT2);
+        reg(IER).write_volatile(IER_RX_AVAILABLE);
+    }
+}
+
+/// Take the next received byte, if any
+pub fn read_byte() -> Option<u8> {
+    unsafe {
+        if reg(LSR).read_volatile() & LSR_DATA_READY == 0 {
+            None
+        } else {
+            Some(reg(RBR).read_volatile())
+        }
+    }
+}
@@ -1,6 +1,8 @@
     .section .text.entry
     .globl _start
 _start:
+    # a0 = hartid, kept in tp, see task::hart_id
+    mv tp, a0
     la sp, boot_stack_top
     call rust_main
 
@@ -0,0 +1,24 @@
+//! Objects behind the file descriptors of a process
+//!
+//! There is no filesystem yet, a descriptor refers either to the console
+//! ([`Stdin`] and [`Stdout`]) or to one end of a [`Pipe`].
+
+mod pipe;
+mod stdio;
+
+use crate::mm::UserBuffer;
+
+/// An object a file descriptor refers to
+pub trait File: Send + Sync {
+    /// Whether the object can be read from
+    fn readable(&self) -> bool;
+    /// Whether the object can be written to
+    fn writable(&self) -> bool;
+    /// Read into `buf`, return the number of bytes read or -1 on failure
+    fn read(&self, buf: UserBuffer) -> isize;
+    /// Write out `buf`, return the number of bytes written or -1 on failure
+    fn write(&self, buf: UserBuffer) -> isize;
+}
+
+pub use pipe::{make_pipe, Pipe};
+pub use stdio::{push_console_input, Stdin, Stdout};
@@ -0,0 +1,169 @@
+//! Pipes between related processes
+//!
+//! Both ends of a pipe share a [`PipeRingBuffer`]. The buffer keeps weak
+//! references to the ends, so it knows when all readers or all writers have
+//! been closed: the ends are shared by the fd tables of the forked processes
+//! and go away with the last descriptor referring to them.
+
+use super::File;
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use crate::task::{current_killed_by, suspend_current_and_run_next};
+use alloc::sync::{Arc, Weak};
+
+/// Size of the buffer of a pipe in bytes
+const RING_BUFFER_SIZE: usize = 512;
+
+/// One end of a pipe
+pub struct Pipe {
+    readable: bool,
+    writable: bool,
+    buffer: Arc<UPSafeCell<PipeRingBuffer>>,
+}
+
+impl Pipe {
+    /// The read end of `buffer`
+    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: true,
+            writable: false,
+            buffer,
+        }
+    }
+    /// The write end of `buffer`
+    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: false,
+            writable: true,
+            buffer,
+        }
+    }
+}
+
+/// Bytes written to a pipe and not read yet
+pub struct PipeRingBuffer {
+    arr: [u8; RING_BUFFER_SIZE],
+    head: usize,
+    len: usize,
+    read_end: Option<Weak<Pipe>>,
+    write_end: Option<Weak<Pipe>>,
+}
+
+impl PipeRingBuffer {
+    pub fn new() -> Self {
+        Self {
+            arr: [0; RING_BUFFER_SIZE],
+            head: 0,
+            len: 0,
+            read_end: None,
+            write_end: None,
+        }
+    }
+    fn set_ends(&mut self, read_end: &Arc<Pipe>, write_end: &Arc<Pipe>) {
+        self.read_end = Some(Arc::downgrade(read_end));
+        self.write_end = Some(Arc::downgrade(write_end));
+    }
+    fn write_byte(&mut self, byte: u8) {
+        self.arr[(self.head + self.len) % RING_BUFFER_SIZE] = byte;
+        self.len += 1;
+    }
+    fn read_byte(&mut self) -> u8 {
+        let byte = self.arr[self.head];
+        self.head = (self.head + 1) % RING_BUFFER_SIZE;
+        self.len -= 1;
+        byte
+    }
+    fn available_read(&self) -> usize {
+        self.len
+    }
+    fn available_write(&self) -> usize {
+        RING_BUFFER_SIZE - self.len
+    }
+    fn all_read_ends_closed(&self) -> bool {
+        self.read_end.as_ref().unwrap().upgrade().is_none()
+    }
+    fn all_write_ends_closed(&self) -> bool {
+        self.write_end.as_ref().unwrap().upgrade().is_none()
+    }
+}
+
+/// Create a pipe and return its read end and write end
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new()) });
+    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
+    buffer.exclusive_access().set_ends(&read_end, &write_end);
+    (read_end, write_end)
+}
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+    fn writable(&self) -> bool {
+        self.writable
+    }
+    /// Wait until the pipe has data, then read as much of it as fits in `buf`.
+    /// Return 0 once the pipe is empty and all write ends are closed.
+    fn read(&self, mut buf: UserBuffer) -> isize {
+        if buf.len() == 0 {
+            return 0;
+        }
+        loop {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            let available = ring_buffer.available_read();
+            if available == 0 {
+                if ring_buffer.all_write_ends_closed() {
+                    return 0;
+                }
+                drop(ring_buffer);
+                if current_killed_by().is_some() {
+                    return -1;
+                }
+                suspend_current_and_run_next();
+                continue;
+            }
+            let mut read = 0;
+            for byte in buf.buffers.iter_mut().flat_map(|buffer| buffer.iter_mut()) {
+                if read == available {
+                    break;
+                }
+                *byte = ring_buffer.read_byte();
+                read += 1;
+            }
+            return read as isize;
+        }
+    }
+    /// Write all of `buf`, waiting for readers to make room when the pipe is full.
+    /// A write fitting in the buffer of the pipe is not interleaved with other writes.
+    /// Return -1 if there is no reader left before anything is written.
+    fn write(&self, buf: UserBuffer) -> isize {
+        let mut bytes = buf.buffers.iter().flat_map(|buffer| buffer.iter());
+        let total = buf.len();
+        let atomic = total <= RING_BUFFER_SIZE;
+        let mut written = 0;
+        while written < total {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            if ring_buffer.all_read_ends_closed() {
+                break;
+            }
+            let available = ring_buffer.available_write();
+            if available == 0 || (atomic && available < total) {
+                drop(ring_buffer);
+                if current_killed_by().is_some() {
+                    break;
+                }
+                suspend_current_and_run_next();
+                continue;
+            }
+            for &byte in bytes.by_ref().take(available) {
+                ring_buffer.write_byte(byte);
+                written += 1;
+            }
+        }
+        match written {
+            0 if total > 0 => -1,
+            _ => written as isize,
+        }
+    }
+}
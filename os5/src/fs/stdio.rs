@@ -0,0 +1,150 @@
+//! The console as a file
+//!
+//! Bytes received by the UART are buffered in [`CONSOLE_INPUT`] from the
+//! external interrupt path, readers of the console block until a byte arrives.
+
+use super::File;
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use crate::task::{
+    add_task, block_current_and_run_next, current_interrupted, current_killed_by, current_task,
+};
+use crate::task::{TaskControlBlock, TaskStatus};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// Bytes received beyond this are dropped until the readers catch up
+const CONSOLE_INPUT_SIZE: usize = 1024;
+/// Ctrl-D, a read finding it in front of the input returns 0
+const EOT: u8 = 0x04;
+
+/// Input from the console
+pub struct Stdin;
+
+/// Output to the console
+pub struct Stdout;
+
+/// Bytes received from the console and not read yet, with the tasks waiting for them
+struct ConsoleInput {
+    buffer: VecDeque<u8>,
+    waiters: Vec<Arc<TaskControlBlock>>,
+}
+
+lazy_static! {
+    static ref CONSOLE_INPUT: UPSafeCell<ConsoleInput> = unsafe {
+        UPSafeCell::new(ConsoleInput {
+            buffer: VecDeque::new(),
+            waiters: Vec::new(),
+        })
+    };
+}
+
+/// Buffer a byte received from the console and wake the readers up
+pub fn push_console_input(byte: u8) {
+    let mut input = CONSOLE_INPUT.exclusive_access();
+    if input.buffer.len() < CONSOLE_INPUT_SIZE {
+        input.buffer.push_back(byte);
+    }
+    for task in input.waiters.drain(..) {
+        // the task may have been woken up already by a kill
+        let mut task_inner = task.inner_exclusive_access();
+        if task_inner.task_status == TaskStatus::Blocked {
+            task_inner.task_status = TaskStatus::Ready;
+            drop(task_inner);
+            add_task(task);
+        }
+    }
+}
+
+impl File for Stdin {
+    fn readable(&self) -> bool {
+        true
+    }
+    fn writable(&self) -> bool {
+        false
+    }
+    /// Wait until the console has input, then read as much of it as fits in `buf`,
+    /// stopping in front of Ctrl-D. Return 0 if Ctrl-D comes first, or -1 if a signal
+    /// interrupts the wait, so that its handler runs on the way back to user space.
+    fn read(&self, mut buf: UserBuffer) -> isize {
+        if buf.len() == 0 {
+            return 0;
+        }
+        loop {
+            let mut input = CONSOLE_INPUT.exclusive_access();
+            if input.buffer.is_empty() {
+                if current_killed_by().is_some() {
+                    return -1;
+                }
+                let task = current_task().unwrap();
+                if !input
+                    .waiters
+                    .iter()
+                    .any(|waiter| Arc::ptr_eq(waiter, &task))
+                {
+                    input.waiters.push(task);
+                }
+                drop(input);
+                block_current_and_run_next();
+                // a signal leaves the task among the waiters, where input arriving after it
+                // blocks on something else would wake it up
+                let task = current_task().unwrap();
+                CONSOLE_INPUT
+                    .exclusive_access()
+                    .waiters
+                    .retain(|waiter| !Arc::ptr_eq(waiter, &task));
+                drop(task);
+                if current_interrupted() {
+                    return -1;
+                }
+                continue;
+            }
+            if input.buffer.front() == Some(&EOT) {
+                input.buffer.pop_front();
+                return 0;
+            }
+            let mut read = 0;
+            for byte in buf.buffers.iter_mut().flat_map(|buffer| buffer.iter_mut()) {
+                match input.buffer.front() {
+                    Some(&front) if front != EOT => {
+                        *byte = front;
+                        input.buffer.pop_front();
+                        read += 1;
+                    }
+                    _ => break,
+                }
+            }
+            return read as isize;
+        }
+    }
+    fn write(&self, _buf: UserBuffer) -> isize {
+        -1
+    }
+}
+
+impl File for Stdout {
+    fn readable(&self) -> bool {
+        false
+    }
+    fn writable(&self) -> bool {
+        true
+    }
+    fn read(&self, _buf: UserBuffer) -> isize {
+        -1
+    }
+    /// Print `buf` in one go, so the output of a single write is never split
+    fn write(&self, buf: UserBuffer) -> isize {
+        // a character may span two pieces of the buffer
+        let bytes: Vec<u8> = buf
+            .buffers
+            .iter()
+            .flat_map(|buffer| buffer.iter())
+            .copied()
+            .collect();
+        print!("{}", String::from_utf8_lossy(&bytes));
+        bytes.len() as isize
+    }
+}
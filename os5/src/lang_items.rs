@@ -1,7 +1,11 @@
 //! The panic handler
 
+use crate::backtrace::backtrace;
 use crate::console::ANSICON;
+use crate::mm::{print_frame_stats, print_heap_stats};
 use crate::sbi::shutdown;
+use crate::task::{current_pid, hart_id, try_current_task};
+use crate::trap::handling_trap;
 
 use core::panic::PanicInfo;
 
@@ -25,5 +29,35 @@ fn panic(info: &PanicInfo) -> ! {
             info.message().unwrap()
         );
     }
-    shutdown()
+    print_panic_context();
+    backtrace();
+    print_frame_stats();
+    print_heap_stats();
+    shutdown(true)
+}
+
+/// Print the current task and the trap being handled,
+/// leaving out what the code that panicked still borrows
+fn print_panic_context() {
+    let task = try_current_task();
+    let inner = task
+        .as_ref()
+        .and_then(|task| task.try_inner_exclusive_access());
+    let name = inner.as_ref().map_or("?", |inner| inner.name());
+    match current_pid() {
+        Some(pid) => {
+            println!("[kernel] hart {} running pid {} [{}]", hart_id(), pid, name);
+        }
+        None => {
+            println!("[kernel] hart {} in the idle control flow", hart_id());
+        }
+    }
+    match handling_trap() {
+        Some(scause) => {
+            println!("[kernel] while handling a trap, scause = {:#x}", scause);
+        }
+        None => {
+            println!("[kernel] not in a trap handler");
+        }
+    }
 }
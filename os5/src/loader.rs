@@ -1,5 +1,7 @@
 //! Loading user applications into memory
 
+use alloc::collections::BTreeMap;
+use alloc::string::String;
 use alloc::vec::Vec;
 use lazy_static::*;
 
@@ -53,12 +55,28 @@ lazy_static! {
     };
 }
 
+lazy_static! {
+    /// Elf data of each app by its name
+    static ref APPS: BTreeMap<&'static str, &'static [u8]> = APP_NAMES
+        .iter()
+        .enumerate()
+        .map(|(i, &name)| (name, get_app_data(i)))
+        .collect();
+}
+
 /// Get elf data by app name
 pub fn get_app_data_by_name(name: &str) -> Option<&'static [u8]> {
-    let num_app = get_num_app();
-    (0..num_app)
-        .find(|&i| APP_NAMES[i] == name)
-        .map(get_app_data)
+    APPS.get(name).copied()
+}
+
+/// The names of the apps in alphabetical order, each followed by a newline
+pub fn app_list() -> String {
+    let mut list = String::new();
+    for name in APPS.keys() {
+        list.push_str(name);
+        list.push('\n');
+    }
+    list
 }
 
 /// Print all of app names during kernel initialization
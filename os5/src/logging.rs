@@ -1,16 +1,197 @@
 //! Global logger
+//!
+//! `LOG` sets the level at boot, and `LOG_MAX` the highest level [`set_level`] may
+//! turn on later, both at build time. Besides the global level, a few module path
+//! prefixes like `task::manager` may be logged up to a higher level of their own.
+//! `log::max_level()` is kept at the highest of them all, so that the log macros
+//! skip a filtered record before formatting anything.
+//!
+//! The records up to `RING_LEVEL` are also kept in a ring buffer whatever the console
+//! levels, along with the lines printed by [`kprintln!`], each stamped with the time
+//! since boot and the current pid. User space reads it back through `sys_syslog`.
+//! A full ring drops its oldest lines whole to make room for a new one.
 
+use crate::sync::SpinLock;
+use crate::task::current_pid;
+use crate::timer::get_time_us;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicUsize, Ordering};
 use log::{self, Level, LevelFilter, Log, Metadata, Record};
 
+/// Size of the log ring in bytes
+pub const LOG_RING_SIZE: usize = 64 * 1024;
+/// Longer lines are cut when kept in the ring
+const MAX_LINE: usize = 256;
+/// Records up to this level are kept in the ring
+const RING_LEVEL: Level = Level::Info;
+
+/// Most module path prefixes with a level of their own
+const MAX_TARGETS: usize = 8;
+/// Levels in the order of their numbers in [`set_level`]
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+/// Number of the global level
+static LEVEL: AtomicUsize = AtomicUsize::new(0);
+/// Module path prefixes, without the crate name, and their levels
+static TARGETS: SpinLock<Vec<(String, LevelFilter)>> = SpinLock::new(Vec::new());
+static LOG_RING: SpinLock<LogRing> = SpinLock::new(LogRing::new());
+
+/// Bytes of whole lines, the oldest one starting at `head`
+struct LogRing {
+    buf: [u8; LOG_RING_SIZE],
+    head: usize,
+    len: usize,
+}
+
+impl LogRing {
+    const fn new() -> Self {
+        Self {
+            buf: [0; LOG_RING_SIZE],
+            head: 0,
+            len: 0,
+        }
+    }
+    fn byte(&self, i: usize) -> u8 {
+        self.buf[(self.head + i) % LOG_RING_SIZE]
+    }
+    /// Drop the oldest line, or what is left of it
+    fn drop_oldest_line(&mut self) {
+        let mut n = 0;
+        while n < self.len {
+            n += 1;
+            if self.byte(n - 1) == b'\n' {
+                break;
+            }
+        }
+        self.head = (self.head + n) % LOG_RING_SIZE;
+        self.len -= n;
+    }
+    fn push_line(&mut self, line: &[u8]) {
+        while self.len + line.len() > LOG_RING_SIZE {
+            self.drop_oldest_line();
+        }
+        for &b in line {
+            self.buf[(self.head + self.len) % LOG_RING_SIZE] = b;
+            self.len += 1;
+        }
+    }
+    /// Copy the newest bytes that fit into `out`, starting at a line if cut, and return
+    /// how many were copied
+    fn read(&self, out: &mut [u8]) -> usize {
+        let mut skip = self.len.saturating_sub(out.len());
+        if skip > 0 {
+            while skip < self.len && self.byte(skip - 1) != b'\n' {
+                skip += 1;
+            }
+        }
+        let n = self.len - skip;
+        for (i, b) in out.iter_mut().take(n).enumerate() {
+            *b = self.byte(skip + i);
+        }
+        n
+    }
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+/// A line formatted on the stack, cut at `MAX_LINE` bytes with its newline kept
+struct LineBuf {
+    buf: [u8; MAX_LINE],
+    len: usize,
+}
+
+impl Write for LineBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let n = s.len().min(MAX_LINE - 1 - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+/// Keep a line in the ring, `level` is left out for a line printed by [`kprintln!`]
+fn keep(level: Option<Level>, args: fmt::Arguments) {
+    let mut line = LineBuf {
+        buf: [0; MAX_LINE],
+        len: 0,
+    };
+    let _ = write!(line, "[{:>10}us] ", get_time_us());
+    if let Some(pid) = current_pid() {
+        let _ = write!(line, "[pid {}] ", pid);
+    }
+    if let Some(level) = level {
+        let _ = write!(line, "{:>5} ", level);
+    }
+    let _ = line.write_fmt(args);
+    line.buf[line.len] = b'\n';
+    line.len += 1;
+    LOG_RING.lock().push_line(&line.buf[..line.len]);
+}
+
+/// Print a line to the console and keep it in the ring, for [`kprintln!`]
+pub fn print_and_keep(args: fmt::Arguments) {
+    println!("{}", args);
+    keep(None, args);
+}
+
+/// Copy the newest lines of the ring that fit into `out`, emptying the ring if `clear`,
+/// and return the number of bytes copied
+pub fn read_log(out: &mut [u8], clear: bool) -> usize {
+    let mut ring = LOG_RING.lock();
+    let n = ring.read(out);
+    if clear {
+        ring.clear();
+    }
+    n
+}
+
+/// Empty the ring
+pub fn clear_log() {
+    LOG_RING.lock().clear();
+}
+
+/// Number of bytes in the ring
+pub fn log_len() -> usize {
+    LOG_RING.lock().len
+}
+
 /// a simple logger
 struct SimpleLogger;
 
+impl SimpleLogger {
+    /// Whether a record goes to the console
+    fn console_enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.level() <= LEVELS[LEVEL.load(Ordering::Relaxed)] {
+            return true;
+        }
+        let target = metadata.target();
+        let target = target.strip_prefix("os::").unwrap_or(target);
+        TARGETS.lock().iter().any(|(prefix, level)| {
+            target.starts_with(prefix.as_str()) && metadata.level() <= *level
+        })
+    }
+}
+
 impl Log for SimpleLogger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= RING_LEVEL || self.console_enabled(metadata)
     }
     fn log(&self, record: &Record) {
-        if !self.enabled(record.metadata()) {
+        if record.level() <= RING_LEVEL {
+            keep(Some(record.level()), *record.args());
+        }
+        if !self.console_enabled(record.metadata()) {
             return;
         }
         let color = match record.level() {
@@ -30,16 +211,69 @@ impl Log for SimpleLogger {
     fn flush(&self) {}
 }
 
+/// Number of the level named `name`, 0 (off) for anything else
+fn parse_level(name: Option<&str>) -> usize {
+    match name {
+        Some("ERROR") => 1,
+        Some("WARN") => 2,
+        Some("INFO") => 3,
+        Some("DEBUG") => 4,
+        Some("TRACE") => 5,
+        _ => 0,
+    }
+}
+
+/// Highest level allowed at run time, `LOG_MAX` defaults to TRACE
+fn max_allowed_level() -> usize {
+    match option_env!("LOG_MAX") {
+        Some(name) => parse_level(Some(name)),
+        None => 5,
+    }
+}
+
+/// Raise `log::max_level()` to the highest level in use, or lower it
+fn update_max_level(targets: &[(String, LevelFilter)]) {
+    let global = LEVELS[LEVEL.load(Ordering::Relaxed)].max(RING_LEVEL.to_level_filter());
+    let max = targets
+        .iter()
+        .map(|(_, level)| *level)
+        .fold(global, Ord::max);
+    log::set_max_level(max);
+}
+
+/// Set the global level, or the level of the modules under `target` if given, to the
+/// level numbered `level` from 0 (off) to 5 (trace); a target at level 0 follows the
+/// global level again. Return the previous level, or `None` if `level` is above `LOG_MAX`
+/// or no more targets can be added
+pub fn set_level(level: usize, target: Option<&str>) -> Option<usize> {
+    if level > max_allowed_level() {
+        return None;
+    }
+    let mut targets = TARGETS.lock();
+    let previous = match target {
+        None => LEVEL.swap(level, Ordering::Relaxed),
+        Some(target) => {
+            let position = targets.iter().position(|(prefix, _)| prefix == target);
+            let previous = position.map_or(0, |i| targets[i].1 as usize);
+            match position {
+                Some(i) if level == 0 => drop(targets.remove(i)),
+                Some(i) => targets[i].1 = LEVELS[level],
+                None if level == 0 => {}
+                None if targets.len() == MAX_TARGETS => return None,
+                None => targets.push((String::from(target), LEVELS[level])),
+            }
+            previous
+        }
+    };
+    update_max_level(&targets);
+    Some(previous)
+}
+
 /// initiate logger
 pub fn init() {
     static LOGGER: SimpleLogger = SimpleLogger;
     log::set_logger(&LOGGER).unwrap();
-    log::set_max_level(match option_env!("LOG") {
-        Some("ERROR") => LevelFilter::Error,
-        Some("WARN") => LevelFilter::Warn,
-        Some("INFO") => LevelFilter::Info,
-        Some("DEBUG") => LevelFilter::Debug,
-        Some("TRACE") => LevelFilter::Trace,
-        _ => LevelFilter::Off,
-    });
+    let level = parse_level(option_env!("LOG")).min(max_allowed_level());
+    LEVEL.store(level, Ordering::Relaxed);
+    update_max_level(&[]);
 }
@@ -14,6 +14,10 @@
 //!
 //! We then call [`task::run_first_task()`] and for the first time go to
 //! userspace.
+//!
+//! With the `smp` feature, hart 0 then starts the other harts through the SBI at
+//! `_start_secondary`, each on a boot stack of its own, and they join the scheduler
+//! from [`rust_main_secondary()`].
 
 #![no_std]
 #![no_main]
@@ -29,11 +33,15 @@ extern crate alloc;
 
 #[macro_use]
 mod console;
+mod backtrace;
 mod config;
+mod drivers;
+mod fs;
 mod lang_items;
 mod loader;
 mod logging;
 mod mm;
+mod random;
 mod sbi;
 mod sync;
 mod syscall;
@@ -43,6 +51,24 @@ mod trap;
 
 core::arch::global_asm!(include_str!("entry.asm"));
 core::arch::global_asm!(include_str!("link_app.S"));
+#[cfg(feature = "smp")]
+core::arch::global_asm!(
+    "    .section .text",
+    "    .globl _start_secondary",
+    "_start_secondary:",
+    "    mv tp, a0",
+    "    mv sp, a1",
+    "    call rust_main_secondary",
+);
+
+/// Boot stacks of the secondary harts, the one of hart 0 is in `entry.asm`
+#[cfg(feature = "smp")]
+#[repr(align(4096))]
+struct BootStacks([[u8; config::BOOT_STACK_SIZE]; config::MAX_HARTS - 1]);
+
+#[cfg(feature = "smp")]
+static mut SECONDARY_STACKS: BootStacks =
+    BootStacks([[0; config::BOOT_STACK_SIZE]; config::MAX_HARTS - 1]);
 
 /// clear BSS segment
 fn clear_bss() {
@@ -56,20 +82,65 @@ fn clear_bss() {
     }
 }
 
+/// Start the harts `1..MAX_HARTS`, those missing from the machine are reported and skipped
+#[cfg(feature = "smp")]
+fn start_secondary_harts() {
+    extern "C" {
+        fn _start_secondary();
+    }
+    for hartid in 1..config::MAX_HARTS {
+        let stack_top =
+            unsafe { SECONDARY_STACKS.0[hartid - 1].as_ptr() as usize } + config::BOOT_STACK_SIZE;
+        let ret = sbi::hart_start(hartid, _start_secondary as usize, stack_top);
+        if ret != 0 {
+            info!("[kernel] hart {} not started, SBI error {}", hartid, ret);
+        }
+    }
+}
+
 #[no_mangle]
 /// the rust entry-point of os
 pub fn rust_main() -> ! {
     clear_bss();
+    // the other harts wait for it until the first task goes to user space
+    sync::big_kernel_lock();
     logging::init();
     println!("[kernel] Hello, world!");
+    random::init();
     mm::init();
+    mm::heap_stats_test();
     mm::remap_test();
+    mm::mmap_coalesce_test();
+    #[cfg(feature = "frame_double_free_test")]
+    mm::frame_double_free_test();
+    #[cfg(feature = "upsafecell_double_borrow_test")]
+    sync::double_borrow_test();
+    syscall::syscall_slot_test();
+    #[cfg(all(feature = "selftest", not(feature = "sched_mlfq")))]
+    task::stride_test();
     task::add_initproc();
     info!("after initproc!");
     trap::init();
     trap::enable_timer_interrupt();
+    drivers::init();
     timer::set_next_trigger();
     loader::list_apps();
+    #[cfg(feature = "smp")]
+    start_secondary_harts();
     task::run_tasks();
     panic!("Unreachable in rust_main!");
 }
+
+#[cfg(feature = "smp")]
+#[no_mangle]
+/// the rust entry-point of the secondary harts, once hart 0 has set the kernel up
+pub fn rust_main_secondary(hartid: usize) -> ! {
+    sync::big_kernel_lock();
+    mm::init_hart();
+    trap::init();
+    trap::enable_timer_interrupt();
+    timer::set_next_trigger();
+    info!("[kernel] hart {} is up", hartid);
+    task::run_tasks();
+    panic!("Unreachable in rust_main_secondary!");
+}
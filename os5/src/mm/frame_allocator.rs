@@ -3,9 +3,13 @@
 
 use super::{PhysAddr, PhysPageNum};
 use crate::config::MEMORY_END;
-use crate::sync::UPSafeCell;
+use crate::sync::SpinLockIrqSave;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug, Formatter};
+#[cfg(feature = "frame_debug")]
+use core::panic::Location;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use lazy_static::*;
 
 /// manage a frame which has the same lifecycle as the tracker
@@ -47,14 +51,56 @@ pub struct StackFrameAllocator {
     current: usize,
     end: usize,
     recycled: Vec<usize>,
+    total: usize,
+    /// First frame managed by the allocator
+    base: usize,
+    /// One bit per frame from `base` on, set while the frame is allocated
+    in_use: Vec<u64>,
+    /// Where each frame was allocated last, reported when it is freed twice
+    #[cfg(feature = "frame_debug")]
+    sites: Vec<Option<&'static Location<'static>>>,
 }
 
 impl StackFrameAllocator {
     pub fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
         self.current = l.0;
         self.end = r.0;
+        self.total = self.end - self.current;
+        self.base = l.0;
+        self.in_use = vec![0; (self.total + 63) / 64];
+        #[cfg(feature = "frame_debug")]
+        {
+            self.sites = vec![None; self.total];
+        }
         info!("last {} Physical Frames.", self.end - self.current);
     }
+    /// Whether the frame `ppn`, which must be managed by the allocator, is allocated
+    fn is_allocated(&self, ppn: usize) -> bool {
+        let index = ppn - self.base;
+        self.in_use[index / 64] & (1 << (index % 64)) != 0
+    }
+    /// Mark the frame `ppn` as allocated or free
+    fn set_allocated(&mut self, ppn: usize, allocated: bool) {
+        let index = ppn - self.base;
+        if allocated {
+            self.in_use[index / 64] |= 1 << (index % 64);
+        } else {
+            self.in_use[index / 64] &= !(1 << (index % 64));
+        }
+    }
+    /// Remember that the frame `ppn` was allocated at `site`
+    #[cfg(feature = "frame_debug")]
+    fn record_site(&mut self, ppn: PhysPageNum, site: &'static Location<'static>) {
+        self.sites[ppn.0 - self.base] = Some(site);
+    }
+    /// Number of frames which can still be allocated
+    pub fn free_count(&self) -> usize {
+        self.end - self.current + self.recycled.len()
+    }
+    /// Number of frames managed by the allocator
+    pub fn total_count(&self) -> usize {
+        self.total
+    }
 }
 impl FrameAllocator for StackFrameAllocator {
     fn new() -> Self {
@@ -62,24 +108,42 @@ impl FrameAllocator for StackFrameAllocator {
             current: 0,
             end: 0,
             recycled: Vec::new(),
+            total: 0,
+            base: 0,
+            in_use: Vec::new(),
+            #[cfg(feature = "frame_debug")]
+            sites: Vec::new(),
         }
     }
     fn alloc(&mut self) -> Option<PhysPageNum> {
-        if let Some(ppn) = self.recycled.pop() {
-            Some(ppn.into())
+        let ppn = if let Some(ppn) = self.recycled.pop() {
+            ppn
         } else if self.current == self.end {
-            None
+            return None;
         } else {
             self.current += 1;
-            Some((self.current - 1).into())
-        }
+            self.current - 1
+        };
+        self.set_allocated(ppn, true);
+        Some(ppn.into())
     }
     fn dealloc(&mut self, ppn: PhysPageNum) {
         let ppn = ppn.0;
         // validity check
-        if ppn >= self.current || self.recycled.iter().any(|v| *v == ppn) {
+        if ppn < self.base || ppn >= self.current {
             panic!("Frame ppn={:#x} has not been allocated!", ppn);
         }
+        if !self.is_allocated(ppn) {
+            #[cfg(feature = "frame_debug")]
+            if let Some(site) = self.sites[ppn - self.base] {
+                panic!(
+                    "Frame ppn={:#x} is freed twice, allocated at {}!",
+                    ppn, site
+                );
+            }
+            panic!("Frame ppn={:#x} is freed twice!", ppn);
+        }
+        self.set_allocated(ppn, false);
         // recycle
         self.recycled.push(ppn);
     }
@@ -89,31 +153,89 @@ type FrameAllocatorImpl = StackFrameAllocator;
 
 lazy_static! {
     /// frame allocator instance through lazy_static!
-    pub static ref FRAME_ALLOCATOR: UPSafeCell<FrameAllocatorImpl> =
-        unsafe { UPSafeCell::new(FrameAllocatorImpl::new()) };
+    pub static ref FRAME_ALLOCATOR: SpinLockIrqSave<FrameAllocatorImpl> =
+        SpinLockIrqSave::new(FrameAllocatorImpl::new());
 }
 
 pub fn init_frame_allocator() {
     extern "C" {
         fn ekernel();
     }
-    FRAME_ALLOCATOR.exclusive_access().init(
+    let mut allocator = FRAME_ALLOCATOR.lock();
+    allocator.init(
         PhysAddr::from(ekernel as usize).ceil(),
         PhysAddr::from(MEMORY_END).floor(),
     );
+    FRAMES_TOTAL.store(allocator.total_count(), Ordering::Relaxed);
 }
 
+/// Statistics of the frame allocator since boot
+#[derive(Clone, Copy, Debug)]
+pub struct FrameStats {
+    /// Number of frames managed by the allocator
+    pub total: usize,
+    /// Number of frames allocated now
+    pub allocated: usize,
+    /// Highest number of frames allocated at the same time
+    pub peak: usize,
+    /// Number of allocations failed for lack of frames
+    pub failures: usize,
+}
+
+// kept out of `FRAME_ALLOCATOR` so that the panic handler can read them
+// even if the panic happened while the allocator was held
+static FRAMES_TOTAL: AtomicUsize = AtomicUsize::new(0);
+static FRAMES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static FRAMES_PEAK: AtomicUsize = AtomicUsize::new(0);
+static FRAME_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
 /// initiate the frame allocator using `ekernel` and `MEMORY_END`
+#[cfg_attr(feature = "frame_debug", track_caller)]
 pub fn frame_alloc() -> Option<FrameTracker> {
-    FRAME_ALLOCATOR
-        .exclusive_access()
-        .alloc()
-        .map(FrameTracker::new)
+    let mut allocator = FRAME_ALLOCATOR.lock();
+    let ppn = allocator.alloc();
+    #[cfg(feature = "frame_debug")]
+    if let Some(ppn) = ppn {
+        allocator.record_site(ppn, Location::caller());
+    }
+    drop(allocator);
+    if ppn.is_some() {
+        let allocated = FRAMES_ALLOCATED.fetch_add(1, Ordering::Relaxed) + 1;
+        FRAMES_PEAK.fetch_max(allocated, Ordering::Relaxed);
+    } else {
+        FRAME_FAILURES.fetch_add(1, Ordering::Relaxed);
+    }
+    ppn.map(FrameTracker::new)
+}
+
+/// Statistics of the frame allocator
+pub fn frame_stats() -> FrameStats {
+    FrameStats {
+        total: FRAMES_TOTAL.load(Ordering::Relaxed),
+        allocated: FRAMES_ALLOCATED.load(Ordering::Relaxed),
+        peak: FRAMES_PEAK.load(Ordering::Relaxed),
+        failures: FRAME_FAILURES.load(Ordering::Relaxed),
+    }
+}
+
+/// Print the statistics of the frame allocator
+pub fn print_frame_stats() {
+    let stats = frame_stats();
+    println!(
+        "[kernel] frames: {} total, {} allocated, {} at peak, {} failed allocations",
+        stats.total, stats.allocated, stats.peak, stats.failures
+    );
+}
+
+/// Number of free frames
+pub fn frame_free_count() -> usize {
+    FRAME_ALLOCATOR.lock().free_count()
 }
 
 /// deallocate a frame
 fn frame_dealloc(ppn: PhysPageNum) {
-    FRAME_ALLOCATOR.exclusive_access().dealloc(ppn);
+    FRAME_ALLOCATOR.lock().dealloc(ppn);
+    FRAMES_ALLOCATED.fetch_sub(1, Ordering::Relaxed);
 }
 
 #[allow(unused)]
@@ -134,3 +256,17 @@ pub fn frame_allocator_test() {
     drop(v);
     info!("frame_allocator_test passed!");
 }
+
+/// Free a frame twice on purpose, the kernel must panic naming the frame
+#[cfg(feature = "frame_double_free_test")]
+pub fn frame_double_free_test() {
+    let frame = frame_alloc().unwrap();
+    let twin = FrameTracker { ppn: frame.ppn };
+    info!(
+        "frame_double_free_test: freeing ppn={:#x} twice",
+        frame.ppn.0
+    );
+    drop(frame);
+    drop(twin);
+    panic!("frame_double_free_test failed!");
+}
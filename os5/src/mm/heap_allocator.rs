@@ -1,16 +1,51 @@
 //! The global allocator
 
 use crate::config::KERNEL_HEAP_SIZE;
+use crate::task::current_pid;
 use buddy_system_allocator::LockedHeap;
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The buddy heap, counting the bytes it hands out
+struct TrackedHeap(LockedHeap);
+
+unsafe impl GlobalAlloc for TrackedHeap {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.0.alloc(layout);
+        if !ptr.is_null() {
+            let in_use = HEAP_IN_USE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            HEAP_PEAK.fetch_max(in_use, Ordering::Relaxed);
+            HEAP_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout);
+        HEAP_IN_USE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
 
 #[global_allocator]
 /// heap allocator instance
-static HEAP_ALLOCATOR: LockedHeap = LockedHeap::empty();
+static HEAP_ALLOCATOR: TrackedHeap = TrackedHeap(LockedHeap::empty());
+
+// kept out of `HEAP_ALLOCATOR` so that they can be read without taking its lock,
+// which the panic handler may find taken
+static HEAP_IN_USE: AtomicUsize = AtomicUsize::new(0);
+static HEAP_PEAK: AtomicUsize = AtomicUsize::new(0);
+static HEAP_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
 
 #[alloc_error_handler]
 /// panic when heap allocation error occurs
 pub fn handle_alloc_error(layout: core::alloc::Layout) -> ! {
-    panic!("Heap allocation error, layout = {:?}", layout);
+    let stats = kernel_heap_stats();
+    panic!(
+        "Heap allocation error, layout = {:?}, {} of {} bytes in use, pid = {:?}",
+        layout,
+        stats.in_use,
+        stats.total,
+        current_pid()
+    );
 }
 
 /// heap space ([u8; KERNEL_HEAP_SIZE])
@@ -20,11 +55,59 @@ static mut HEAP_SPACE: [u8; KERNEL_HEAP_SIZE] = [0; KERNEL_HEAP_SIZE];
 pub fn init_heap() {
     unsafe {
         HEAP_ALLOCATOR
+            .0
             .lock()
             .init(HEAP_SPACE.as_ptr() as usize, KERNEL_HEAP_SIZE);
     }
 }
 
+/// Statistics of the kernel heap since boot, counting the sizes requested
+#[derive(Clone, Copy, Debug)]
+pub struct KernelHeapStats {
+    /// Size of the heap in bytes
+    pub total: usize,
+    /// Bytes allocated now
+    pub in_use: usize,
+    /// Highest number of bytes allocated at the same time
+    pub peak: usize,
+    /// Number of allocations made
+    pub allocations: usize,
+}
+
+/// Statistics of the kernel heap
+pub fn kernel_heap_stats() -> KernelHeapStats {
+    KernelHeapStats {
+        total: KERNEL_HEAP_SIZE,
+        in_use: HEAP_IN_USE.load(Ordering::Relaxed),
+        peak: HEAP_PEAK.load(Ordering::Relaxed),
+        allocations: HEAP_ALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+/// Print the statistics of the kernel heap
+pub fn print_heap_stats() {
+    let stats = kernel_heap_stats();
+    println!(
+        "[kernel] heap: {} bytes total, {} in use, {} at peak, {} allocations",
+        stats.total, stats.in_use, stats.peak, stats.allocations
+    );
+}
+
+/// Check that the bytes in use come back to where they were after a large allocation is freed
+pub fn heap_stats_test() {
+    use alloc::vec::Vec;
+    let before = kernel_heap_stats();
+    let len = KERNEL_HEAP_SIZE / 8;
+    let v: Vec<u8> = Vec::with_capacity(len);
+    let during = kernel_heap_stats();
+    assert_eq!(during.in_use, before.in_use + len);
+    assert!(during.peak >= during.in_use);
+    assert_eq!(during.allocations, before.allocations + 1);
+    drop(v);
+    assert_eq!(kernel_heap_stats().in_use, before.in_use);
+    info!("heap_stats_test passed!");
+}
+
 #[allow(unused)]
 pub fn heap_test() {
     use alloc::boxed::Box;
@@ -3,12 +3,16 @@
 use super::{frame_alloc, FrameTracker};
 use super::{PTEFlags, PageTable, PageTableEntry};
 use super::{PhysAddr, PhysPageNum, VirtAddr, VirtPageNum};
-use super::{StepByOne, VPNRange};
-use crate::config::{MEMORY_END, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE};
+use super::{ShmSegment, StepByOne, VPNRange};
+use crate::config::{
+    DENY_WX, MEMORY_END, MMAP_BASE, MMAP_TOP, MMIO, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT,
+    USER_SPACE_END, USER_STACK_GROWTH, USER_STACK_LIMIT, USER_STACK_SIZE,
+};
 use crate::sync::UPSafeCell;
 use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::convert::TryFrom;
 use lazy_static::*;
 use riscv::register::satp;
 
@@ -25,16 +29,190 @@ extern "C" {
     fn strampoline();
 }
 
+/// [`MemorySet::mmap`] 的返回值：参数不合法
+pub const MMAP_EINVAL: isize = -1;
+/// [`MemorySet::mmap`] 的返回值：物理内存或地址空间不足
+pub const MMAP_ENOMEM: isize = -12;
+
 lazy_static! {
     /// a memory set instance through lazy_static! managing kernel space
     pub static ref KERNEL_SPACE: Arc<UPSafeCell<MemorySet>> =
         Arc::new(unsafe { UPSafeCell::new(MemorySet::new_kernel()) });
+    /// The frame mapped read-only at the lazy pages read before being written,
+    /// never freed nor written as this reference is kept forever
+    static ref ZERO_FRAME: Arc<FrameTracker> = Arc::new(frame_alloc().unwrap());
+}
+
+/// Whether `frame` is the shared zero frame rather than a frame of its own
+fn is_zero_frame(frame: &Arc<FrameTracker>) -> bool {
+    Arc::ptr_eq(frame, &ZERO_FRAME)
+}
+
+/// Errors of building or changing a memory set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryError {
+    /// No physical frame is left
+    OutOfMemory,
+    /// A user pointer is not mapped with the permission required
+    BadAddress,
+    /// The image is not an ELF file, or is truncated
+    BadElf,
+    /// The ELF file is not a 64-bit RISC-V one
+    WrongArch,
+    /// A segment of the ELF file lies outside the room of the program below `MMAP_BASE`
+    BadSegment,
+    /// A segment of the ELF file is both writable and executable, see [`DENY_WX`]
+    WritableExec,
+    /// Two segments of the ELF file share a page
+    OverlappingSegments,
+    /// The entry point of the ELF file is not in an executable segment
+    BadEntry,
+}
+
+/// `e_ident[EI_CLASS]` of a 64-bit ELF file
+const ELFCLASS64: u8 = 2;
+/// Offset of `e_machine` in the ELF header
+const E_MACHINE_OFFSET: usize = 18;
+/// `e_machine` of RISC-V
+const EM_RISCV: u16 = 243;
+/// Size of a 64-bit program header
+const PH_ENTRY_SIZE: usize = 56;
+
+/// A loadable segment of an ELF file
+struct Segment<'a> {
+    start: VirtAddr,
+    end: VirtAddr,
+    perm: MapPermission,
+    /// The bytes copied to the start of the segment, the rest is zeroed
+    data: &'a [u8],
+}
+
+/// The non-empty loadable segments of `elf` sorted by address, checked before anything is
+/// mapped: no two of them share a page, they leave room below `MMAP_BASE` for the user
+/// stack and the heap, and the entry point lies in an executable one
+fn elf_segments<'a>(elf: &xmas_elf::ElfFile<'a>) -> Result<Vec<Segment<'a>>, MemoryError> {
+    let mut segments = Vec::new();
+    for i in 0..elf.header.pt2.ph_count() {
+        let ph = elf.program_header(i).map_err(|_| MemoryError::BadElf)?;
+        if ph.get_type().map_err(|_| MemoryError::BadElf)? != xmas_elf::program::Type::Load
+            || ph.mem_size() == 0
+        {
+            continue;
+        }
+        let start = ph.virtual_addr() as usize;
+        let end = match start.checked_add(ph.mem_size() as usize) {
+            Some(end) if end <= MMAP_BASE => end,
+            _ => return Err(MemoryError::BadSegment),
+        };
+        if ph.file_size() > ph.mem_size() {
+            return Err(MemoryError::BadElf);
+        }
+        let data_start = ph.offset() as usize;
+        let data = match data_start
+            .checked_add(ph.file_size() as usize)
+            .and_then(|data_end| elf.input.get(data_start..data_end))
+        {
+            Some(data) => data,
+            None => return Err(MemoryError::BadElf),
+        };
+        let mut perm = MapPermission::U;
+        let ph_flags = ph.flags();
+        if ph_flags.is_read() {
+            perm |= MapPermission::R;
+        }
+        if ph_flags.is_write() {
+            perm |= MapPermission::W;
+        }
+        if ph_flags.is_execute() {
+            perm |= MapPermission::X;
+        }
+        if DENY_WX && perm.contains(MapPermission::W | MapPermission::X) {
+            return Err(MemoryError::WritableExec);
+        }
+        segments.push(Segment {
+            start: start.into(),
+            end: end.into(),
+            perm,
+            data,
+        });
+    }
+    segments.sort_by_key(|segment| segment.start);
+    if segments
+        .windows(2)
+        .any(|pair| pair[0].end.ceil() > pair[1].start.floor())
+    {
+        return Err(MemoryError::OverlappingSegments);
+    }
+    // the guard page, the room of the stack and an empty heap follow the last segment
+    if let Some(last) = segments.last() {
+        let end: VirtAddr = last.end.ceil().into();
+        if usize::from(end) + PAGE_SIZE + USER_STACK_LIMIT > MMAP_BASE {
+            return Err(MemoryError::BadSegment);
+        }
+    }
+    let entry = VirtAddr::from(elf.header.pt2.entry_point() as usize);
+    if !segments.iter().any(|segment| {
+        segment.perm.contains(MapPermission::X) && segment.start <= entry && entry < segment.end
+    }) {
+        return Err(MemoryError::BadEntry);
+    }
+    Ok(segments)
+}
+
+/// Parse `elf_data` as a 64-bit RISC-V ELF file whose program headers all lie in it
+fn parse_elf(elf_data: &[u8]) -> Result<xmas_elf::ElfFile, MemoryError> {
+    if elf_data.len() < 4 || elf_data[..4] != [0x7f, 0x45, 0x4c, 0x46] {
+        return Err(MemoryError::BadElf);
+    }
+    if elf_data.get(4) != Some(&ELFCLASS64) {
+        return Err(MemoryError::WrongArch);
+    }
+    let elf = xmas_elf::ElfFile::new(elf_data).map_err(|_| MemoryError::BadElf)?;
+    let machine = u16::from_le_bytes([elf_data[E_MACHINE_OFFSET], elf_data[E_MACHINE_OFFSET + 1]]);
+    if machine != EM_RISCV {
+        return Err(MemoryError::WrongArch);
+    }
+    // xmas_elf slices the program headers out of the input without checking
+    let pt2 = &elf.header.pt2;
+    let ph_size = pt2.ph_entry_size() as usize;
+    let ph_end = (pt2.ph_count() as usize)
+        .checked_mul(ph_size)
+        .and_then(|size| size.checked_add(pt2.ph_offset() as usize));
+    if ph_size != PH_ENTRY_SIZE || !matches!(ph_end, Some(end) if end <= elf_data.len()) {
+        return Err(MemoryError::BadElf);
+    }
+    Ok(elf)
+}
+
+/// End of `[start, start + len)`, or None if it wraps around or leaves the user space
+fn user_range_end(start: usize, len: usize) -> Option<usize> {
+    start.checked_add(len).filter(|&end| end <= USER_SPACE_END)
+}
+
+/// Successful return value of [`MemorySet::mmap`] for a mapping at `addr` requested at `start`
+#[cfg(not(feature = "mmap_compat"))]
+fn mmap_result(_start: usize, addr: usize) -> isize {
+    addr as isize
+}
+
+/// Successful return value of [`MemorySet::mmap`], 0 unless the kernel picked `addr` itself
+#[cfg(feature = "mmap_compat")]
+fn mmap_result(start: usize, addr: usize) -> isize {
+    if start == 0 {
+        addr as isize
+    } else {
+        0
+    }
 }
 
 /// memory set structure, controls virtual-memory space
 pub struct MemorySet {
     page_table: PageTable,
     areas: Vec<MapArea>,
+    /// The unmapped page right below the lowest address the user stack may grow to
+    stack_guard: Option<VirtPageNum>,
+    /// End of the user stack, which grows down from here
+    stack_top: Option<VirtPageNum>,
 }
 
 impl MemorySet {
@@ -42,7 +220,26 @@ impl MemorySet {
         Self {
             page_table: PageTable::new(),
             areas: Vec::new(),
+            stack_guard: None,
+            stack_top: None,
+        }
+    }
+    /// A user space with only the trampoline mapped, or an error if no frame is left
+    fn new_user() -> Result<Self, MemoryError> {
+        let mut memory_set = Self {
+            page_table: PageTable::try_new().ok_or(MemoryError::OutOfMemory)?,
+            areas: Vec::new(),
+            stack_guard: None,
+            stack_top: None,
+        };
+        if !memory_set.page_table.try_map(
+            VirtAddr::from(TRAMPOLINE).into(),
+            PhysAddr::from(strampoline as usize).into(),
+            PTEFlags::R | PTEFlags::X,
+        ) {
+            return Err(MemoryError::OutOfMemory);
         }
+        Ok(memory_set)
     }
     pub fn token(&self) -> usize {
         self.page_table.token()
@@ -53,11 +250,295 @@ impl MemorySet {
         start_va: VirtAddr,
         end_va: VirtAddr,
         permission: MapPermission,
-    ) {
-        self.push(
+    ) -> Result<(), MemoryError> {
+        self.try_push(
             MapArea::new(start_va, end_va, MapType::Framed, permission),
             None,
-        );
+        )
+    }
+    /// Like `insert_framed_area`, but frames are only allocated on the first access.
+    /// The new pages join the lazy areas right before or after them with the same permission.
+    pub fn insert_lazy_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) {
+        let mut map_area = MapArea::new(start_va, end_va, MapType::Framed, permission);
+        map_area.lazy = true;
+        let (start, end) = (map_area.vpn_range.get_start(), map_area.vpn_range.get_end());
+        if let Some(i) = self
+            .areas
+            .iter()
+            .position(|area| area.can_merge(&map_area) && area.vpn_range.get_start() == end)
+        {
+            let next = self.areas.swap_remove(i);
+            map_area.append(next);
+        }
+        match self
+            .areas
+            .iter_mut()
+            .find(|area| area.can_merge(&map_area) && area.vpn_range.get_end() == start)
+        {
+            Some(prev) => prev.append(map_area),
+            None => self.push(map_area, None),
+        }
+    }
+    /// Number of areas in the memory set
+    pub fn area_count(&self) -> usize {
+        self.areas.len()
+    }
+    /// Number of pages in the areas mapped by mmap, touched or not
+    pub fn mmap_pages(&self) -> usize {
+        self.areas
+            .iter()
+            .filter(|area| area.lazy)
+            .map(|area| area.vpn_range.get_end().0 - area.vpn_range.get_start().0)
+            .sum()
+    }
+    /// Number of frames mapped, including those shared with other memory sets
+    pub fn resident_pages(&self) -> usize {
+        self.areas.iter().map(|area| area.own_frames()).sum()
+    }
+    /// Number of pages in lazy areas which have not been written yet,
+    /// those read so far are mapped to the zero frame
+    pub fn lazy_pages(&self) -> usize {
+        self.areas
+            .iter()
+            .filter(|area| area.lazy)
+            .map(|area| {
+                area.vpn_range.get_end().0 - area.vpn_range.get_start().0 - area.own_frames()
+            })
+            .sum()
+    }
+    /// Number of frames mapped which are also mapped by another memory set
+    /// (copy-on-write after fork) or kept by a shared memory segment
+    pub fn shared_pages(&self) -> usize {
+        self.areas
+            .iter()
+            .flat_map(|area| area.data_frames.values())
+            .filter(|frame| Arc::strong_count(frame) > 1 && !is_zero_frame(frame))
+            .count()
+    }
+    /// Whether `vpn` is mapped, or belongs to a lazy area and will be on access
+    pub fn is_mapped(&self, vpn: VirtPageNum) -> bool {
+        if let Some(pte) = self.page_table.translate(vpn) {
+            if pte.is_valid() {
+                return true;
+            }
+        }
+        self.areas
+            .iter()
+            .any(|area| area.lazy && area.contains(vpn))
+    }
+    /// Whether `vpn` is the guard page below the user stack
+    pub fn is_stack_guard(&self, vpn: VirtPageNum) -> bool {
+        self.stack_guard == Some(vpn)
+    }
+    /// Start of the user stack as grown so far
+    fn stack_bottom(&self) -> Option<VirtPageNum> {
+        let top = self.stack_top?;
+        self.areas
+            .iter()
+            .map(|area| area.vpn_range)
+            .find(|range| range.get_end() == top && range.get_start() < top)
+            .map(|range| range.get_start())
+    }
+    /// Whether `vpn` is an unmapped page between the guard page and the user stack,
+    /// that is a fault there is a stack overflow the stack could not grow for
+    pub fn is_below_stack(&self, vpn: VirtPageNum) -> bool {
+        match (self.stack_guard, self.stack_bottom()) {
+            (Some(guard), Some(bottom)) => guard <= vpn && vpn < bottom && !self.is_mapped(vpn),
+            _ => false,
+        }
+    }
+    /// Grow the user stack down to `vpn` if it lies less than `USER_STACK_GROWTH` below it
+    /// and the stack stays within `max_pages`, return false if it does not, the pages in
+    /// between are taken or no frame is left
+    pub fn grow_stack(&mut self, vpn: VirtPageNum, max_pages: usize) -> bool {
+        let (guard, bottom, top) = match (self.stack_guard, self.stack_bottom(), self.stack_top) {
+            (Some(guard), Some(bottom), Some(top)) => (guard, bottom, top),
+            _ => return false,
+        };
+        let limit = VirtPageNum(bottom.0.saturating_sub(USER_STACK_GROWTH / PAGE_SIZE));
+        if vpn <= guard || vpn < limit || vpn >= bottom || top.0 - vpn.0 > max_pages {
+            return false;
+        }
+        if VPNRange::new(vpn, bottom)
+            .into_iter()
+            .any(|vpn| self.is_mapped(vpn))
+        {
+            return false;
+        }
+        let page_table = &mut self.page_table;
+        let grown = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start() == bottom)
+            .unwrap()
+            .prepend_to(page_table, vpn)
+            .is_ok();
+        grown
+    }
+    /// The `n` user areas nearest to `va` as `(start, end, permission)`, the closest first
+    pub fn nearest_areas(
+        &self,
+        va: VirtAddr,
+        n: usize,
+    ) -> Vec<(VirtAddr, VirtAddr, MapPermission)> {
+        let vpn = va.floor();
+        let mut areas: Vec<(VirtPageNum, VirtPageNum, MapPermission)> = self
+            .areas
+            .iter()
+            .filter(|area| area.map_perm.contains(MapPermission::U))
+            .map(|area| {
+                (
+                    area.vpn_range.get_start(),
+                    area.vpn_range.get_end(),
+                    area.map_perm,
+                )
+            })
+            .filter(|(start, end, _)| start < end)
+            .collect();
+        // pages between the area and the page of `va`, 0 if it is inside
+        areas.sort_unstable_by_key(|&(start, end, _)| {
+            if vpn < start {
+                start.0 - vpn.0
+            } else if vpn >= end {
+                vpn.0 + 1 - end.0
+            } else {
+                0
+            }
+        });
+        areas
+            .into_iter()
+            .take(n)
+            .map(|(start, end, perm)| (start.into(), end.into(), perm))
+            .collect()
+    }
+    /// Find the lowest free range of `len` bytes in the mmap window
+    pub fn find_free_area(&self, len: usize) -> Option<VirtAddr> {
+        let pages = VirtAddr::from(len).ceil().0;
+        let mut ranges: Vec<(VirtPageNum, VirtPageNum)> = self
+            .areas
+            .iter()
+            .map(|area| (area.vpn_range.get_start(), area.vpn_range.get_end()))
+            .filter(|(start, end)| start < end)
+            .collect();
+        ranges.sort_unstable_by_key(|(start, _)| *start);
+        let mut base = VirtAddr::from(MMAP_BASE).floor();
+        for (start, end) in ranges {
+            if end <= base {
+                continue;
+            }
+            if start.0 >= base.0 + pages {
+                break;
+            }
+            base = end;
+        }
+        if base.0 + pages > VirtAddr::from(MMAP_TOP).floor().0 {
+            return None;
+        }
+        Some(base.into())
+    }
+    /// Give `vpn` a private frame if it is a copy-on-write page, including a lazy page
+    /// mapped to the zero frame, return false if it is not such a page or no frame is left
+    pub fn handle_cow_fault(&mut self, vpn: VirtPageNum) -> bool {
+        match self.page_table.translate(vpn) {
+            Some(pte) if pte.is_valid() && pte.is_cow() => {}
+            _ => return false,
+        }
+        let page_table = &mut self.page_table;
+        let area = match self.areas.iter_mut().find(|area| area.contains(vpn)) {
+            Some(area) => area,
+            None => return false,
+        };
+        let flags = PTEFlags::from_bits(area.map_perm.bits as u16).unwrap();
+        let frame = area.data_frames.get(&vpn).unwrap();
+        if Arc::strong_count(frame) == 1 {
+            // the other owners are gone, simply take the frame back
+            page_table.set_flags(vpn, flags);
+        } else {
+            let new_frame = match frame_alloc() {
+                Some(frame) => frame,
+                None => return false,
+            };
+            // a new frame is zeroed already, a copy of the zero frame would change nothing
+            if !is_zero_frame(frame) {
+                new_frame
+                    .ppn
+                    .get_bytes_array()
+                    .copy_from_slice(frame.ppn.get_bytes_array());
+            }
+            // the page table needs no new frame for a page mapped already
+            page_table.unmap(vpn);
+            page_table.map(vpn, new_frame.ppn, flags);
+            // dropping our reference to the shared frame
+            area.data_frames.insert(vpn, Arc::new(new_frame));
+        }
+        true
+    }
+    /// Do what the page faults would do before the kernel accesses `[start_va, end_va)`:
+    /// map the lazy pages and, if `write`, break the copy-on-write sharing.
+    /// Return false if no frame is left.
+    pub fn populate(&mut self, start_va: VirtAddr, end_va: VirtAddr, write: bool) -> bool {
+        let (start, end) = (start_va.floor(), end_va.ceil());
+        let mut lazy_pages = Vec::new();
+        let mut cow_pages = Vec::new();
+        // only walk the pages of the areas, the range itself may be huge
+        for area in self.areas.iter() {
+            let from = start.max(area.vpn_range.get_start());
+            let to = end.min(area.vpn_range.get_end());
+            if from >= to {
+                continue;
+            }
+            if area.lazy {
+                lazy_pages.extend(
+                    VPNRange::new(from, to)
+                        .into_iter()
+                        .filter(|vpn| !area.data_frames.contains_key(vpn)),
+                );
+            }
+            if write && area.map_perm.contains(MapPermission::W) {
+                cow_pages.extend(area.data_frames.range(from..to).map(|(vpn, _)| *vpn));
+            }
+        }
+        if !lazy_pages
+            .into_iter()
+            .all(|vpn| self.handle_lazy_fault(vpn, write))
+        {
+            return false;
+        }
+        for vpn in cow_pages {
+            let is_cow = self
+                .page_table
+                .translate(vpn)
+                .map_or(false, |pte| pte.is_cow());
+            if is_cow && !self.handle_cow_fault(vpn) {
+                return false;
+            }
+        }
+        true
+    }
+    /// Allocate the frame of a lazy page on its first access, or map it to the zero frame
+    /// if the access is not a `write`. Return false if `vpn` is not such a page or no frame is left
+    pub fn handle_lazy_fault(&mut self, vpn: VirtPageNum, write: bool) -> bool {
+        let page_table = &mut self.page_table;
+        match self
+            .areas
+            .iter_mut()
+            .find(|area| area.lazy && area.contains(vpn))
+        {
+            Some(area) if !area.data_frames.contains_key(&vpn) => {
+                let mapped = if write {
+                    area.map_one(page_table, vpn)
+                } else {
+                    area.map_zero(page_table, vpn)
+                };
+                mapped.is_ok()
+            }
+            _ => false,
+        }
     }
     pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
         if let Some((idx, area)) = self
@@ -70,12 +551,17 @@ impl MemorySet {
             self.areas.remove(idx);
         }
     }
-    fn push(&mut self, mut map_area: MapArea, data: Option<&[u8]>) {
-        map_area.map(&mut self.page_table);
+    fn push(&mut self, map_area: MapArea, data: Option<&[u8]>) {
+        self.try_push(map_area, data).unwrap();
+    }
+    /// Map and add `map_area`, nothing is left mapped if no frame is left
+    fn try_push(&mut self, mut map_area: MapArea, data: Option<&[u8]>) -> Result<(), MemoryError> {
+        map_area.map(&mut self.page_table)?;
         if let Some(data) = data {
             map_area.copy_data(&mut self.page_table, data);
         }
         self.areas.push(map_area);
+        Ok(())
     }
     /// Mention that trampoline is not collected by areas.
     fn map_trampoline(&mut self) {
@@ -148,52 +634,48 @@ impl MemorySet {
             ),
             None,
         );
+        info!("mapping memory-mapped registers");
+        for &(start, len) in MMIO {
+            memory_set.push(
+                MapArea::new(
+                    start.into(),
+                    (start + len).into(),
+                    MapType::Identical,
+                    MapPermission::R | MapPermission::W,
+                ),
+                None,
+            );
+        }
         memory_set
     }
     /// Include sections in elf and trampoline and TrapContext and user stack,
     /// also returns user_sp and entry point.
-    pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
-        let mut memory_set = Self::new_bare();
-        // map trampoline
-        memory_set.map_trampoline();
+    pub fn from_elf(elf_data: &[u8]) -> Result<(Self, usize, usize), MemoryError> {
+        let elf = parse_elf(elf_data)?;
+        let segments = elf_segments(&elf)?;
         // map program headers of elf, with U flag
-        let elf = xmas_elf::ElfFile::new(elf_data).unwrap();
-        let elf_header = elf.header;
-        let magic = elf_header.pt1.magic;
-        assert_eq!(magic, [0x7f, 0x45, 0x4c, 0x46], "invalid elf!");
-        let ph_count = elf_header.pt2.ph_count();
-        let mut max_end_vpn = VirtPageNum(0);
-        for i in 0..ph_count {
-            let ph = elf.program_header(i).unwrap();
-            if ph.get_type().unwrap() == xmas_elf::program::Type::Load {
-                let start_va: VirtAddr = (ph.virtual_addr() as usize).into();
-                let end_va: VirtAddr = ((ph.virtual_addr() + ph.mem_size()) as usize).into();
-                let mut map_perm = MapPermission::U;
-                let ph_flags = ph.flags();
-                if ph_flags.is_read() {
-                    map_perm |= MapPermission::R;
-                }
-                if ph_flags.is_write() {
-                    map_perm |= MapPermission::W;
-                }
-                if ph_flags.is_execute() {
-                    map_perm |= MapPermission::X;
-                }
-                let map_area = MapArea::new(start_va, end_va, MapType::Framed, map_perm);
-                max_end_vpn = map_area.vpn_range.get_end();
-                memory_set.push(
-                    map_area,
-                    Some(&elf.input[ph.offset() as usize..(ph.offset() + ph.file_size()) as usize]),
-                );
-            }
+        let mut memory_set = Self::new_user()?;
+        for segment in segments.iter() {
+            // the frames mapped so far are freed with `memory_set` if no frame is left
+            memory_set.try_push(
+                MapArea::new(segment.start, segment.end, MapType::Framed, segment.perm),
+                Some(segment.data),
+            )?;
         }
-        // map user stack with U flags
+        // map user stack with U flags, the segments are sorted
+        let max_end_vpn = segments
+            .last()
+            .map_or(VirtPageNum(0), |segment| segment.end.ceil());
         let max_end_va: VirtAddr = max_end_vpn.into();
         let mut user_stack_bottom: usize = max_end_va.into();
-        // guard page
+        // guard page, left unmapped so that a stack overflow faults there
+        memory_set.stack_guard = Some(VirtAddr::from(user_stack_bottom).floor());
         user_stack_bottom += PAGE_SIZE;
+        // room for the stack to grow down into
+        user_stack_bottom += USER_STACK_LIMIT - USER_STACK_SIZE;
         let user_stack_top = user_stack_bottom + USER_STACK_SIZE;
-        memory_set.push(
+        memory_set.stack_top = Some(VirtAddr::from(user_stack_top).floor());
+        memory_set.try_push(
             MapArea::new(
                 user_stack_bottom.into(),
                 user_stack_top.into(),
@@ -201,9 +683,19 @@ impl MemorySet {
                 MapPermission::R | MapPermission::W | MapPermission::U,
             ),
             None,
+        )?;
+        // an empty heap right above the user stack, grown by sbrk
+        memory_set.push(
+            MapArea::new(
+                user_stack_top.into(),
+                user_stack_top.into(),
+                MapType::Framed,
+                MapPermission::R | MapPermission::W | MapPermission::U,
+            ),
+            None,
         );
         // map TrapContext
-        memory_set.push(
+        memory_set.try_push(
             MapArea::new(
                 TRAP_CONTEXT.into(),
                 TRAMPOLINE.into(),
@@ -211,22 +703,38 @@ impl MemorySet {
                 MapPermission::R | MapPermission::W,
             ),
             None,
-        );
-        (
+        )?;
+        Ok((
             memory_set,
             user_stack_top,
             elf.header.pt2.entry_point() as usize,
-        )
+        ))
     }
-    /// Copy an identical user_space
-    pub fn from_existed_user(user_space: &MemorySet) -> MemorySet {
-        let mut memory_set = Self::new_bare();
-        // map trampoline
-        memory_set.map_trampoline();
-        // copy data sections/trap_context/user_stack
+    /// Copy an identical user_space.
+    /// User pages are shared with `user_space`, the writable ones copy-on-write.
+    pub fn from_existed_user(user_space: &mut MemorySet) -> Result<MemorySet, MemoryError> {
+        let mut memory_set = Self::new_user()?;
+        memory_set.stack_guard = user_space.stack_guard;
+        memory_set.stack_top = user_space.stack_top;
+        // share data sections/user_stack, copy trap_context
         for area in user_space.areas.iter() {
-            let new_area = MapArea::from_another(area);
-            memory_set.push(new_area, None);
+            let mut new_area = MapArea::from_another(area);
+            if area.map_perm.contains(MapPermission::U) {
+                // the frames are shared through their reference count: writable pages become
+                // copy-on-write, read-only ones such as text and rodata are never copied at all.
+                // Only the pages of lazy areas touched so far have frames
+                for (vpn, frame) in area.data_frames.iter() {
+                    new_area.data_frames.insert(*vpn, frame.clone());
+                    let flags = area.pte_flags(*vpn);
+                    if !memory_set.page_table.try_map(*vpn, frame.ppn, flags) {
+                        return Err(MemoryError::OutOfMemory);
+                    }
+                    user_space.page_table.set_flags(*vpn, flags);
+                }
+                memory_set.areas.push(new_area);
+                continue;
+            }
+            memory_set.try_push(new_area, None)?;
             // copy data from another space
             for vpn in area.vpn_range {
                 let src_ppn = user_space.translate(vpn).unwrap().ppn();
@@ -236,7 +744,7 @@ impl MemorySet {
                     .copy_from_slice(src_ppn.get_bytes_array());
             }
         }
-        memory_set
+        Ok(memory_set)
     }
     pub fn activate(&self) {
         let satp = self.page_table.token();
@@ -248,25 +756,317 @@ impl MemorySet {
     pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
         self.page_table.translate(vpn)
     }
+    /// Release the frames of the user pages, keeping the kernel ones like the trap context
+    pub fn release_user_pages(&mut self) {
+        for area in self
+            .areas
+            .iter_mut()
+            .filter(|area| area.map_perm.contains(MapPermission::U))
+        {
+            area.unmap(&mut self.page_table);
+        }
+        self.areas
+            .retain(|area| !area.map_perm.contains(MapPermission::U));
+    }
     pub fn recycle_data_pages(&mut self) {
         //*self = Self::new_bare();
         self.areas.clear();
     }
-    pub fn munmap(&mut self, vpn: VirtPageNum) {
-        for area in &mut self.areas {
-            if vpn < area.vpn_range.get_end() && vpn >= area.vpn_range.get_start() {
-                area.unmap_one(&mut self.page_table, vpn);
+    /// Shrink the area starting at `start` so that it ends at `new_end`
+    pub fn shrink_to(&mut self, start: VirtAddr, new_end: VirtAddr) -> bool {
+        if let Some(area) = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start() == start.floor())
+        {
+            area.shrink_to(&mut self.page_table, new_end.ceil());
+            true
+        } else {
+            false
+        }
+    }
+    /// Grow the area starting at `start` so that it ends at `new_end`,
+    /// fail if any new page is mapped already or no frame is left
+    pub fn append_to(&mut self, start: VirtAddr, new_end: VirtAddr) -> bool {
+        let new_end = new_end.ceil();
+        let old_end = match self
+            .areas
+            .iter()
+            .find(|area| area.vpn_range.get_start() == start.floor())
+        {
+            Some(area) => area.vpn_range.get_end(),
+            None => return false,
+        };
+        if new_end <= old_end {
+            return true;
+        }
+        if VPNRange::new(old_end, new_end)
+            .into_iter()
+            .any(|vpn| self.is_mapped(vpn))
+        {
+            return false;
+        }
+        let page_table = &mut self.page_table;
+        self.areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start() == start.floor())
+            .unwrap()
+            .append_to(page_table, new_end)
+            .is_ok()
+    }
+    /// Whether every page in `vpn_range` belongs to a user area
+    fn is_user_range(&self, vpn_range: VPNRange) -> bool {
+        vpn_range.into_iter().all(|vpn| {
+            self.areas
+                .iter()
+                .any(|area| area.map_perm.contains(MapPermission::U) && area.contains(vpn))
+        })
+    }
+    /// Whether every page in `vpn_range` belongs to an area created by mmap
+    fn is_mmap_range(&self, vpn_range: VPNRange) -> bool {
+        vpn_range.into_iter().all(|vpn| {
+            self.areas
+                .iter()
+                .any(|area| area.lazy && area.contains(vpn))
+        })
+    }
+    /// Split the areas crossing `start` or `end`, so that every area lies
+    /// either inside or outside `[start, end)`
+    fn split_areas(&mut self, start: VirtPageNum, end: VirtPageNum) {
+        let areas = core::mem::take(&mut self.areas);
+        for mut area in areas {
+            if area.contains(start) && area.vpn_range.get_start() < start {
+                let rest = area.split_off(start);
+                self.areas.push(area);
+                area = rest;
+            }
+            if area.contains(end) && area.vpn_range.get_start() < end {
+                let rest = area.split_off(end);
+                self.areas.push(rest);
+            }
+            self.areas.push(area);
+        }
+    }
+    /// Change the permission of the user pages in `vpn_range`, splitting the areas
+    /// partially covered. Fail if any page is not mapped by a user area or lies
+    /// in the user stack, which has to stay one area to grow.
+    fn protect_range(&mut self, vpn_range: VPNRange, perm: MapPermission) -> bool {
+        if !self.is_user_range(vpn_range) {
+            return false;
+        }
+        let (start, end) = (vpn_range.get_start(), vpn_range.get_end());
+        if let (Some(bottom), Some(top)) = (self.stack_bottom(), self.stack_top) {
+            if start < top && bottom < end {
+                return false;
+            }
+        }
+        self.split_areas(start, end);
+        for area in self.areas.iter_mut() {
+            let (area_start, area_end) = (area.vpn_range.get_start(), area.vpn_range.get_end());
+            if area_start < start || area_end > end || area_start == area_end {
+                continue;
+            }
+            area.map_perm = perm;
+            for vpn in area.data_frames.keys() {
+                self.page_table.set_flags(*vpn, area.pte_flags(*vpn));
             }
         }
+        true
+    }
+    /// Unmap the pages in `vpn_range`, shrinking or splitting the areas
+    /// partially covered. Fail if any page is not mapped by mmap, so that the
+    /// ELF segments, the heap and the user stack stay in place.
+    fn unmap_range(&mut self, vpn_range: VPNRange) -> bool {
+        if !self.is_mmap_range(vpn_range) {
+            return false;
+        }
+        let (start, end) = (vpn_range.get_start(), vpn_range.get_end());
+        self.split_areas(start, end);
+        let areas = core::mem::take(&mut self.areas);
+        for mut area in areas {
+            let (area_start, area_end) = (area.vpn_range.get_start(), area.vpn_range.get_end());
+            // keep empty areas such as the heap before the first sbrk
+            if area_start >= start && area_end <= end && area_start < area_end {
+                area.unmap(&mut self.page_table);
+            } else {
+                self.areas.push(area);
+            }
+        }
+        true
+    }
+    /// Map `[start, start + len)` with the permission `port`,
+    /// or a free range picked by the kernel if `start` is 0.
+    /// Return the start address of the mapping, [`MMAP_EINVAL`] or [`MMAP_ENOMEM`]
+    pub fn mmap(&mut self, start: usize, len: usize, port: usize) -> isize {
+        // start 需要映射的虚存起始地址，要求按页对齐
+        // len 映射字节长度，可以为 0
+        // port：第 0 位表示是否可读，第 1 位表示是否可写，第 2 位表示是否可执行。其他位无效且必须为 0
+        // start 为 0 时由内核选择空闲的区域
+        // 成功时返回映射的起始地址，失败时返回负的错误码
+        let start_va = VirtAddr::from(start);
+        // start 没有按页大小对齐
+        if start_va.page_offset() != 0 {
+            return MMAP_EINVAL;
+        }
+        // [start, start + len) 回绕或超出用户地址空间
+        if user_range_end(start, len).is_none() {
+            return MMAP_EINVAL;
+        }
+        let perm = match MapPermission::try_from(port) {
+            Ok(perm) => perm,
+            Err(_) => return MMAP_EINVAL,
+        };
+
+        // len为0, 直接返回成功
+        if len == 0 {
+            return mmap_result(start, start);
+        }
+
+        let start_va = if start == 0 {
+            match self.find_free_area(len) {
+                Some(start_va) => start_va,
+                None => return MMAP_ENOMEM,
+            }
+        } else {
+            start_va
+        };
+        let end_va = VirtAddr::from(usize::from(start_va) + len);
+        let vpn_start = start_va.floor();
+        let vpn_end = end_va.ceil();
+        let vpn_range = VPNRange::new(vpn_start, vpn_end);
+
+        // [start, start + len) 中存在已经被映射的页，或者覆盖了用户栈下方的保护页
+        if vpn_range
+            .into_iter()
+            .any(|vpn| self.is_mapped(vpn) || self.is_stack_guard(vpn))
+        {
+            return MMAP_EINVAL;
+        }
+        // 物理页帧在第一次访问时才分配，不预留，区域大小只受 RLIMIT_AS 限制，
+        // 缺页时物理页帧耗尽由 OOM killer 处理
+        self.insert_lazy_area(start_va, end_va, perm);
+        mmap_result(start, usize::from(start_va))
+    }
+
+    /// Change the permission of `[start, start + len)` to `port`
+    pub fn mprotect(&mut self, start: usize, len: usize, port: usize) -> isize {
+        let start_va = VirtAddr::from(start);
+        // start 没有按页大小对齐
+        if start_va.page_offset() != 0 {
+            return -1;
+        }
+        let end = match user_range_end(start, len) {
+            Some(end) => end,
+            None => return -1,
+        };
+        let perm = match MapPermission::try_from(port) {
+            Ok(perm) => perm,
+            Err(_) => return -1,
+        };
+        if len == 0 {
+            return 0;
+        }
+
+        let vpn_range = VPNRange::new(start_va.floor(), VirtAddr::from(end).ceil());
+        // [start, start + len) 中存在未被映射的虚存或者落在用户栈上时失败
+        if self.protect_range(vpn_range, perm) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    /// Unmap `[start, start + len)`
+    pub fn munmap(&mut self, start: usize, len: usize) -> isize {
+        let start_va = VirtAddr::from(start);
+        // start 没有按页大小对齐
+        if start_va.page_offset() != 0 {
+            return -1;
+        }
+        // [start, start + len) 回绕或超出用户地址空间
+        let end_va = match user_range_end(start, len) {
+            Some(end) => VirtAddr::from(end),
+            None => return -1,
+        };
+        // len为0, 直接返回成功
+        if len == 0 {
+            return 0;
+        }
+
+        let vpn_start = start_va.floor();
+        let vpn_end = end_va.ceil();
+        let vpn_range = VPNRange::new(vpn_start, vpn_end);
+
+        // [start, start + len) 中存在不是由 mmap 映射的虚存时失败，部分覆盖的区域会被拆分
+        if self.unmap_range(vpn_range) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    /// Map the frames of `segment` readable and writable at `hint`, or at a free range
+    /// picked by the kernel if `hint` is 0 or taken. Return the start of the mapping.
+    pub fn attach_shm(
+        &mut self,
+        hint: usize,
+        segment: Arc<ShmSegment>,
+    ) -> Result<VirtAddr, MemoryError> {
+        let len = segment.size();
+        let start_va = VirtAddr::from(hint);
+        let hint_free = hint != 0
+            && start_va.page_offset() == 0
+            && user_range_end(hint, len).is_some()
+            && VPNRange::new(start_va.floor(), VirtAddr::from(hint + len).ceil())
+                .into_iter()
+                .all(|vpn| !self.is_mapped(vpn) && !self.is_stack_guard(vpn));
+        let start_va = if hint_free {
+            start_va
+        } else {
+            self.find_free_area(len).ok_or(MemoryError::OutOfMemory)?
+        };
+        let end_va = VirtAddr::from(usize::from(start_va) + len);
+        let mut map_area = MapArea::new(
+            start_va,
+            end_va,
+            MapType::Shared,
+            MapPermission::R | MapPermission::W | MapPermission::U,
+        );
+        for (vpn, frame) in map_area.vpn_range.into_iter().zip(segment.frames.iter()) {
+            map_area.data_frames.insert(vpn, frame.clone());
+        }
+        map_area.shm = Some(segment);
+        self.try_push(map_area, None)?;
+        Ok(start_va)
+    }
+
+    /// Unmap the shared memory segment attached at `start`, the frames are kept
+    /// by the segment. Fail if no segment is attached there.
+    pub fn detach_shm(&mut self, start: usize) -> bool {
+        let start_vpn = VirtAddr::from(start).floor();
+        if VirtAddr::from(start).page_offset() != 0
+            || !self.areas.iter().any(|area| {
+                area.map_type == MapType::Shared && area.vpn_range.get_start() == start_vpn
+            })
+        {
+            return false;
+        }
+        self.remove_area_with_start_vpn(start_vpn);
+        true
     }
 }
 
 /// map area structure, controls a contiguous piece of virtual memory
 pub struct MapArea {
     vpn_range: VPNRange,
-    data_frames: BTreeMap<VirtPageNum, FrameTracker>,
+    /// Frames of the mapped pages, shared with other spaces after fork
+    data_frames: BTreeMap<VirtPageNum, Arc<FrameTracker>>,
     map_type: MapType,
     map_perm: MapPermission,
+    /// Frames are allocated on the first access of each page rather than by `map`
+    lazy: bool,
+    /// The segment whose frames a shared area maps, kept alive while attached
+    shm: Option<Arc<ShmSegment>>,
 }
 
 impl MapArea {
@@ -283,6 +1083,8 @@ impl MapArea {
             data_frames: BTreeMap::new(),
             map_type,
             map_perm,
+            lazy: false,
+            shm: None,
         }
     }
     pub fn from_another(another: &MapArea) -> Self {
@@ -291,44 +1093,191 @@ impl MapArea {
             data_frames: BTreeMap::new(),
             map_type: another.map_type,
             map_perm: another.map_perm,
+            lazy: another.lazy,
+            shm: another.shm.clone(),
         }
     }
-    pub fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+    pub fn contains(&self, vpn: VirtPageNum) -> bool {
+        vpn >= self.vpn_range.get_start() && vpn < self.vpn_range.get_end()
+    }
+    /// PTE flags of a mapped page, a shared writable frame is mapped copy-on-write
+    /// unless it belongs to a shared memory segment
+    fn pte_flags(&self, vpn: VirtPageNum) -> PTEFlags {
+        let flags = PTEFlags::from_bits(self.map_perm.bits as u16).unwrap();
+        match self.data_frames.get(&vpn) {
+            Some(frame)
+                if self.map_type != MapType::Shared
+                    && flags.contains(PTEFlags::W)
+                    && Arc::strong_count(frame) > 1 =>
+            {
+                (flags - PTEFlags::W) | PTEFlags::COW
+            }
+            _ => flags,
+        }
+    }
+    /// Map the lazy page `vpn` to the zero frame, copy-on-write if the area is writable,
+    /// nothing is mapped if no frame is left for the page table
+    fn map_zero(
+        &mut self,
+        page_table: &mut PageTable,
+        vpn: VirtPageNum,
+    ) -> Result<(), MemoryError> {
+        self.data_frames.insert(vpn, ZERO_FRAME.clone());
+        if !page_table.try_map(vpn, ZERO_FRAME.ppn, self.pte_flags(vpn)) {
+            self.data_frames.remove(&vpn);
+            return Err(MemoryError::OutOfMemory);
+        }
+        Ok(())
+    }
+    /// Number of frames mapped which are not the zero frame
+    fn own_frames(&self) -> usize {
+        self.data_frames
+            .values()
+            .filter(|frame| !is_zero_frame(frame))
+            .count()
+    }
+    /// Map `vpn`, nothing is mapped if no frame is left
+    pub fn map_one(
+        &mut self,
+        page_table: &mut PageTable,
+        vpn: VirtPageNum,
+    ) -> Result<(), MemoryError> {
         let ppn: PhysPageNum;
         match self.map_type {
             MapType::Identical => {
                 ppn = PhysPageNum(vpn.0);
             }
             MapType::Framed => {
-                let frame = frame_alloc().unwrap();
+                let frame = frame_alloc().ok_or(MemoryError::OutOfMemory)?;
                 ppn = frame.ppn;
-                self.data_frames.insert(vpn, frame);
+                self.data_frames.insert(vpn, Arc::new(frame));
             }
+            MapType::Shared => {
+                // the frames of the segment are put in place before mapping
+                ppn = self
+                    .data_frames
+                    .get(&vpn)
+                    .ok_or(MemoryError::BadAddress)?
+                    .ppn;
+            }
+        }
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits as u16).unwrap();
+        if !page_table.try_map(vpn, ppn, pte_flags) {
+            self.data_frames.remove(&vpn);
+            return Err(MemoryError::OutOfMemory);
         }
-        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
-        page_table.map(vpn, ppn, pte_flags);
+        Ok(())
     }
 
     pub fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
-        #[allow(clippy::single_match)]
         match self.map_type {
             MapType::Framed => {
+                // a lazy page never touched has nothing to unmap
+                if self.data_frames.remove(&vpn).is_none() && self.lazy {
+                    return;
+                }
+            }
+            MapType::Shared => {
+                // the frame stays with the segment
                 self.data_frames.remove(&vpn);
             }
-            _ => {}
+            MapType::Identical => {}
         }
         page_table.unmap(vpn);
     }
-    pub fn map(&mut self, page_table: &mut PageTable) {
+    /// Map all the pages, the ones mapped already are unmapped again if no frame is left
+    pub fn map(&mut self, page_table: &mut PageTable) -> Result<(), MemoryError> {
+        if self.lazy {
+            return Ok(());
+        }
         for vpn in self.vpn_range {
-            self.map_one(page_table, vpn);
+            if let Err(err) = self.map_one(page_table, vpn) {
+                for mapped in VPNRange::new(self.vpn_range.get_start(), vpn) {
+                    self.unmap_one(page_table, mapped);
+                }
+                return Err(err);
+            }
         }
+        Ok(())
     }
     pub fn unmap(&mut self, page_table: &mut PageTable) {
         for vpn in self.vpn_range {
             self.unmap_one(page_table, vpn);
         }
     }
+    /// Whether `other` may be merged into this area: both are lazy anonymous
+    /// mappings with the same permission
+    fn can_merge(&self, other: &MapArea) -> bool {
+        self.lazy
+            && other.lazy
+            && self.map_type == MapType::Framed
+            && other.map_type == MapType::Framed
+            && self.shm.is_none()
+            && other.shm.is_none()
+            && self.map_perm == other.map_perm
+    }
+    /// Take over `other`, which must start where this area ends
+    fn append(&mut self, mut other: MapArea) {
+        assert_eq!(self.vpn_range.get_end(), other.vpn_range.get_start());
+        self.data_frames.append(&mut other.data_frames);
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), other.vpn_range.get_end());
+    }
+    /// Split the area at `at`, keep `[start, at)` and return `[at, end)`
+    pub fn split_off(&mut self, at: VirtPageNum) -> MapArea {
+        let tail = MapArea {
+            vpn_range: VPNRange::new(at, self.vpn_range.get_end()),
+            data_frames: self.data_frames.split_off(&at),
+            map_type: self.map_type,
+            map_perm: self.map_perm,
+            lazy: self.lazy,
+            shm: self.shm.clone(),
+        };
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), at);
+        tail
+    }
+    /// Unmap the pages from `new_end` on
+    pub fn shrink_to(&mut self, page_table: &mut PageTable, new_end: VirtPageNum) {
+        for vpn in VPNRange::new(new_end, self.vpn_range.get_end()) {
+            self.unmap_one(page_table, vpn);
+        }
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), new_end);
+    }
+    /// Map the pages up to `new_end`, nothing changes if no frame is left
+    pub fn append_to(
+        &mut self,
+        page_table: &mut PageTable,
+        new_end: VirtPageNum,
+    ) -> Result<(), MemoryError> {
+        let old_end = self.vpn_range.get_end();
+        for vpn in VPNRange::new(old_end, new_end) {
+            if let Err(err) = self.map_one(page_table, vpn) {
+                for mapped in VPNRange::new(old_end, vpn) {
+                    self.unmap_one(page_table, mapped);
+                }
+                return Err(err);
+            }
+        }
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), new_end);
+        Ok(())
+    }
+    /// Map the pages from `new_start` on, nothing changes if no frame is left
+    pub fn prepend_to(
+        &mut self,
+        page_table: &mut PageTable,
+        new_start: VirtPageNum,
+    ) -> Result<(), MemoryError> {
+        let old_start = self.vpn_range.get_start();
+        for vpn in VPNRange::new(new_start, old_start) {
+            if let Err(err) = self.map_one(page_table, vpn) {
+                for mapped in VPNRange::new(new_start, vpn) {
+                    self.unmap_one(page_table, mapped);
+                }
+                return Err(err);
+            }
+        }
+        self.vpn_range = VPNRange::new(new_start, self.vpn_range.get_end());
+        Ok(())
+    }
     /// data: start-aligned but maybe with shorter length
     /// assume that all frames were cleared before
     pub fn copy_data(&mut self, page_table: &mut PageTable, data: &[u8]) {
@@ -354,10 +1303,11 @@ impl MapArea {
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
-/// map type for memory set: identical or framed
+/// map type for memory set: identical, framed or mapping a shared memory segment
 pub enum MapType {
     Identical,
     Framed,
+    Shared,
 }
 
 bitflags! {
@@ -370,6 +1320,35 @@ bitflags! {
     }
 }
 
+impl TryFrom<usize> for MapPermission {
+    type Error = ();
+
+    fn try_from(port: usize) -> Result<Self, Self::Error> {
+        // 第 0 位表示是否可读，第 1 位表示是否可写，第 2 位表示是否可执行。其他位无效且必须为 0
+        // port & !0x7 != 0 (port 其余位必须为0)
+        // port & 0x7 = 0 (这样的内存无意义)
+        // 可写但不可读 (RISC-V 保留了 W = 1 且 R = 0 的页表项)
+        if (port & !0x7) != 0 || (port & 0x7) == 0 || port & 0x3 == 0x2 {
+            return Err(());
+        }
+        // 既可写又可执行（除非打开了 allow_wx），mmap 与 mprotect 都拒绝而不是去掉 X
+        if DENY_WX && port & 0x6 == 0x6 {
+            return Err(());
+        }
+        let mut permission = MapPermission::U;
+        if port & 1 != 0 {
+            permission |= MapPermission::R;
+        }
+        if port & 2 != 0 {
+            permission |= MapPermission::W;
+        }
+        if port & 4 != 0 {
+            permission |= MapPermission::X;
+        }
+        Ok(permission)
+    }
+}
+
 #[allow(unused)]
 pub fn remap_test() {
     let mut kernel_space = KERNEL_SPACE.exclusive_access();
@@ -393,3 +1372,32 @@ pub fn remap_test() {
         .executable());
     info!("remap_test passed!");
 }
+
+/// Check that adjacent mmap areas are merged, and that unmapping and mapping
+/// again single pages does not leave more and more areas behind
+#[allow(unused)]
+pub fn mmap_coalesce_test() {
+    let mut memory_set = MemorySet::new_bare();
+    let pages = 256;
+    for i in 0..pages {
+        let start = MMAP_BASE + i * PAGE_SIZE;
+        assert_eq!(memory_set.mmap(start, PAGE_SIZE, 3), start as isize);
+    }
+    assert_eq!(memory_set.area_count(), 1);
+    for _ in 0..2 {
+        for i in (0..pages).step_by(2) {
+            assert_eq!(memory_set.munmap(MMAP_BASE + i * PAGE_SIZE, PAGE_SIZE), 0);
+        }
+        assert_eq!(memory_set.area_count(), pages / 2);
+        for i in (0..pages).step_by(2) {
+            let start = MMAP_BASE + i * PAGE_SIZE;
+            assert_eq!(memory_set.mmap(start, PAGE_SIZE, 3), start as isize);
+        }
+        assert_eq!(memory_set.area_count(), 1);
+    }
+    // areas with another permission stay apart
+    let start = MMAP_BASE + pages * PAGE_SIZE;
+    assert_eq!(memory_set.mmap(start, PAGE_SIZE, 1), start as isize);
+    assert_eq!(memory_set.area_count(), 2);
+    info!("mmap_coalesce_test passed!");
+}
@@ -6,23 +6,43 @@
 //!
 //! Every task or process has a memory_set to control its virtual memory.
 
-
 mod address;
 mod frame_allocator;
 mod heap_allocator;
 mod memory_set;
 mod page_table;
+mod shm;
+mod tlb;
 
 pub use address::*;
-pub use frame_allocator::{frame_alloc, FrameTracker};
-pub use memory_set::remap_test;
-pub use memory_set::{MapPermission, MemorySet, KERNEL_SPACE};
-pub use page_table::{translated_byte_buffer, translated_refmut, translated_str, PageTableEntry, convert_to_physical_addr};
+#[cfg(feature = "frame_double_free_test")]
+pub use frame_allocator::frame_double_free_test;
+pub use frame_allocator::{
+    frame_alloc, frame_free_count, frame_stats, print_frame_stats, FrameStats, FrameTracker,
+};
+pub use heap_allocator::{heap_stats_test, kernel_heap_stats, print_heap_stats, KernelHeapStats};
+pub use memory_set::{mmap_coalesce_test, remap_test};
+pub use memory_set::{MapPermission, MemoryError, MemorySet, KERNEL_SPACE};
+pub use page_table::{
+    copy_from_user, copy_to_user, translated_byte_buffer, translated_byte_buffer_mut,
+    translated_refmut, translated_str, PageTableEntry, UserBuffer,
+};
 use page_table::{PTEFlags, PageTable};
+pub use shm::{shm_get, shm_segment, ShmSegment};
+pub use tlb::flush_tlb_page;
+use tlb::{asid_alloc, AsidHandle};
 
 /// initiate heap allocator, frame allocator and kernel space
 pub fn init() {
     heap_allocator::init_heap();
     frame_allocator::init_frame_allocator();
     KERNEL_SPACE.exclusive_access().activate();
+    // the kernel space has taken ASID 0 already
+    tlb::init_asid();
+}
+
+/// Switch a secondary hart to the kernel space set up by [`init`]
+#[cfg(feature = "smp")]
+pub fn init_hart() {
+    KERNEL_SPACE.exclusive_access().activate();
 }
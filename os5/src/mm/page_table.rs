@@ -1,14 +1,18 @@
 //! Implementation of [`PageTableEntry`] and [`PageTable`].
 
-use super::{frame_alloc, FrameTracker, PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum};
+use super::{asid_alloc, flush_tlb_page, AsidHandle};
+use super::{
+    frame_alloc, FrameTracker, MemoryError, PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum,
+};
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use bitflags::*;
+use core::mem::{size_of, MaybeUninit};
 
 bitflags! {
     /// page table entry flags
-    pub struct PTEFlags: u8 {
+    pub struct PTEFlags: u16 {
         const V = 1 << 0;
         const R = 1 << 1;
         const W = 1 << 2;
@@ -17,6 +21,8 @@ bitflags! {
         const G = 1 << 5;
         const A = 1 << 6;
         const D = 1 << 7;
+        /// Software bit: a shared page to be copied on the first write
+        const COW = 1 << 8;
     }
 }
 
@@ -40,7 +46,7 @@ impl PageTableEntry {
         (self.bits >> 10 & ((1usize << 44) - 1)).into()
     }
     pub fn flags(&self) -> PTEFlags {
-        PTEFlags::from_bits(self.bits as u8).unwrap()
+        PTEFlags::from_bits_truncate(self.bits as u16)
     }
     pub fn is_valid(&self) -> bool {
         (self.flags() & PTEFlags::V) != PTEFlags::empty()
@@ -54,28 +60,38 @@ impl PageTableEntry {
     pub fn executable(&self) -> bool {
         (self.flags() & PTEFlags::X) != PTEFlags::empty()
     }
+    pub fn is_cow(&self) -> bool {
+        (self.flags() & PTEFlags::COW) != PTEFlags::empty()
+    }
 }
 
 /// page table structure
 pub struct PageTable {
     root_ppn: PhysPageNum,
     frames: Vec<FrameTracker>,
+    asid: AsidHandle,
 }
 
 /// Assume that it won't oom when creating/mapping.
 impl PageTable {
     pub fn new() -> Self {
-        let frame = frame_alloc().unwrap();
-        PageTable {
+        Self::try_new().unwrap()
+    }
+    /// Like `new`, but None if no frame is left for the root
+    pub fn try_new() -> Option<Self> {
+        let frame = frame_alloc()?;
+        Some(PageTable {
             root_ppn: frame.ppn,
             frames: vec![frame],
-        }
+            asid: asid_alloc(),
+        })
     }
     /// Temporarily used to get arguments from user space.
     pub fn from_token(satp: usize) -> Self {
         Self {
             root_ppn: PhysPageNum::from(satp & ((1usize << 44) - 1)),
             frames: Vec::new(),
+            asid: AsidHandle(0),
         }
     }
     fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
@@ -89,7 +105,7 @@ impl PageTable {
                 break;
             }
             if !pte.is_valid() {
-                let frame = frame_alloc().unwrap();
+                let frame = frame_alloc()?;
                 *pte = PageTableEntry::new(frame.ppn, PTEFlags::V);
                 self.frames.push(frame);
             }
@@ -116,15 +132,32 @@ impl PageTable {
     }
     #[allow(unused)]
     pub fn map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
-        let pte = self.find_pte_create(vpn).unwrap();
+        assert!(self.try_map(vpn, ppn, flags), "out of physical frames");
+    }
+    /// Map `vpn`, return false if no frame is left for the page table itself
+    pub fn try_map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) -> bool {
+        let pte = match self.find_pte_create(vpn) {
+            Some(pte) => pte,
+            None => return false,
+        };
         assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
         *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+        self.flush(vpn);
+        true
     }
     #[allow(unused)]
     pub fn unmap(&mut self, vpn: VirtPageNum) {
         let pte = self.find_pte_create(vpn).unwrap();
         assert!(pte.is_valid(), "vpn {:?} is invalid before unmapping", vpn);
         *pte = PageTableEntry::empty();
+        self.flush(vpn);
+    }
+    /// Replace the flags of a mapped page, keeping its frame
+    pub fn set_flags(&mut self, vpn: VirtPageNum, flags: PTEFlags) {
+        let pte = self.find_pte_create(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is invalid before remapping", vpn);
+        *pte = PageTableEntry::new(pte.ppn(), flags | PTEFlags::V);
+        self.flush(vpn);
     }
     pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
         self.find_pte(vpn).copied()
@@ -140,23 +173,35 @@ impl PageTable {
         })
     }
     pub fn token(&self) -> usize {
-        8usize << 60 | self.root_ppn.0
+        8usize << 60 | self.asid.satp_bits() | self.root_ppn.0
+    }
+    /// Flush the TLB entry of `vpn` after its PTE has changed
+    fn flush(&self, vpn: VirtPageNum) {
+        flush_tlb_page(vpn.into(), self.asid.0);
     }
 }
 
-/// translate a pointer to a mutable u8 Vec through page table
-pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&'static mut [u8]> {
+/// Translate the user buffer `[ptr, ptr + len)` page by page,
+/// fail if any page is not a user page with `flags`
+fn translated_user_buffer(
+    token: usize,
+    ptr: usize,
+    len: usize,
+    flags: PTEFlags,
+) -> Result<Vec<&'static mut [u8]>, MemoryError> {
     let page_table = PageTable::from_token(token);
-    let mut start = ptr as usize;
-    let end = start + len;
+    let mut start = ptr;
+    let end = ptr.checked_add(len).ok_or(MemoryError::BadAddress)?;
     let mut v = Vec::new();
     while start < end {
         let start_va = VirtAddr::from(start);
         let mut vpn = start_va.floor();
-        let ppn = page_table.translate(vpn).unwrap().ppn();
+        let ppn = match page_table.translate(vpn) {
+            Some(pte) if pte.is_valid() && pte.flags().contains(flags | PTEFlags::U) => pte.ppn(),
+            _ => return Err(MemoryError::BadAddress),
+        };
         vpn.step();
-        let mut end_va: VirtAddr = vpn.into();
-        end_va = end_va.min(VirtAddr::from(end));
+        let end_va = VirtAddr::from(vpn).min(VirtAddr::from(end));
         if end_va.page_offset() == 0 {
             v.push(&mut ppn.get_bytes_array()[start_va.page_offset()..]);
         } else {
@@ -164,26 +209,98 @@ pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&
         }
         start = end_va.into();
     }
-    v
+    Ok(v)
+}
+
+/// translate a pointer to a mutable u8 Vec through page table,
+/// the buffer must be readable by the user
+pub fn translated_byte_buffer(
+    token: usize,
+    ptr: *const u8,
+    len: usize,
+) -> Result<Vec<&'static mut [u8]>, MemoryError> {
+    translated_user_buffer(token, ptr as usize, len, PTEFlags::R)
+}
+
+/// Like `translated_byte_buffer`, but the buffer must be writable by the user
+pub fn translated_byte_buffer_mut(
+    token: usize,
+    ptr: *mut u8,
+    len: usize,
+) -> Result<Vec<&'static mut [u8]>, MemoryError> {
+    translated_user_buffer(token, ptr as usize, len, PTEFlags::W)
+}
+
+/// A user buffer, split into the pieces lying in different pages
+pub struct UserBuffer {
+    pub buffers: Vec<&'static mut [u8]>,
+}
+
+impl UserBuffer {
+    pub fn new(buffers: Vec<&'static mut [u8]>) -> Self {
+        Self { buffers }
+    }
+    /// Total length in bytes
+    pub fn len(&self) -> usize {
+        self.buffers.iter().map(|buffer| buffer.len()).sum()
+    }
+}
+
+/// Physical address of the user address `va`, which must be in a user page with `flags`
+fn translated_user_va(
+    page_table: &PageTable,
+    va: usize,
+    flags: PTEFlags,
+) -> Result<PhysAddr, MemoryError> {
+    match page_table.translate(VirtAddr::from(va).floor()) {
+        Some(pte) if pte.is_valid() && pte.flags().contains(flags | PTEFlags::U) => {
+            let aligned_pa: usize = PhysAddr::from(pte.ppn()).into();
+            Ok((aligned_pa + VirtAddr::from(va).page_offset()).into())
+        }
+        _ => Err(MemoryError::BadAddress),
+    }
+}
+
+/// Copy `val` to the user space of `token` page by page, `ptr` must be writable by the user
+pub fn copy_to_user<T: Copy>(token: usize, ptr: *mut T, val: &T) -> Result<(), MemoryError> {
+    let src = unsafe { core::slice::from_raw_parts(val as *const T as *const u8, size_of::<T>()) };
+    let mut copied = 0;
+    for buffer in translated_user_buffer(token, ptr as usize, size_of::<T>(), PTEFlags::W)? {
+        buffer.copy_from_slice(&src[copied..copied + buffer.len()]);
+        copied += buffer.len();
+    }
+    Ok(())
+}
+
+/// Copy a `T` from the user space of `token` page by page, `ptr` must be readable by the user
+pub fn copy_from_user<T: Copy>(token: usize, ptr: *const T) -> Result<T, MemoryError> {
+    let mut val = MaybeUninit::<T>::uninit();
+    let dst =
+        unsafe { core::slice::from_raw_parts_mut(val.as_mut_ptr() as *mut u8, size_of::<T>()) };
+    let mut copied = 0;
+    for buffer in translated_user_buffer(token, ptr as usize, size_of::<T>(), PTEFlags::R)? {
+        dst[copied..copied + buffer.len()].copy_from_slice(buffer);
+        copied += buffer.len();
+    }
+    Ok(unsafe { val.assume_init() })
 }
 
-pub fn translated_str(token: usize, ptr: *const u8) -> String {
+/// Read a nul-terminated string from user space, stopping at the first page which is
+/// not readable by the user
+pub fn translated_str(token: usize, ptr: *const u8) -> Result<String, MemoryError> {
     let page_table = PageTable::from_token(token);
     let mut string = String::new();
     let mut va = ptr as usize;
     loop {
-        let ch: u8 = *(page_table
-            .translate_va(VirtAddr::from(va))
-            .unwrap()
-            .get_mut());
+        let ch: u8 = *translated_user_va(&page_table, va, PTEFlags::R)?.get_ref();
         if ch == 0 {
             break;
         } else {
             string.push(ch as char);
-            va += 1;
+            va = va.checked_add(1).ok_or(MemoryError::BadAddress)?;
         }
     }
-    string
+    Ok(string)
 }
 
 pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
@@ -196,11 +313,3 @@ pub fn translated_refmut<T>(token: usize, ptr: *mut T) -> &'static mut T {
         .unwrap()
         .get_mut()
 }
-
-pub fn convert_to_physical_addr(token: usize, virt_addr: usize) -> usize {
-    let page_table = PageTable::from_token(token);
-    let va = VirtAddr::from(virt_addr);
-    let vpn = VirtPageNum::from(va.floor());
-    let ppn = page_table.find_pte(vpn).unwrap().ppn();
-    PhysAddr::from(ppn).0 + va.page_offset()
-}
@@ -0,0 +1,91 @@
+//! Shared memory segments
+//!
+//! A segment is a set of frames found by a key. It is kept alive by the
+//! process which created it and by every area it is attached to, the
+//! registry itself only holds weak references.
+
+use super::{frame_alloc, FrameTracker, MemoryError};
+use crate::config::PAGE_SIZE;
+use crate::sync::UPSafeCell;
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+/// A key which always creates a new segment
+pub const IPC_PRIVATE: usize = 0;
+
+/// Frames shared by the processes attaching the same segment
+pub struct ShmSegment {
+    key: usize,
+    pub frames: Vec<Arc<FrameTracker>>,
+}
+
+impl ShmSegment {
+    /// Size of the segment in bytes
+    pub fn size(&self) -> usize {
+        self.frames.len() * PAGE_SIZE
+    }
+}
+
+struct ShmRegistry {
+    next_id: usize,
+    segments: BTreeMap<usize, Weak<ShmSegment>>,
+}
+
+lazy_static! {
+    static ref SHM_REGISTRY: UPSafeCell<ShmRegistry> = unsafe {
+        UPSafeCell::new(ShmRegistry {
+            next_id: 0,
+            segments: BTreeMap::new(),
+        })
+    };
+}
+
+/// Find the segment of `key` or create one of `size` bytes if there is none.
+///
+/// Return the id of the segment, with the reference kept by its creator if it is new.
+/// Fail if the segment found is smaller than `size`, or a new one would be empty.
+pub fn shm_get(key: usize, size: usize) -> Result<(usize, Option<Arc<ShmSegment>>), MemoryError> {
+    let mut registry = SHM_REGISTRY.exclusive_access();
+    // forget the segments freed since
+    registry
+        .segments
+        .retain(|_, segment| segment.strong_count() > 0);
+    if key != IPC_PRIVATE {
+        let found = registry.segments.iter().find_map(|(&id, segment)| {
+            segment
+                .upgrade()
+                .filter(|segment| segment.key == key)
+                .map(|segment| (id, segment))
+        });
+        if let Some((id, segment)) = found {
+            if size > segment.size() {
+                return Err(MemoryError::BadAddress);
+            }
+            return Ok((id, None));
+        }
+    }
+    let pages = (size + PAGE_SIZE - 1) / PAGE_SIZE;
+    if pages == 0 {
+        return Err(MemoryError::BadAddress);
+    }
+    let mut frames = Vec::with_capacity(pages);
+    for _ in 0..pages {
+        frames.push(Arc::new(frame_alloc().ok_or(MemoryError::OutOfMemory)?));
+    }
+    let segment = Arc::new(ShmSegment { key, frames });
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.segments.insert(id, Arc::downgrade(&segment));
+    Ok((id, Some(segment)))
+}
+
+/// The live segment with id `shmid`
+pub fn shm_segment(shmid: usize) -> Option<Arc<ShmSegment>> {
+    SHM_REGISTRY
+        .exclusive_access()
+        .segments
+        .get(&shmid)
+        .and_then(|segment| segment.upgrade())
+}
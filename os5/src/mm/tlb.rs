@@ -0,0 +1,155 @@
+//! ASIDs and TLB maintenance
+//!
+//! Every user address space gets an ASID of its own, so that the TLB entries of
+//! different spaces, and of the kernel which keeps ASID 0, live side by side and
+//! switching `satp` between them needs no flush. Without ASIDs in the hardware,
+//! or once they run out, a space gets ASID 0 and the trampoline flushes the whole
+//! TLB on every switch as before. A PTE change only flushes the entry of its
+//! page under the ASID of the table, see [`flush_tlb_page`].
+//!
+//! With the `smp` feature ASIDs are not used: a space may run on another hart
+//! later, whose TLB a flush on this hart does not reach, so every switch flushes.
+
+use super::VirtAddr;
+use crate::sync::UPSafeCell;
+use crate::timer::get_time;
+use alloc::vec::Vec;
+use lazy_static::*;
+use riscv::register::satp;
+
+/// Position of the ASID field in `satp`
+const ASID_SHIFT: usize = 44;
+/// Width of the ASID field in `satp` for Sv39, of which the hardware may implement less
+const ASID_FIELD_BITS: usize = 16;
+/// Number of `satp` writes timed at boot
+const SWITCH_ROUNDS: usize = 1000;
+
+/// ASID allocator handing out `1..=max`, `max` is 0 until [`init_asid`] finds the ASID bits
+struct AsidAllocator {
+    max: usize,
+    current: usize,
+    recycled: Vec<usize>,
+}
+
+impl AsidAllocator {
+    fn new() -> Self {
+        Self {
+            max: 0,
+            current: 1,
+            recycled: Vec::new(),
+        }
+    }
+    /// Take a free ASID, or 0 if there is none
+    fn alloc(&mut self) -> usize {
+        if let Some(asid) = self.recycled.pop() {
+            asid
+        } else if self.current <= self.max {
+            self.current += 1;
+            self.current - 1
+        } else {
+            0
+        }
+    }
+    fn dealloc(&mut self, asid: usize) {
+        if asid != 0 {
+            self.recycled.push(asid);
+        }
+    }
+}
+
+lazy_static! {
+    static ref ASID_ALLOCATOR: UPSafeCell<AsidAllocator> =
+        unsafe { UPSafeCell::new(AsidAllocator::new()) };
+}
+
+/// ASID of a page table, 0 is shared by the kernel and the spaces without one of their own
+pub struct AsidHandle(pub usize);
+
+impl AsidHandle {
+    /// The ASID field of `satp`
+    pub fn satp_bits(&self) -> usize {
+        self.0 << ASID_SHIFT
+    }
+}
+
+impl Drop for AsidHandle {
+    fn drop(&mut self) {
+        ASID_ALLOCATOR.exclusive_access().dealloc(self.0);
+    }
+}
+
+/// Allocate an ASID for a new address space, flushing what an address space
+/// gone may have left in the TLB under it
+pub fn asid_alloc() -> AsidHandle {
+    let asid = ASID_ALLOCATOR.exclusive_access().alloc();
+    if asid != 0 {
+        unsafe {
+            core::arch::asm!("sfence.vma zero, {}", in(reg) asid);
+        }
+    }
+    AsidHandle(asid)
+}
+
+/// Flush the whole TLB, every ASID included
+pub fn flush_tlb_all() {
+    unsafe {
+        core::arch::asm!("sfence.vma");
+    }
+}
+
+/// Flush the entries of the page at `va` tagged with `asid`,
+/// or of all address spaces if `asid` is 0
+pub fn flush_tlb_page(va: VirtAddr, asid: usize) {
+    let va: usize = va.into();
+    unsafe {
+        if asid == 0 {
+            core::arch::asm!("sfence.vma {}, zero", in(reg) va);
+        } else {
+            core::arch::asm!("sfence.vma {}, {}", in(reg) va, in(reg) asid);
+        }
+    }
+}
+
+/// `mtime` cycles taken by `SWITCH_ROUNDS` writes of the current `satp`,
+/// each followed by a full flush if `flush`
+fn satp_switch_cost(flush: bool) -> usize {
+    let token = satp::read().bits();
+    let start = get_time();
+    for _ in 0..SWITCH_ROUNDS {
+        unsafe {
+            satp::write(token);
+        }
+        if flush {
+            flush_tlb_all();
+        }
+    }
+    get_time() - start
+}
+
+/// Find how many ASID bits the hart implements by writing all ones to the field
+/// of the kernel `satp`, and report what a switch costs with and without flushing
+pub fn init_asid() {
+    let token = satp::read().bits();
+    let all_ones = ((1 << ASID_FIELD_BITS) - 1) << ASID_SHIFT;
+    unsafe {
+        satp::write(token | all_ones);
+    }
+    let bits = ((satp::read().bits() & all_ones) >> ASID_SHIFT).count_ones() as usize;
+    unsafe {
+        satp::write(token);
+    }
+    flush_tlb_all();
+    let bits = if cfg!(feature = "smp") { 0 } else { bits };
+    ASID_ALLOCATOR.exclusive_access().max = (1 << bits) - 1;
+    if bits == 0 {
+        info!("[kernel] no ASIDs, the TLB is flushed on every switch");
+    } else {
+        info!("[kernel] {} ASID bits", bits);
+    }
+    info!(
+        "[kernel] {} satp switches: {} cycles with a full flush, {} without",
+        SWITCH_ROUNDS,
+        satp_switch_cost(true),
+        satp_switch_cost(false)
+    );
+}
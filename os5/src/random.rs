@@ -0,0 +1,129 @@
+//! Random bytes for getrandom, drawn from a ChaCha20 keystream
+//!
+//! The key is seeded at boot from `mtime` and from memory nothing has written since
+//! power-on. Each draw ends by replacing the key with a fresh block, so that the bytes
+//! handed out cannot be recovered from the state later, and `mtime` is mixed into the
+//! key again every [`RESEED_BLOCKS`] blocks.
+
+use crate::config::{MEMORY_END, PAGE_SIZE};
+use crate::sync::UPSafeCell;
+use crate::timer::get_time;
+use core::convert::TryInto;
+use lazy_static::*;
+
+/// Upper bound of the bytes of one draw
+pub const MAX_DRAW: usize = 256;
+/// Blocks generated between two reseeds from `mtime`
+const RESEED_BLOCKS: usize = 1024;
+/// Words read from the memory past the kernel image at boot, one every `COLD_STRIDE` bytes
+const COLD_READS: usize = 16;
+const COLD_STRIDE: usize = 64 * PAGE_SIZE;
+/// "expand 32-byte k"
+const SIGMA: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// State of the generator, the nonce is always zero
+struct ChaCha20 {
+    key: [u32; 8],
+    counter: u64,
+    /// Blocks generated since `mtime` was last mixed in
+    since_reseed: usize,
+}
+
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+impl ChaCha20 {
+    /// The next 64 bytes of the keystream
+    fn block(&mut self) -> [u8; 64] {
+        let mut input = [0u32; 16];
+        input[..4].copy_from_slice(&SIGMA);
+        input[4..12].copy_from_slice(&self.key);
+        input[12] = self.counter as u32;
+        input[13] = (self.counter >> 32) as u32;
+        let mut x = input;
+        for _ in 0..10 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        let mut out = [0u8; 64];
+        for (i, word) in x.iter().enumerate() {
+            out[i * 4..i * 4 + 4].copy_from_slice(&word.wrapping_add(input[i]).to_le_bytes());
+        }
+        self.counter = self.counter.wrapping_add(1);
+        self.since_reseed += 1;
+        out
+    }
+    /// Replace the key with the first half of a fresh block
+    fn rekey(&mut self) {
+        let block = self.block();
+        for (i, word) in self.key.iter_mut().enumerate() {
+            *word = u32::from_le_bytes(block[i * 4..i * 4 + 4].try_into().unwrap());
+        }
+    }
+    /// Fold `value` into the key
+    fn mix(&mut self, value: usize) {
+        self.key[0] ^= value as u32;
+        self.key[1] ^= (value >> 32) as u32;
+        self.rekey();
+    }
+    fn fill(&mut self, buf: &mut [u8]) {
+        if self.since_reseed >= RESEED_BLOCKS {
+            self.since_reseed = 0;
+            self.mix(get_time());
+        }
+        for chunk in buf.chunks_mut(64) {
+            let block = self.block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.rekey();
+    }
+}
+
+lazy_static! {
+    static ref RNG: UPSafeCell<ChaCha20> = unsafe {
+        UPSafeCell::new(ChaCha20 {
+            key: [0; 8],
+            counter: 0,
+            since_reseed: 0,
+        })
+    };
+}
+
+/// Seed the generator, before the frame allocator hands out the memory it reads
+pub fn init() {
+    extern "C" {
+        fn ekernel();
+    }
+    let mut rng = RNG.exclusive_access();
+    rng.mix(get_time());
+    let start = (ekernel as usize + PAGE_SIZE - 1) / PAGE_SIZE * PAGE_SIZE;
+    for addr in (start..MEMORY_END).step_by(COLD_STRIDE).take(COLD_READS) {
+        // the frame allocator has not handed out this memory yet
+        rng.mix(unsafe { (addr as *const usize).read_volatile() });
+    }
+    rng.mix(get_time());
+}
+
+/// Fold `value` into the state, so that what is drawn next depends on it
+pub fn add_entropy(value: usize) {
+    RNG.exclusive_access().mix(value);
+}
+
+/// Fill `buf` with random bytes
+pub fn fill_random(buf: &mut [u8]) {
+    RNG.exclusive_access().fill(buf);
+}
@@ -6,6 +6,13 @@ const SBI_SET_TIMER: usize = 0;
 const SBI_CONSOLE_PUTCHAR: usize = 1;
 const SBI_CONSOLE_GETCHAR: usize = 2;
 const SBI_SHUTDOWN: usize = 8;
+/// Hart state management extension, whose function 0 is `hart_start`
+const SBI_EXT_HSM: usize = 0x48534D;
+/// System reset extension, whose function 0 is `system_reset`
+const SBI_EXT_SRST: usize = 0x53525354;
+const SRST_TYPE_SHUTDOWN: usize = 0;
+const SRST_REASON_NONE: usize = 0;
+const SRST_REASON_FAILURE: usize = 1;
 
 #[inline(always)]
 /// general sbi call
@@ -39,8 +46,21 @@ pub fn console_getchar() -> usize {
     sbi_call(SBI_CONSOLE_GETCHAR, 0, 0, 0)
 }
 
-/// use sbi call to shutdown the kernel
-pub fn shutdown() -> ! {
+/// use sbi call to shutdown the kernel, QEMU exits with a non-zero status on `failure`.
+/// The legacy call without a reason is the fallback if the firmware has no reset extension
+pub fn shutdown(failure: bool) -> ! {
+    let reason = if failure {
+        SRST_REASON_FAILURE
+    } else {
+        SRST_REASON_NONE
+    };
+    sbi_call(SBI_EXT_SRST, SRST_TYPE_SHUTDOWN, reason, 0);
     sbi_call(SBI_SHUTDOWN, 0, 0, 0);
     panic!("It should shutdown!");
 }
+
+/// use sbi call to start `hartid` in S mode at the physical address `start_addr`,
+/// with `a0` set to its id and `a1` to `opaque`; 0 on success, a negative SBI error otherwise
+pub fn hart_start(hartid: usize, start_addr: usize, opaque: usize) -> isize {
+    sbi_call(SBI_EXT_HSM, hartid, start_addr, opaque) as isize
+}
@@ -0,0 +1,29 @@
+//! The big kernel lock
+//!
+//! With the `smp` feature, a hart holds the lock whenever it runs kernel code: it is
+//! taken at the trap entry and given back right before returning to user space, and
+//! the idle loop lets go of it only while waiting for an interrupt. The data behind
+//! [`super::UPSafeCell`] is thus still touched by a single hart at a time.
+//!
+//! A task may take the lock on one hart and give it back on another after being
+//! switched, so the lock has no guard. Without `smp` both calls do nothing.
+
+#[cfg(feature = "smp")]
+use super::SpinLock;
+
+#[cfg(feature = "smp")]
+static BIG_KERNEL_LOCK: SpinLock<()> = SpinLock::new(());
+
+/// Wait for the other harts to leave the kernel
+pub fn big_kernel_lock() {
+    #[cfg(feature = "smp")]
+    core::mem::forget(BIG_KERNEL_LOCK.lock());
+}
+
+/// Let the other harts into the kernel, the lock must be held by this hart
+pub fn big_kernel_unlock() {
+    #[cfg(feature = "smp")]
+    unsafe {
+        BIG_KERNEL_LOCK.force_unlock();
+    }
+}
@@ -1,5 +1,11 @@
 //! Synchronization and interior mutability primitives
 
+mod bkl;
+mod spinlock;
 mod up;
 
+pub use bkl::{big_kernel_lock, big_kernel_unlock};
+pub use spinlock::{SpinLock, SpinLockGuard, SpinLockIrqSave, SpinLockIrqSaveGuard};
+#[cfg(feature = "upsafecell_double_borrow_test")]
+pub use up::double_borrow_test;
 pub use up::UPSafeCell;
@@ -0,0 +1,138 @@
+//! Ticket spinlocks
+//!
+//! A ticket lock hands the lock over in the order the harts asked for it.
+//! Taking a ticket is a single AMO, `amoadd.w`/`amoadd.d`, and releasing is a plain
+//! increment of the ticket served, so an uncontended lock costs about as much as
+//! the `RefCell` check of [`super::UPSafeCell`].
+//!
+//! The locks are not reentrant, taking a lock twice on the same hart spins forever.
+//! They are not poisoned either: the console takes no lock and the panic handler only
+//! reads atomic counters, so a panic while a lock is held still gets printed.
+
+use core::cell::UnsafeCell;
+use core::hint::spin_loop;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use riscv::register::sstatus;
+
+/// Mutual exclusion between harts, granted in FIFO order
+pub struct SpinLock<T> {
+    /// Next ticket to hand out
+    next: AtomicUsize,
+    /// Ticket allowed to hold the lock
+    serving: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+unsafe impl<T: Send> Send for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            serving: AtomicUsize::new(0),
+            data: UnsafeCell::new(value),
+        }
+    }
+    /// Wait for our turn and take the lock, it is released when the guard is dropped
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        let ticket = self.next.fetch_add(1, Ordering::Relaxed);
+        while self.serving.load(Ordering::Acquire) != ticket {
+            spin_loop();
+        }
+        SpinLockGuard { lock: self }
+    }
+    /// Release the lock held through a guard that was forgotten
+    ///
+    /// # Safety
+    ///
+    /// The lock must be held, and the guard must never be dropped.
+    pub unsafe fn force_unlock(&self) {
+        // only the holder writes `serving`
+        let next = self.serving.load(Ordering::Relaxed) + 1;
+        self.serving.store(next, Ordering::Release);
+    }
+}
+
+/// Access to the data of a held [`SpinLock`]
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.lock.force_unlock();
+        }
+    }
+}
+
+/// A [`SpinLock`] held with the interrupts of the hart disabled,
+/// for data also touched from trap handlers
+pub struct SpinLockIrqSave<T>(SpinLock<T>);
+
+impl<T> SpinLockIrqSave<T> {
+    pub const fn new(value: T) -> Self {
+        Self(SpinLock::new(value))
+    }
+    /// Disable interrupts and take the lock, both are given back when the guard is dropped
+    pub fn lock(&self) -> SpinLockIrqSaveGuard<'_, T> {
+        let sie = sstatus::read().sie();
+        unsafe {
+            sstatus::clear_sie();
+        }
+        SpinLockIrqSaveGuard {
+            guard: ManuallyDrop::new(self.0.lock()),
+            sie,
+        }
+    }
+}
+
+/// Access to the data of a held [`SpinLockIrqSave`]
+pub struct SpinLockIrqSaveGuard<'a, T> {
+    guard: ManuallyDrop<SpinLockGuard<'a, T>>,
+    /// Whether interrupts were enabled before taking the lock
+    sie: bool,
+}
+
+impl<T> Deref for SpinLockIrqSaveGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for SpinLockIrqSaveGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for SpinLockIrqSaveGuard<'_, T> {
+    fn drop(&mut self) {
+        // release the lock before an interrupt may come in and want it
+        unsafe {
+            ManuallyDrop::drop(&mut self.guard);
+        }
+        if self.sie {
+            unsafe {
+                sstatus::set_sie();
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 //! Uniprocessor interior mutability primitives
 
+#[cfg(feature = "debug_upsafecell")]
+use crate::task::current_pid;
+#[cfg(feature = "debug_upsafecell")]
+use core::cell::Cell;
 use core::cell::{RefCell, RefMut};
+#[cfg(feature = "debug_upsafecell")]
+use core::panic::Location;
 
 /// Wrap a static data structure inside it so that we are
 /// able to access it without any `unsafe`.
@@ -9,9 +15,15 @@ use core::cell::{RefCell, RefMut};
 ///
 /// In order to get mutable reference of inner data, call
 /// `exclusive_access`.
+///
+/// With the `debug_upsafecell` feature, a conflicting borrow panics with
+/// the places of both borrows.
 pub struct UPSafeCell<T> {
     /// inner data
     inner: RefCell<T>,
+    /// Where the data was last borrowed, which is where the live borrow comes from if any
+    #[cfg(feature = "debug_upsafecell")]
+    borrowed_at: Cell<Option<&'static Location<'static>>>,
 }
 
 unsafe impl<T> Sync for UPSafeCell<T> {}
@@ -22,10 +34,60 @@ impl<T> UPSafeCell<T> {
     pub unsafe fn new(value: T) -> Self {
         Self {
             inner: RefCell::new(value),
+            #[cfg(feature = "debug_upsafecell")]
+            borrowed_at: Cell::new(None),
         }
     }
     /// Panic if the data has been borrowed.
+    #[cfg(not(feature = "debug_upsafecell"))]
     pub fn exclusive_access(&self) -> RefMut<'_, T> {
         self.inner.borrow_mut()
     }
+    /// Panic if the data has been borrowed, naming where it was.
+    #[cfg(feature = "debug_upsafecell")]
+    #[track_caller]
+    pub fn exclusive_access(&self) -> RefMut<'_, T> {
+        let caller = Location::caller();
+        match self.inner.try_borrow_mut() {
+            Ok(inner) => {
+                self.borrowed_at.set(Some(caller));
+                inner
+            }
+            Err(_) => match self.borrowed_at.get() {
+                Some(holder) => panic!(
+                    "UPSafeCell borrowed at {} while already borrowed at {}, pid = {:?}",
+                    caller,
+                    holder,
+                    current_pid()
+                ),
+                None => panic!(
+                    "UPSafeCell borrowed at {} while already borrowed, pid = {:?}",
+                    caller,
+                    current_pid()
+                ),
+            },
+        }
+    }
+    /// Like `exclusive_access`, but None instead of a panic if the data has been borrowed.
+    #[cfg_attr(feature = "debug_upsafecell", track_caller)]
+    pub fn try_exclusive_access(&self) -> Option<RefMut<'_, T>> {
+        let inner = self.inner.try_borrow_mut().ok()?;
+        #[cfg(feature = "debug_upsafecell")]
+        self.borrowed_at.set(Some(Location::caller()));
+        Some(inner)
+    }
+    /// Whether the data is borrowed now
+    pub fn is_borrowed(&self) -> bool {
+        self.inner.try_borrow_mut().is_err()
+    }
+}
+
+/// Borrow a cell twice on purpose, the kernel must panic naming both borrows
+#[cfg(feature = "upsafecell_double_borrow_test")]
+pub fn double_borrow_test() {
+    let cell = unsafe { UPSafeCell::new(0) };
+    let _first = cell.exclusive_access();
+    info!("double_borrow_test: borrowing a cell twice");
+    let _second = cell.exclusive_access();
+    panic!("double_borrow_test failed!");
 }
@@ -1,50 +1,204 @@
 //! File and filesystem-related syscalls
 
-use crate::mm::translated_byte_buffer;
-use crate::sbi::console_getchar;
-use crate::task::{current_user_token, suspend_current_and_run_next};
+use super::write_to_user;
+use crate::fs::{make_pipe, File};
+use crate::logging::{clear_log, log_len, read_log, LOG_RING_SIZE};
+use crate::mm::{copy_from_user, translated_byte_buffer, translated_byte_buffer_mut, UserBuffer};
+use crate::task::{current_task, current_user_token, populate_current_user};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
 
-const FD_STDIN: usize = 0;
-const FD_STDOUT: usize = 1;
+/// Upper bound of the bytes written by one writev
+const WRITEV_MAX: usize = 4096;
+/// Upper bound of the buffers gathered by one writev
+const IOV_MAX: usize = 64;
 
+/// syslog actions, numbered as on Linux
+const SYSLOG_ACTION_READ_ALL: usize = 3;
+const SYSLOG_ACTION_READ_CLEAR: usize = 4;
+const SYSLOG_ACTION_CLEAR: usize = 5;
+const SYSLOG_ACTION_SIZE_UNREAD: usize = 9;
+const SYSLOG_ACTION_SIZE_BUFFER: usize = 10;
+
+/// A buffer in user space, gathered by writev
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct IoVec {
+    pub base: usize,
+    pub len: usize,
+}
+
+/// The object behind `fd` of the current task.
+///
+/// The TCB is released before the object is used,
+/// as reading or writing a pipe may wait for another task.
+fn current_file(fd: usize) -> Option<Arc<dyn File>> {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    inner.fd_table.get(fd).cloned().flatten()
+}
+
+/// Write `len` bytes at `buf` to `fd`, -1 if `fd` is closed or not writable
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
-    match fd {
-        FD_STDOUT => {
-            let buffers = translated_byte_buffer(current_user_token(), buf, len);
-            for buffer in buffers {
-                print!("{}", core::str::from_utf8(buffer).unwrap());
-            }
-            len as isize
+    let file = match current_file(fd) {
+        Some(file) => file,
+        None => return -1,
+    };
+    if !file.writable() {
+        return -1;
+    }
+    if !populate_current_user(buf as usize, len, false) {
+        return -1;
+    }
+    match translated_byte_buffer(current_user_token(), buf, len) {
+        Ok(buffers) => file.write(UserBuffer::new(buffers)),
+        Err(_) => -1,
+    }
+}
+
+/// Read up to `len` bytes from `fd` into `buf`, -1 if `fd` is closed or not readable
+pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
+    let file = match current_file(fd) {
+        Some(file) => file,
+        None => return -1,
+    };
+    if !file.readable() {
+        return -1;
+    }
+    if !populate_current_user(buf as usize, len, true) {
+        return -1;
+    }
+    match translated_byte_buffer_mut(current_user_token(), buf as *mut u8, len) {
+        Ok(buffers) => file.read(UserBuffer::new(buffers)),
+        Err(_) => -1,
+    }
+}
+
+/// Write the `iovcnt` buffers described at `iov` to `fd` with a single write,
+/// which keeps the output to the console from being interleaved.
+/// At most `WRITEV_MAX` bytes are written, the number of which is returned.
+/// Every buffer is checked first, so nothing is written if any of them is bad.
+pub fn sys_writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> isize {
+    let file = match current_file(fd) {
+        Some(file) => file,
+        None => return -1,
+    };
+    if !file.writable() || iovcnt > IOV_MAX {
+        return -1;
+    }
+    if !populate_current_user(iov as usize, iovcnt * size_of::<IoVec>(), false) {
+        return -1;
+    }
+    let token = current_user_token();
+    let mut buffers = Vec::new();
+    let mut total = 0;
+    for i in 0..iovcnt {
+        let iovec = match copy_from_user(token, iov.wrapping_add(i)) {
+            Ok(iovec) => iovec,
+            Err(_) => return -1,
+        };
+        let len = iovec.len.min(WRITEV_MAX - total);
+        if !populate_current_user(iovec.base, len, false) {
+            return -1;
         }
-        _ => {
-            panic!("Unsupported fd in sys_write!");
+        match translated_byte_buffer(token, iovec.base as *const u8, len) {
+            Ok(buffer) => buffers.extend(buffer),
+            Err(_) => return -1,
         }
+        total += len;
     }
+    file.write(UserBuffer::new(buffers))
 }
 
-pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
-    match fd {
-        FD_STDIN => {
-            assert_eq!(len, 1, "Only support len = 1 in sys_read!");
-            let mut c: usize;
-            loop {
-                c = console_getchar();
-                if c == 0 {
-                    suspend_current_and_run_next();
-                    continue;
-                } else {
-                    break;
-                }
+/// Close `fd`, -1 if it is not open.
+/// The object goes away with the last descriptor, so closing a write end can end a pipe.
+pub fn sys_close(fd: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    match inner.fd_table.get_mut(fd).and_then(|file| file.take()) {
+        Some(_) => 0,
+        None => -1,
+    }
+}
+
+/// Make the lowest closed descriptor refer to the object behind `fd` and return it,
+/// -1 if `fd` is not open or there is no descriptor left
+pub fn sys_dup(fd: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let file = match inner.fd_table.get(fd) {
+        Some(Some(file)) => file.clone(),
+        _ => return -1,
+    };
+    match inner.alloc_fd() {
+        Some(new_fd) => {
+            inner.fd_table[new_fd] = Some(file);
+            new_fd as isize
+        }
+        None => -1,
+    }
+}
+
+/// Create a pipe and store the descriptors of its read end and write end into `pipe`
+pub fn sys_pipe(pipe: *mut [u32; 2]) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let (pipe_read, pipe_write) = make_pipe();
+    let read_fd = match inner.alloc_fd() {
+        Some(fd) => fd,
+        None => return -1,
+    };
+    inner.fd_table[read_fd] = Some(pipe_read);
+    let write_fd = match inner.alloc_fd() {
+        Some(fd) => fd,
+        None => {
+            inner.fd_table[read_fd] = None;
+            return -1;
+        }
+    };
+    inner.fd_table[write_fd] = Some(pipe_write);
+    let token = inner.get_user_token();
+    drop(inner);
+    if write_to_user(token, pipe, &[read_fd as u32, write_fd as u32]).is_err() {
+        let mut inner = task.inner_exclusive_access();
+        inner.fd_table[read_fd] = None;
+        inner.fd_table[write_fd] = None;
+        return -1;
+    }
+    0
+}
+
+/// Read the kernel log ring: `READ_ALL` copies the newest lines that fit in `len` bytes
+/// into `buf`, `READ_CLEAR` empties the ring too, `CLEAR` only empties it, `SIZE_UNREAD`
+/// and `SIZE_BUFFER` return the bytes in the ring and its size
+pub fn sys_syslog(action: usize, buf: *mut u8, len: usize) -> isize {
+    match action {
+        SYSLOG_ACTION_READ_ALL | SYSLOG_ACTION_READ_CLEAR => {
+            if !populate_current_user(buf as usize, len, true) {
+                return -1;
             }
-            let ch = c as u8;
-            let mut buffers = translated_byte_buffer(current_user_token(), buf, len);
-            unsafe {
-                buffers[0].as_mut_ptr().write_volatile(ch);
+            let buffers = match translated_byte_buffer_mut(current_user_token(), buf, len) {
+                Ok(buffers) => buffers,
+                Err(_) => return -1,
+            };
+            let mut data = vec![0; len.min(LOG_RING_SIZE)];
+            let n = read_log(&mut data, action == SYSLOG_ACTION_READ_CLEAR);
+            let mut copied = 0;
+            for buffer in buffers {
+                let count = buffer.len().min(n - copied);
+                buffer[..count].copy_from_slice(&data[copied..copied + count]);
+                copied += count;
             }
-            1
+            n as isize
         }
-        _ => {
-            panic!("Unsupported fd in sys_read!");
+        SYSLOG_ACTION_CLEAR => {
+            clear_log();
+            0
         }
+        SYSLOG_ACTION_SIZE_UNREAD => log_len() as isize,
+        SYSLOG_ACTION_SIZE_BUFFER => LOG_RING_SIZE as isize,
+        _ => -1,
     }
 }
@@ -10,44 +10,361 @@
 //! `sys_` then the name of the syscall. You can find functions like this in
 //! submodules, and you should also implement syscalls this way.
 
+const SYSCALL_DUP: usize = 24;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_PIPE: usize = 59;
 const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_WRITEV: usize = 66;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_NANOSLEEP: usize = 115;
 const SYSCALL_YIELD: usize = 124;
+const SYSCALL_KILL: usize = 129;
+const SYSCALL_SIGACTION: usize = 134;
+const SYSCALL_SIGPROCMASK: usize = 135;
+const SYSCALL_SIGRETURN: usize = 139;
+const SYSCALL_TIMES: usize = 153;
 const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_SETTIMEOFDAY: usize = 170;
+const SYSCALL_CLOCK_GETTIME: usize = 113;
 const SYSCALL_GETPID: usize = 172;
+const SYSCALL_GETPPID: usize = 173;
+const SYSCALL_SYSINFO: usize = 179;
+const SYSCALL_SHMGET: usize = 194;
+const SYSCALL_SHMAT: usize = 196;
+const SYSCALL_SHMDT: usize = 197;
 const SYSCALL_FORK: usize = 220;
 const SYSCALL_EXEC: usize = 221;
 const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_PRLIMIT: usize = 261;
 const SYSCALL_SPAWN: usize = 400;
+const SYSCALL_SBRK: usize = 214;
 const SYSCALL_MUNMAP: usize = 215;
 const SYSCALL_MMAP: usize = 222;
+const SYSCALL_MPROTECT: usize = 226;
 const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_PRCTL: usize = 167;
 const SYSCALL_TASK_INFO: usize = 410;
+const SYSCALL_HOLD_TASK: usize = 411;
+const SYSCALL_ALARM: usize = 412;
+const SYSCALL_TASK_STATS: usize = 413;
+const SYSCALL_SCHED_QUEUE: usize = 414;
+const SYSCALL_SCHED_SETQUANTUM: usize = 415;
+const SYSCALL_SCHED_GETQUANTUM: usize = 416;
+const SYSCALL_SCHED_STAT: usize = 417;
+const SYSCALL_MEM_STATS: usize = 418;
+const SYSCALL_STRACE: usize = 419;
+const SYSCALL_KLOGCTL: usize = 420;
+const SYSCALL_SYSLOG: usize = 116;
+const SYSCALL_PAUSE: usize = 421;
+const SYSCALL_GETRANDOM: usize = 278;
+const SYSCALL_UNAME: usize = 160;
+const SYSCALL_SHUTDOWN: usize = 422;
+const SYSCALL_EXIT_GROUP: usize = 94;
+const SYSCALL_SETPGID: usize = 154;
+const SYSCALL_GETPGID: usize = 155;
+const SYSCALL_WAIT4: usize = 423;
+const SYSCALL_GETRUSAGE: usize = 165;
+const SYSCALL_LIST_APPS: usize = 424;
+
+/// Ids of every syscall handled by [`syscall()`], task counters are indexed by the
+/// position of the id in this table
+pub const SYSCALL_IDS: [usize; 57] = [
+    SYSCALL_DUP,
+    SYSCALL_CLOSE,
+    SYSCALL_PIPE,
+    SYSCALL_READ,
+    SYSCALL_WRITE,
+    SYSCALL_WRITEV,
+    SYSCALL_EXIT,
+    SYSCALL_SLEEP,
+    SYSCALL_NANOSLEEP,
+    SYSCALL_YIELD,
+    SYSCALL_KILL,
+    SYSCALL_SIGACTION,
+    SYSCALL_SIGPROCMASK,
+    SYSCALL_SIGRETURN,
+    SYSCALL_TIMES,
+    SYSCALL_GET_TIME,
+    SYSCALL_SETTIMEOFDAY,
+    SYSCALL_CLOCK_GETTIME,
+    SYSCALL_GETPID,
+    SYSCALL_GETPPID,
+    SYSCALL_SYSINFO,
+    SYSCALL_SHMGET,
+    SYSCALL_SHMAT,
+    SYSCALL_SHMDT,
+    SYSCALL_FORK,
+    SYSCALL_EXEC,
+    SYSCALL_WAITPID,
+    SYSCALL_PRLIMIT,
+    SYSCALL_SPAWN,
+    SYSCALL_SBRK,
+    SYSCALL_MUNMAP,
+    SYSCALL_MMAP,
+    SYSCALL_MPROTECT,
+    SYSCALL_SET_PRIORITY,
+    SYSCALL_PRCTL,
+    SYSCALL_TASK_INFO,
+    SYSCALL_HOLD_TASK,
+    SYSCALL_ALARM,
+    SYSCALL_TASK_STATS,
+    SYSCALL_SCHED_QUEUE,
+    SYSCALL_SCHED_SETQUANTUM,
+    SYSCALL_SCHED_GETQUANTUM,
+    SYSCALL_SCHED_STAT,
+    SYSCALL_MEM_STATS,
+    SYSCALL_STRACE,
+    SYSCALL_KLOGCTL,
+    SYSCALL_SYSLOG,
+    SYSCALL_PAUSE,
+    SYSCALL_GETRANDOM,
+    SYSCALL_UNAME,
+    SYSCALL_SHUTDOWN,
+    SYSCALL_EXIT_GROUP,
+    SYSCALL_SETPGID,
+    SYSCALL_GETPGID,
+    SYSCALL_WAIT4,
+    SYSCALL_GETRUSAGE,
+    SYSCALL_LIST_APPS,
+];
+
+/// Names of the syscalls in [`SYSCALL_IDS`], in the same order, for strace
+const SYSCALL_NAMES: [&str; 57] = [
+    "dup",
+    "close",
+    "pipe",
+    "read",
+    "write",
+    "writev",
+    "exit",
+    "sleep",
+    "nanosleep",
+    "yield",
+    "kill",
+    "sigaction",
+    "sigprocmask",
+    "sigreturn",
+    "times",
+    "get_time",
+    "settimeofday",
+    "clock_gettime",
+    "getpid",
+    "getppid",
+    "sysinfo",
+    "shmget",
+    "shmat",
+    "shmdt",
+    "fork",
+    "exec",
+    "waitpid",
+    "prlimit",
+    "spawn",
+    "sbrk",
+    "munmap",
+    "mmap",
+    "mprotect",
+    "set_priority",
+    "prctl",
+    "task_info",
+    "hold_task",
+    "alarm",
+    "task_stats",
+    "sched_queue",
+    "sched_setquantum",
+    "sched_getquantum",
+    "sched_stat",
+    "mem_stats",
+    "strace",
+    "klogctl",
+    "syslog",
+    "pause",
+    "getrandom",
+    "uname",
+    "shutdown",
+    "exit_group",
+    "setpgid",
+    "getpgid",
+    "wait4",
+    "getrusage",
+    "list_apps",
+];
+
+/// Position of `syscall_id` in [`SYSCALL_IDS`], `None` for an unsupported syscall
+pub fn syscall_slot(syscall_id: usize) -> Option<usize> {
+    SYSCALL_IDS.iter().position(|&id| id == syscall_id)
+}
 
 mod fs;
 mod process;
 
+use crate::config::MAX_SYSCALL_NUM;
+use crate::mm::{copy_to_user, translated_str, MemoryError};
+use crate::task::{current_strace, current_task, current_user_token, populate_current_user};
+use crate::task::{
+    record_current_syscall, RLimit, SchedEntry, SchedStat, SchedStats, SignalAction,
+};
+use alloc::format;
+use alloc::string::String;
+use core::mem::size_of;
 use fs::*;
 use process::*;
 
+/// `copy_to_user` into the current task, after handling the lazy and
+/// copy-on-write pages under `ptr` like page faults would
+fn write_to_user<T: Copy>(token: usize, ptr: *mut T, val: &T) -> Result<(), MemoryError> {
+    if !populate_current_user(ptr as usize, size_of::<T>(), true) {
+        return Err(MemoryError::OutOfMemory);
+    }
+    copy_to_user(token, ptr, val)
+}
+
+/// `[pid N] name(arg0, arg1, arg2)` as strace prints it, with the path given to exec and spawn
+fn strace_call(syscall_id: usize, args: [usize; 4]) -> String {
+    let name = match syscall_slot(syscall_id) {
+        Some(slot) => String::from(SYSCALL_NAMES[slot]),
+        None => format!("unknown_{}", syscall_id),
+    };
+    let path = match syscall_id {
+        SYSCALL_EXEC | SYSCALL_SPAWN => {
+            match translated_str(current_user_token(), args[0] as *const u8) {
+                Ok(path) => format!(" {:?}", path),
+                Err(_) => String::from(" <bad address>"),
+            }
+        }
+        _ => String::new(),
+    };
+    format!(
+        "[pid {}] {}({:#x}{}, {:#x}, {:#x})",
+        current_task().unwrap().getpid(),
+        name,
+        args[0],
+        path,
+        args[1],
+        args[2]
+    )
+}
+
 /// handle syscall exception with `syscall_id` and other arguments
-pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
-    match syscall_id {
+pub fn syscall(syscall_id: usize, args: [usize; 4]) -> isize {
+    record_current_syscall(syscall_id);
+    // formatted before the call, as exec replaces the memory the path is read from
+    let traced = current_strace().then(|| strace_call(syscall_id, args));
+    if let (Some(call), SYSCALL_EXIT) = (&traced, syscall_id) {
+        // each line is a single console write, so that it is not broken up by others
+        let line = format!("{} = ?\n", call);
+        print!("{}", line);
+    }
+    let ret = match syscall_id {
+        SYSCALL_DUP => sys_dup(args[0]),
+        SYSCALL_CLOSE => sys_close(args[0]),
+        SYSCALL_PIPE => sys_pipe(args[0] as *mut [u32; 2]),
         SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
         SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_WRITEV => sys_writev(args[0], args[1] as *const IoVec, args[2]),
         SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_SLEEP => sys_sleep(args[0]),
+        SYSCALL_NANOSLEEP => sys_nanosleep(args[0] as *const TimeSpec, args[1] as *mut TimeSpec),
         SYSCALL_YIELD => sys_yield(),
+        SYSCALL_KILL => sys_kill(args[0] as isize, args[1]),
+        SYSCALL_SIGACTION => sys_sigaction(
+            args[0],
+            args[1] as *const SignalAction,
+            args[2] as *mut SignalAction,
+        ),
+        SYSCALL_SIGPROCMASK => sys_sigprocmask(args[0] as u32),
+        SYSCALL_SIGRETURN => sys_sigreturn(),
+        SYSCALL_TIMES => sys_times(args[0] as *mut Tms),
         SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_GETPPID => sys_getppid(),
+        SYSCALL_SYSINFO => sys_sysinfo(args[0] as *mut SysInfo),
+        SYSCALL_SHMGET => sys_shmget(args[0], args[1]),
+        SYSCALL_SHMAT => sys_shmat(args[0], args[1]),
+        SYSCALL_SHMDT => sys_shmdt(args[0]),
         SYSCALL_FORK => sys_fork(),
-        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
-        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
-        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_EXEC => sys_exec(
+            args[0] as *const u8,
+            args[1] as *const usize,
+            args[2] as *const usize,
+        ),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32, args[2]),
+        SYSCALL_PRLIMIT => sys_prlimit(
+            args[0],
+            args[1],
+            args[2] as *const RLimit,
+            args[3] as *mut RLimit,
+        ),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1] as *mut TimeZone),
+        SYSCALL_SETTIMEOFDAY => sys_settimeofday(args[0] as *const TimeVal, args[1]),
+        SYSCALL_CLOCK_GETTIME => sys_clock_gettime(args[0], args[1] as *mut TimeSpec),
+        SYSCALL_SBRK => sys_sbrk(args[0] as isize),
         SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
         SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_MPROTECT => sys_mprotect(args[0], args[1], args[2]),
         SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
+        SYSCALL_PRCTL => sys_prctl(args[0], args[1]),
         SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
-        SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
-        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+        #[cfg(feature = "selftest")]
+        SYSCALL_HOLD_TASK => sys_hold_task(args[0], args[1]),
+        SYSCALL_ALARM => sys_alarm(args[0]),
+        SYSCALL_TASK_STATS => sys_task_stats(args[0] as *mut SchedStats),
+        SYSCALL_SCHED_QUEUE => sys_sched_queue(),
+        SYSCALL_SCHED_SETQUANTUM => sys_sched_setquantum(args[0]),
+        SYSCALL_SCHED_GETQUANTUM => sys_sched_getquantum(),
+        SYSCALL_SCHED_STAT => sys_sched_stat(
+            args[0] as *mut SchedStat,
+            args[1] as *mut SchedEntry,
+            args[2],
+        ),
+        SYSCALL_MEM_STATS => sys_mem_stats(args[0] as *mut MemStats),
+        SYSCALL_STRACE => sys_strace(args[0], args[1]),
+        SYSCALL_KLOGCTL => sys_klogctl(args[0], args[1] as *const u8),
+        SYSCALL_SYSLOG => sys_syslog(args[0], args[1] as *mut u8, args[2]),
+        SYSCALL_PAUSE => sys_pause(),
+        SYSCALL_GETRANDOM => sys_getrandom(args[0] as *mut u8, args[1], args[2]),
+        SYSCALL_UNAME => sys_uname(args[0] as *mut Utsname),
+        SYSCALL_SHUTDOWN => sys_shutdown(args[0]),
+        SYSCALL_EXIT_GROUP => sys_exit_group(args[0] as i32),
+        SYSCALL_SETPGID => sys_setpgid(args[0], args[1]),
+        SYSCALL_GETPGID => sys_getpgid(args[0]),
+        SYSCALL_WAIT4 => sys_wait4(
+            args[0] as isize,
+            args[1] as *mut i32,
+            args[2],
+            args[3] as *mut Rusage,
+        ),
+        SYSCALL_GETRUSAGE => sys_getrusage(args[0] as isize, args[1] as *mut Rusage),
+        SYSCALL_LIST_APPS => sys_list_apps(args[0] as *mut u8, args[1]),
+        SYSCALL_SPAWN => sys_spawn(
+            args[0] as *const u8,
+            args[1] as *const usize,
+            args[2] as *const usize,
+        ),
+        _ => {
+            warn!("[kernel] Unsupported syscall_id: {}", syscall_id);
+            -1
+        }
+    };
+    if let Some(call) = traced {
+        let line = format!("{} = {}\n", call, ret);
+        print!("{}", line);
+    }
+    ret
+}
+
+/// Check that every dispatched syscall owns a distinct counter and fits in `TaskInfo`
+pub fn syscall_slot_test() {
+    for (slot, &id) in SYSCALL_IDS.iter().enumerate() {
+        assert!(id < MAX_SYSCALL_NUM);
+        assert_eq!(syscall_slot(id), Some(slot));
+    }
+    assert_eq!(syscall_slot(MAX_SYSCALL_NUM), None);
+    for name in SYSCALL_NAMES {
+        assert_eq!(
+            SYSCALL_NAMES.iter().filter(|&&other| other == name).count(),
+            1
+        );
     }
+    info!("syscall_slot_test passed!");
 }
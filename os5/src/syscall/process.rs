@@ -1,14 +1,35 @@
 //! Process management syscalls
 
-use crate::config::MAX_SYSCALL_NUM;
-use crate::loader::get_app_data_by_name;
-use crate::mm::{translated_refmut, translated_str, convert_to_physical_addr};
+use super::write_to_user;
+use crate::config::{MAX_ARGS_SIZE, MAX_ENV_SIZE, MAX_SYSCALL_NUM};
+use crate::loader::{app_list, get_app_data_by_name};
+use crate::logging;
+use crate::mm::{
+    copy_from_user, copy_to_user, frame_free_count, frame_stats, kernel_heap_stats,
+    translated_byte_buffer_mut, translated_str, MemoryError, MemorySet,
+};
+use crate::random::{add_entropy, fill_random, MAX_DRAW};
+#[cfg(feature = "selftest")]
+use crate::task::hold_task;
 use crate::task::{
-    current_user_token, exit_current_and_run_next, mmap,
-    munmap, suspend_current_and_run_next, TaskStatus, current_task, add_task, TaskControlBlock,
+    add_task, block_current_and_run_next, count_tasks, current_killed_by, current_queue_level,
+    current_sched_stats, current_task, current_user_token, exit_current_and_run_next, get_pgid,
+    kill_current_descendants, kill_group, kill_task, live_tasks, mmap, mprotect, munmap,
+    online_harts, pause_current, populate_current_user, power_off, retry_on_oom, sched_snapshot,
+    set_current_alarm, set_current_priority, set_pgid, set_strace, shm_attach_current,
+    shm_detach_current, shm_get_current, suspend_current_and_run_next, task_name, valid_signum,
+    RLimit, ResourceUsage, SchedEntry, SchedStat, SchedStats, SignalAction, SignalFlags,
+    TaskStatus, INITPROC, SIGKILL, SIGSTOP, TASK_NAME_LEN,
+};
+use crate::timer::{
+    add_timer, clock_ticks, cycles_to_us, get_quantum_us, get_realtime_ns, get_time, get_time_ms,
+    get_time_ns, get_time_us, ns_to_cycles, set_quantum_us, set_realtime_ns, WakeReason,
+    NANO_PER_SEC,
 };
-use crate::timer::get_time_us;
+use alloc::string::String;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem::size_of;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -17,6 +38,155 @@ pub struct TimeVal {
     pub usec: usize,
 }
 
+/// The timezone reported by get_time, always UTC without DST
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeZone {
+    pub minuteswest: i32,
+    pub dsttime: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TimeSpec {
+    pub sec: usize,
+    pub nsec: usize,
+}
+
+impl TimeSpec {
+    fn from_ns(ns: usize) -> Self {
+        Self {
+            sec: ns / NANO_PER_SEC,
+            nsec: ns % NANO_PER_SEC,
+        }
+    }
+}
+
+/// Wall-clock time, which can be set by settimeofday
+const CLOCK_REALTIME: usize = 0;
+/// Time since boot, which never goes backwards
+const CLOCK_MONOTONIC: usize = 1;
+
+/// CPU time of a process and its reaped children, in clock ticks
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Tms {
+    pub utime: usize,
+    pub stime: usize,
+    pub cutime: usize,
+    pub cstime: usize,
+}
+
+/// Resource usage of a reaped child stored by wait4, or of a process or its reaped
+/// children stored by getrusage. `maxrss` is in pages
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Rusage {
+    pub utime: TimeVal,
+    pub stime: TimeVal,
+    pub maxrss: usize,
+    pub minflt: usize,
+    pub majflt: usize,
+    pub nvcsw: usize,
+    pub nivcsw: usize,
+}
+
+impl Rusage {
+    fn new(user_time: usize, kernel_time: usize, usage: &ResourceUsage) -> Self {
+        Self {
+            utime: TimeVal::from_cycles(user_time),
+            stime: TimeVal::from_cycles(kernel_time),
+            maxrss: usage.maxrss,
+            minflt: usage.minflt,
+            // no page is ever read from a disk
+            majflt: 0,
+            nvcsw: usage.nvcsw,
+            nivcsw: usage.nivcsw,
+        }
+    }
+}
+
+/// getrusage reports the calling process itself
+const RUSAGE_SELF: isize = 0;
+/// getrusage reports the reaped children of the calling process
+const RUSAGE_CHILDREN: isize = -1;
+
+impl TimeVal {
+    fn from_cycles(cycles: usize) -> Self {
+        let us = cycles_to_us(cycles);
+        Self {
+            sec: us / 1_000_000,
+            usec: us % 1_000_000,
+        }
+    }
+}
+
+/// Length of each field of `Utsname`, the terminating zero included
+const UTSNAME_LEN: usize = 65;
+
+/// Identification of the kernel filled by uname
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Utsname {
+    pub sysname: [u8; UTSNAME_LEN],
+    pub nodename: [u8; UTSNAME_LEN],
+    pub release: [u8; UTSNAME_LEN],
+    pub version: [u8; UTSNAME_LEN],
+    pub machine: [u8; UTSNAME_LEN],
+}
+
+/// `s` as a zero-terminated field of `Utsname`, cut down to fit if too long
+fn utsname_field(s: &str) -> [u8; UTSNAME_LEN] {
+    let mut field = [0; UTSNAME_LEN];
+    let len = s.len().min(UTSNAME_LEN - 1);
+    field[..len].copy_from_slice(&s.as_bytes()[..len]);
+    field
+}
+
+/// System-wide statistics
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SysInfo {
+    /// Seconds since boot
+    pub uptime: usize,
+    /// Number of physical frames managed by the frame allocator
+    pub total_frames: usize,
+    /// Number of free physical frames
+    pub free_frames: usize,
+    /// Highest number of physical frames allocated at the same time since boot
+    pub peak_frames: usize,
+    /// Number of frame allocations failed since boot
+    pub frame_failures: usize,
+    /// Size of the kernel heap in bytes
+    pub total_heap: usize,
+    /// Bytes of the kernel heap not allocated
+    pub free_heap: usize,
+    /// Highest number of bytes of the kernel heap allocated at the same time since boot
+    pub peak_heap: usize,
+    /// Number of kernel heap allocations since boot
+    pub heap_allocations: usize,
+    /// Number of live processes, zombies included
+    pub procs: usize,
+    /// Number of zombies not reaped yet
+    pub zombies: usize,
+    /// Number of harts running tasks
+    pub harts: usize,
+}
+
+/// Memory usage of the current process in pages. A frame shared with other
+/// processes counts as resident in each of them, and also in `shared_pages`.
+/// A lazy page only read so far is mapped to the zero frame and still counts as lazy.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MemStats {
+    /// Number of physical frames mapped
+    pub resident_pages: usize,
+    /// Number of pages reserved by mmap but not written yet
+    pub lazy_pages: usize,
+    /// Number of resident frames shared copy-on-write or through shared memory
+    pub shared_pages: usize,
+}
+
 #[derive(Clone, Copy)]
 pub struct TaskInfo {
     pub status: TaskStatus,
@@ -36,6 +206,294 @@ pub fn sys_exit(exit_code: i32) -> ! {
     panic!("Unreachable in sys_exit!");
 }
 
+/// Kill every descendant of the current task, then exit like sys_exit
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    kill_current_descendants();
+    sys_exit(exit_code)
+}
+
+/// Block the current task for at least `ms` milliseconds
+pub fn sys_sleep(ms: usize) -> isize {
+    let deadline = ns_to_cycles(get_time_ns().saturating_add(ms.saturating_mul(1_000_000)));
+    add_timer(deadline, &current_task().unwrap(), WakeReason::Sleep);
+    block_current_and_run_next();
+    0
+}
+
+/// Block the current task for the time in `req`. Return -1 and store the unslept time
+/// into `rem` if woken up early by a signal, otherwise return 0 and zero `rem`.
+/// Either way `rem` may be null.
+pub fn sys_nanosleep(req: *const TimeSpec, rem: *mut TimeSpec) -> isize {
+    let token = current_user_token();
+    let req: TimeSpec = match copy_from_user(token, req) {
+        Ok(req) => req,
+        Err(_) => return -1,
+    };
+    if req.nsec >= NANO_PER_SEC {
+        return -1;
+    }
+    let expire_ns = match req
+        .sec
+        .checked_mul(NANO_PER_SEC)
+        .and_then(|ns| ns.checked_add(req.nsec))
+        .and_then(|ns| ns.checked_add(get_time_ns()))
+    {
+        Some(expire_ns) => expire_ns,
+        None => return -1,
+    };
+    // even a zero sleep gives up the CPU once
+    add_timer(
+        ns_to_cycles(expire_ns),
+        &current_task().unwrap(),
+        WakeReason::Sleep,
+    );
+    block_current_and_run_next();
+    // the deadline is rounded to `mtime`, only a signal tells an early wake up
+    let interrupted = current_task().unwrap().inner_exclusive_access().interrupted;
+    let remaining = if interrupted {
+        expire_ns.saturating_sub(get_time_ns())
+    } else {
+        0
+    };
+    if !rem.is_null() && write_to_user(token, rem, &TimeSpec::from_ns(remaining)).is_err() {
+        return -1;
+    }
+    if interrupted {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Store the scheduling statistics of the current task into `stats`
+pub fn sys_task_stats(stats: *mut SchedStats) -> isize {
+    match write_to_user(current_user_token(), stats, &current_sched_stats()) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Return the MLFQ queue the current task is in, or -1 with the stride scheduler
+pub fn sys_sched_queue() -> isize {
+    current_queue_level().map_or(-1, |level| level as isize)
+}
+
+/// Set the scheduling time slice to `us` microseconds, clamped to [1 ms, 1 s],
+/// and return the length set. Only allowed for initproc, or anyone with `sched_debug`.
+pub fn sys_sched_setquantum(us: usize) -> isize {
+    if !cfg!(feature = "sched_debug") && current_task().unwrap().getpid() != INITPROC.getpid() {
+        return -1;
+    }
+    set_quantum_us(us) as isize
+}
+
+/// Return the scheduling time slice in microseconds
+pub fn sys_sched_getquantum() -> isize {
+    get_quantum_us() as isize
+}
+
+/// Store a snapshot of the scheduler into `stat`, and the current task followed by the
+/// ready tasks into `entries`, truncated to `len` of them.
+/// Return the number of tasks in the snapshot.
+pub fn sys_sched_stat(stat: *mut SchedStat, entries: *mut SchedEntry, len: usize) -> isize {
+    let (snapshot, tasks) = sched_snapshot();
+    let token = current_user_token();
+    if write_to_user(token, stat, &snapshot).is_err() {
+        return -1;
+    }
+    for (i, entry) in tasks.iter().take(len).enumerate() {
+        if write_to_user(token, entries.wrapping_add(i), entry).is_err() {
+            return -1;
+        }
+    }
+    tasks.len() as isize
+}
+
+/// Raise SIGALRM for the current task in `seconds`, or cancel the alarm if it is 0.
+/// Return the seconds left before the previous alarm.
+pub fn sys_alarm(seconds: usize) -> isize {
+    set_current_alarm(seconds) as isize
+}
+
+/// Send the signal `signum` to the process `pid`, or to every process in the group `-pid`
+/// if `pid` is negative. Signalling every process at once with -1 is not supported
+pub fn sys_kill(pid: isize, signum: usize) -> isize {
+    match pid {
+        -1 => -1,
+        pid if pid < 0 => kill_group(-pid as usize, signum),
+        pid => kill_task(pid as usize, signum),
+    }
+}
+
+/// Move `pid`, or the caller if 0, into the process group `pgid`, or into a new group led
+/// by itself if `pgid` is 0. Only the caller and its children which have not run exec
+/// may be moved
+pub fn sys_setpgid(pid: usize, pgid: usize) -> isize {
+    set_pgid(pid, pgid)
+}
+
+/// The process group of `pid`, or of the caller if 0
+pub fn sys_getpgid(pid: usize) -> isize {
+    get_pgid(pid)
+}
+
+/// Block until a signal runs its handler or kills the caller, always return -1
+pub fn sys_pause() -> isize {
+    pause_current();
+    -1
+}
+
+/// Install `action` as the handler of `signum` and store the previous one into `old_action`,
+/// either pointer may be null. SIGKILL and SIGSTOP cannot be handled.
+pub fn sys_sigaction(
+    signum: usize,
+    action: *const SignalAction,
+    old_action: *mut SignalAction,
+) -> isize {
+    if !valid_signum(signum) || signum == SIGKILL || signum == SIGSTOP {
+        return -1;
+    }
+    let task = current_task().unwrap();
+    // ---- access current TCB exclusively
+    let inner = task.inner_exclusive_access();
+    let token = inner.get_user_token();
+    let prev = inner.signal_actions[signum];
+    drop(inner);
+    // ---- release current TCB
+    let new = if action.is_null() {
+        prev
+    } else {
+        match copy_from_user(token, action) {
+            Ok(new) => new,
+            Err(_) => return -1,
+        }
+    };
+    if !old_action.is_null() && write_to_user(token, old_action, &prev).is_err() {
+        return -1;
+    }
+    task.inner_exclusive_access().signal_actions[signum] = new;
+    0
+}
+
+/// Store the limit of `resource` of the process `pid` into `old_limit` and replace it with
+/// `new_limit`, a null pointer skips either. Only the caller itself (pid 0 or its own pid)
+/// and `RLIMIT_STACK`, `RLIMIT_NPROC` and `RLIMIT_AS` are supported.
+pub fn sys_prlimit(
+    pid: usize,
+    resource: usize,
+    new_limit: *const RLimit,
+    old_limit: *mut RLimit,
+) -> isize {
+    let task = current_task().unwrap();
+    if pid != 0 && pid != task.getpid() {
+        return -1;
+    }
+    // ---- access current TCB exclusively
+    let inner = task.inner_exclusive_access();
+    let token = inner.get_user_token();
+    let prev = match inner.rlimits.get(resource) {
+        Some(prev) => prev,
+        None => return -1,
+    };
+    drop(inner);
+    // ---- release current TCB
+    let new = if new_limit.is_null() {
+        prev
+    } else {
+        match copy_from_user(token, new_limit) {
+            Ok(new) => new,
+            Err(_) => return -1,
+        }
+    };
+    if !prev.allows(&new) {
+        return -1;
+    }
+    if !old_limit.is_null() && write_to_user(token, old_limit, &prev).is_err() {
+        return -1;
+    }
+    // a limit below the current usage only fails what comes next
+    task.inner_exclusive_access().rlimits.set(resource, new);
+    0
+}
+
+/// Block the signals in `mask` and return the previously blocked ones.
+/// SIGKILL and SIGSTOP cannot be blocked.
+pub fn sys_sigprocmask(mask: u32) -> isize {
+    let task = current_task().unwrap();
+    // ---- access current TCB exclusively
+    let mut inner = task.inner_exclusive_access();
+    let old_mask = inner.signal_mask;
+    inner.signal_mask =
+        SignalFlags::from_bits_truncate(mask) - (SignalFlags::SIGKILL | SignalFlags::SIGSTOP);
+    old_mask.bits() as isize
+    // ---- release current TCB automatically
+}
+
+/// Return from a signal handler to the trap context it interrupted
+pub fn sys_sigreturn() -> isize {
+    let task = current_task().unwrap();
+    // ---- access current TCB exclusively
+    let mut inner = task.inner_exclusive_access();
+    match inner.trap_ctx_backup.take() {
+        Some(trap_cx) => {
+            inner.handling_sig = None;
+            inner.signal_mask = inner.signal_mask_backup;
+            *inner.get_trap_cx() = trap_cx;
+            // trap_handler stores the return value to a0, so hand the interrupted a0 back
+            trap_cx.x[10] as isize
+        }
+        None => -1,
+    }
+    // ---- release current TCB automatically
+}
+
+/// getrandom flags, accepted but not needed as the generator never blocks
+const GRND_NONBLOCK: usize = 1;
+const GRND_RANDOM: usize = 2;
+
+/// Fill `buf` with up to 256 random bytes, return the number of bytes written
+pub fn sys_getrandom(buf: *mut u8, len: usize, flags: usize) -> isize {
+    if flags & !(GRND_NONBLOCK | GRND_RANDOM) != 0 {
+        return -1;
+    }
+    let len = len.min(MAX_DRAW);
+    if !populate_current_user(buf as usize, len, true) {
+        return -1;
+    }
+    let buffers = match translated_byte_buffer_mut(current_user_token(), buf, len) {
+        Ok(buffers) => buffers,
+        Err(_) => return -1,
+    };
+    let mut data = [0; MAX_DRAW];
+    fill_random(&mut data[..len]);
+    let mut copied = 0;
+    for buffer in buffers {
+        buffer.copy_from_slice(&data[copied..copied + buffer.len()]);
+        copied += buffer.len();
+    }
+    len as isize
+}
+
+/// Copy the names of the apps, each followed by a newline, into `buf` truncated to `len`
+/// bytes, return the length of the whole list
+pub fn sys_list_apps(buf: *mut u8, len: usize) -> isize {
+    let list = app_list();
+    let len = len.min(list.len());
+    if !populate_current_user(buf as usize, len, true) {
+        return -1;
+    }
+    let buffers = match translated_byte_buffer_mut(current_user_token(), buf, len) {
+        Ok(buffers) => buffers,
+        Err(_) => return -1,
+    };
+    let mut copied = 0;
+    for buffer in buffers {
+        buffer.copy_from_slice(&list.as_bytes()[copied..copied + buffer.len()]);
+        copied += buffer.len();
+    }
+    list.len() as isize
+}
+
 /// current task gives up resources for other tasks
 pub fn sys_yield() -> isize {
     suspend_current_and_run_next();
@@ -46,11 +504,57 @@ pub fn sys_getpid() -> isize {
     current_task().unwrap().pid.0 as isize
 }
 
+/// Set the name of the current task to the string at `arg`
+const PR_SET_NAME: usize = 15;
+/// Store the name of the current task into the 16-byte buffer at `arg`
+const PR_GET_NAME: usize = 16;
+
+/// Operate on the current task according to `option`, return -1 for an unknown option
+pub fn sys_prctl(option: usize, arg: usize) -> isize {
+    let task = current_task().unwrap();
+    let token = current_user_token();
+    match option {
+        PR_SET_NAME => match translated_str(token, arg as *const u8) {
+            Ok(name) => {
+                task.inner_exclusive_access().name = task_name(&name);
+                0
+            }
+            Err(_) => -1,
+        },
+        PR_GET_NAME => {
+            let name = task.inner_exclusive_access().name;
+            match write_to_user(token, arg as *mut [u8; TASK_NAME_LEN], &name) {
+                Ok(()) => 0,
+                Err(_) => -1,
+            }
+        }
+        _ => -1,
+    }
+}
+
+/// Return the pid of the parent, orphans have been adopted by initproc
+pub fn sys_getppid() -> isize {
+    let task = current_task().unwrap();
+    let parent = task.inner_exclusive_access().parent.clone();
+    match parent.and_then(|parent| parent.upgrade()) {
+        Some(parent) => parent.getpid() as isize,
+        None => INITPROC.getpid() as isize,
+    }
+}
+
 /// Syscall Fork which returns 0 for child process and child_pid for parent process
 pub fn sys_fork() -> isize {
     let current_task = current_task().unwrap();
-    let new_task = current_task.fork();
+    if !current_task.inner_exclusive_access().fits_nproc_limit() {
+        return -1;
+    }
+    let new_task = match retry_on_oom(|| current_task.fork()) {
+        Ok(task) => task,
+        Err(_) => return -1,
+    };
     let new_pid = new_task.pid.0;
+    // the parent and the child must not draw the same bytes next
+    add_entropy(new_pid ^ get_time());
     // modify trap context of new_task, because it returns immediately after switching
     let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
     // we do not have to move to next instruction since we have done it before
@@ -61,91 +565,352 @@ pub fn sys_fork() -> isize {
     new_pid as isize
 }
 
-/// Syscall Exec which accepts the elf path
-pub fn sys_exec(path: *const u8) -> isize {
+/// Return value of exec and spawn for an image the loader rejects, -1 if no frame is left
+fn exec_error(err: MemoryError) -> isize {
+    match err {
+        MemoryError::BadElf => -2,
+        MemoryError::WrongArch => -3,
+        MemoryError::BadSegment => -4,
+        MemoryError::WritableExec => -5,
+        MemoryError::OverlappingSegments => -6,
+        MemoryError::BadEntry => -7,
+        MemoryError::OutOfMemory | MemoryError::BadAddress => -1,
+    }
+}
+
+/// Syscall Exec which accepts the elf path and null-terminated arrays of argument
+/// and `KEY=VALUE` environment strings. Return -1 if there is no such app, or a code
+/// of [`exec_error`] with the current program left intact. On success the return value
+/// lands in a0 of the new program, so it is argc
+pub fn sys_exec(path: *const u8, argv: *const usize, envp: *const usize) -> isize {
     let token = current_user_token();
-    let path = translated_str(token, path);
+    let path = match translated_str(token, path) {
+        Ok(path) => path,
+        Err(_) => return -1,
+    };
+    let args = match translated_args(token, argv, MAX_ARGS_SIZE) {
+        Some(args) => args,
+        None => return -1,
+    };
+    let envs = match translated_args(token, envp, MAX_ENV_SIZE) {
+        Some(envs) => envs,
+        None => return -1,
+    };
     if let Some(data) = get_app_data_by_name(path.as_str()) {
         let task = current_task().unwrap();
-        task.exec(data);
-        0
+        let argc = args.len();
+        match task.exec(&path, data, args, envs) {
+            Ok(()) => argc as isize,
+            Err(err) => exec_error(err),
+        }
     } else {
         -1
     }
 }
 
+/// Return at once instead of blocking when no child has exited yet
+const WNOHANG: usize = 1;
+
 /// If there is not a child process whose pid is same as given, return -1.
-/// Else if there is a child process but it is still running, return -2.
-pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
-    let task = current_task().unwrap();
-    // find a child process
+/// Else if there is a child process but it is still running, block until it exits,
+/// or return 0 if `options` contains `WNOHANG`.
+/// The exit code is not stored if `exit_code_ptr` is null.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32, options: usize) -> isize {
+    sys_wait4(pid, exit_code_ptr, options, core::ptr::null_mut())
+}
 
-    // ---- access current TCB exclusively
-    let mut inner = task.inner_exclusive_access();
-    if !inner
-        .children
-        .iter()
-        .any(|p| pid == -1 || pid as usize == p.getpid())
-    {
-        return -1;
+/// Store `val` at `ptr` in `memory_set`, whose task is held by the caller,
+/// return false if the page cannot be written
+fn store_in<T: Copy>(memory_set: &mut MemorySet, ptr: *mut T, val: &T) -> bool {
+    let start = ptr as usize;
+    let end = start + size_of::<T>();
+    memory_set.populate(start.into(), end.into(), true)
+        && copy_to_user(memory_set.token(), ptr, val).is_ok()
+}
+
+/// waitpid which also stores the resource usage of the reaped child itself into `rusage_ptr`,
+/// unless it is null
+pub fn sys_wait4(
+    pid: isize,
+    exit_code_ptr: *mut i32,
+    options: usize,
+    rusage_ptr: *mut Rusage,
+) -> isize {
+    loop {
+        let task = current_task().unwrap();
+        // find a child process
+
+        // ---- access current TCB exclusively
+        let mut inner = task.inner_exclusive_access();
+        if !inner
+            .children
+            .iter()
+            .any(|p| pid == -1 || pid as usize == p.getpid())
+        {
+            return -1;
+            // ---- release current PCB
+        }
+        let pair = inner.children.iter().enumerate().find(|(_, p)| {
+            // ++++ temporarily access child PCB lock exclusively,
+            // a child whose inner is held is busy in the kernel hence no zombie
+            (pid == -1 || pid as usize == p.getpid())
+                && p.try_inner_exclusive_access()
+                    .map_or(false, |child_inner| child_inner.is_zombie())
+            // ++++ release child PCB
+        });
+        if let Some((idx, child)) = pair {
+            // ++++ temporarily access child TCB exclusively
+            let child_inner = task.child_inner_exclusive_access(child);
+            let exit_code = child_inner.exit_code;
+            let rusage = Rusage::new(
+                child_inner.user_time,
+                child_inner.kernel_time,
+                &child_inner.rusage,
+            );
+            drop(child_inner);
+            // ++++ release child PCB
+            // the child stays a zombie if its exit code or usage cannot be stored
+            if !exit_code_ptr.is_null()
+                && !store_in(&mut inner.memory_set, exit_code_ptr, &exit_code)
+            {
+                return -1;
+            }
+            if !rusage_ptr.is_null() && !store_in(&mut inner.memory_set, rusage_ptr, &rusage) {
+                return -1;
+            }
+            let child = inner.children.remove(idx);
+            // ++++ temporarily access child TCB exclusively
+            let child_inner = task.child_inner_exclusive_access(&child);
+            inner.children_user_time += child_inner.user_time + child_inner.children_user_time;
+            inner.children_kernel_time +=
+                child_inner.kernel_time + child_inner.children_kernel_time;
+            inner.children_rusage.add(&child_inner.rusage);
+            inner.children_rusage.add(&child_inner.children_rusage);
+            drop(child_inner);
+            // ++++ release child PCB
+            // the child is deallocated once the other references to it, if any, go away
+            if Arc::strong_count(&child) > 1 {
+                debug!(
+                    "[kernel] pid {} is reaped while referenced {} more times",
+                    child.getpid(),
+                    Arc::strong_count(&child) - 1
+                );
+            }
+            return child.getpid() as isize;
+        }
+        if options & WNOHANG != 0 {
+            return 0;
+        }
+        // exit_current_and_run_next of the child wakes us up
+        inner.waiting_for = Some(pid);
+        drop(inner);
         // ---- release current PCB
+        drop(task);
+        block_current_and_run_next();
+        // woken up by a kill, give up so that trap_handler can exit
+        if current_killed_by().is_some() {
+            return -1;
+        }
     }
-    let pair = inner.children.iter().enumerate().find(|(_, p)| {
-        // ++++ temporarily access child PCB lock exclusively
-        p.inner_exclusive_access().is_zombie() && (pid == -1 || pid as usize == p.getpid())
-        // ++++ release child PCB
-    });
-    if let Some((idx, _)) = pair {
-        let child = inner.children.remove(idx);
-        // confirm that child will be deallocated after removing from children list
-        assert_eq!(Arc::strong_count(&child), 1);
-        let found_pid = child.getpid();
-        // ++++ temporarily access child TCB exclusively
-        let exit_code = child.inner_exclusive_access().exit_code;
-        // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
-        found_pid as isize
-    } else {
-        -2
-    }
-    // ---- release current PCB lock automatically
 }
 
 // YOUR JOB: 引入虚地址后重写 sys_get_time
-pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
-    let us = get_time_us();
-    // 将虚拟地址转换为物理地址
+/// Store the time since boot into `ts` and a zeroed timezone into `tz`,
+/// either pointer may be null to skip it
+pub fn sys_get_time(ts: *mut TimeVal, tz: *mut TimeZone) -> isize {
     let token = current_user_token();
-    let ts = convert_to_physical_addr(token, _ts as usize) as *mut TimeVal;
-    unsafe {
-        *ts = TimeVal {
+    if !ts.is_null() {
+        let us = get_time_us();
+        let tv = TimeVal {
             sec: us / 1_000_000,
             usec: us % 1_000_000,
         };
+        if write_to_user(token, ts, &tv).is_err() {
+            return -1;
+        }
+    }
+    if !tz.is_null() && write_to_user(token, tz, &TimeZone::default()).is_err() {
+        return -1;
     }
     0
 }
 
-// CLUE: 从 ch4 开始不再对调度算法进行测试~
-pub fn sys_set_priority(_prio: isize) -> isize {
-    -1
+/// Fill the user `Tms` with the CPU time of the current task, return the clock ticks since boot
+pub fn sys_times(buf: *mut Tms) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    // include this syscall so far
+    inner.account_time(false);
+    let tms = Tms {
+        utime: clock_ticks(inner.user_time),
+        stime: clock_ticks(inner.kernel_time),
+        cutime: clock_ticks(inner.children_user_time),
+        cstime: clock_ticks(inner.children_kernel_time),
+    };
+    let token = inner.get_user_token();
+    drop(inner);
+    match write_to_user(token, buf, &tms) {
+        Ok(()) => clock_ticks(get_time()) as isize,
+        Err(_) => -1,
+    }
 }
 
-// YOUR JOB: 引入虚地址后重写 sys_task_info
-pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
-    // let first_sched_ts = get_first_sched_time();
-    // let syscall_times = list_syscall_counts();
-    // let curr_ts = get_time_us();
-    // let token = current_user_token();
-    // let ti = convert_to_physical_addr(token, _ti as usize) as *mut TaskInfo;
-    // unsafe {
-    //     *ti = TaskInfo {
-    //         status: TaskStatus::Running,
-    //         syscall_times: syscall_times,
-    //         time: (usize::from(curr_ts) - usize::from(first_sched_ts)) / 1000,
-    //     }
-    // }
-    0
+/// Fill `buf` with the resource usage of the current task if `who` is `RUSAGE_SELF`,
+/// or with the totals of its reaped children if `who` is `RUSAGE_CHILDREN`
+pub fn sys_getrusage(who: isize, buf: *mut Rusage) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let rusage = match who {
+        RUSAGE_SELF => {
+            // include this syscall so far
+            inner.account_time(false);
+            inner.update_maxrss();
+            Rusage::new(inner.user_time, inner.kernel_time, &inner.rusage)
+        }
+        RUSAGE_CHILDREN => Rusage::new(
+            inner.children_user_time,
+            inner.children_kernel_time,
+            &inner.children_rusage,
+        ),
+        _ => return -1,
+    };
+    let token = inner.get_user_token();
+    drop(inner);
+    match write_to_user(token, buf, &rusage) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Fill `buf` with the names of the kernel and the machine
+pub fn sys_uname(buf: *mut Utsname) -> isize {
+    let utsname = Utsname {
+        sysname: utsname_field("rCore-lab"),
+        nodename: utsname_field("qemu-virt"),
+        release: utsname_field(env!("CARGO_PKG_VERSION")),
+        version: utsname_field(concat!("#1 ", env!("BUILD_TIME"))),
+        machine: utsname_field("riscv64"),
+    };
+    match write_to_user(current_user_token(), buf, &utsname) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Store the system-wide statistics into `info`
+pub fn sys_sysinfo(info: *mut SysInfo) -> isize {
+    let heap = kernel_heap_stats();
+    let frames = frame_stats();
+    let sysinfo = SysInfo {
+        uptime: get_time_ms() / 1000,
+        total_frames: frames.total,
+        free_frames: frame_free_count(),
+        peak_frames: frames.peak,
+        frame_failures: frames.failures,
+        total_heap: heap.total,
+        free_heap: heap.total - heap.in_use,
+        peak_heap: heap.peak,
+        heap_allocations: heap.allocations,
+        procs: live_tasks(),
+        zombies: count_tasks(TaskStatus::Zombie),
+        harts: online_harts(),
+    };
+    match write_to_user(current_user_token(), info, &sysinfo) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Store the memory usage of the current process into `stats`
+pub fn sys_mem_stats(stats: *mut MemStats) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let mem_stats = MemStats {
+        resident_pages: inner.memory_set.resident_pages(),
+        lazy_pages: inner.memory_set.lazy_pages(),
+        shared_pages: inner.memory_set.shared_pages(),
+    };
+    let token = inner.get_user_token();
+    drop(inner);
+    match write_to_user(token, stats, &mem_stats) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Store the time of `clock_id`, -1 for an unknown clock
+pub fn sys_clock_gettime(clock_id: usize, ts: *mut TimeSpec) -> isize {
+    let ns = match clock_id {
+        CLOCK_REALTIME => get_realtime_ns(),
+        CLOCK_MONOTONIC => get_time_ns(),
+        _ => return -1,
+    };
+    match write_to_user(current_user_token(), ts, &TimeSpec::from_ns(ns)) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Set the wall-clock time, only initproc is allowed to
+pub fn sys_settimeofday(tv: *const TimeVal, _tz: usize) -> isize {
+    if current_task().unwrap().getpid() != INITPROC.getpid() {
+        return -1;
+    }
+    let tv: TimeVal = match copy_from_user(current_user_token(), tv) {
+        Ok(tv) => tv,
+        Err(_) => return -1,
+    };
+    if tv.usec >= 1_000_000 {
+        return -1;
+    }
+    let ns = tv
+        .sec
+        .checked_mul(NANO_PER_SEC)
+        .and_then(|ns| ns.checked_add(tv.usec * 1000));
+    match ns {
+        Some(ns) if set_realtime_ns(ns) => 0,
+        _ => -1,
+    }
+}
+
+/// Set the priority of the current task and return it.
+/// The priority must be at least 2, it is inherited by fork and kept by exec.
+pub fn sys_set_priority(prio: isize) -> isize {
+    if prio < 2 {
+        return -1;
+    }
+    set_current_priority(prio as usize);
+    prio
+}
+
+/// Fill the user `TaskInfo` with the syscall counters and the time (in ms)
+/// elapsed since the current task was first dispatched or last ran exec
+pub fn sys_task_info(ti: *mut TaskInfo) -> isize {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let info = TaskInfo {
+        status: TaskStatus::Running,
+        syscall_times: inner.syscall_times_by_id(),
+        time: inner
+            .first_dispatched
+            .map_or(0, |first| get_time_us().saturating_sub(first) / 1000),
+    };
+    let token = inner.get_user_token();
+    drop(inner);
+    match write_to_user(token, ti, &info) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Move the program break by `size` bytes and return the old one, or -1 on failure
+pub fn sys_sbrk(size: isize) -> isize {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().change_program_brk(size);
+    match result {
+        Some(old_brk) => old_brk as isize,
+        None => -1,
+    }
 }
 
 // YOUR JOB: 扩展内核以实现 sys_mmap 和 sys_munmap
@@ -157,25 +922,124 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
     munmap(_start, _len)
 }
 
+/// Id of the shared memory segment of `key`, created with `size` bytes if there is none
+pub fn sys_shmget(key: usize, size: usize) -> isize {
+    shm_get_current(key, size)
+}
+
+/// Map the shared memory segment `shmid` near `addr`, return where it is mapped
+pub fn sys_shmat(shmid: usize, addr: usize) -> isize {
+    shm_attach_current(shmid, addr)
+}
+
+/// Unmap the shared memory segment attached at `addr`
+pub fn sys_shmdt(addr: usize) -> isize {
+    shm_detach_current(addr)
+}
+
+#[cfg(feature = "selftest")]
+/// Keep an extra reference to the child `pid` if `hold` is not 0, or drop it otherwise
+pub fn sys_hold_task(pid: usize, hold: usize) -> isize {
+    hold_task(pid, hold != 0)
+}
+
+/// Trace the syscalls of `pid`, the current process or a child of it, if `on` is not 0
+pub fn sys_strace(pid: usize, on: usize) -> isize {
+    set_strace(pid, on != 0)
+}
+
+/// Power the machine off, reporting `failure` through the exit status of QEMU. Only initproc
+/// may do it, or any process with the `shutdown_any` feature. Return -1 unless allowed
+pub fn sys_shutdown(failure: usize) -> isize {
+    if !cfg!(feature = "shutdown_any") && current_task().unwrap().getpid() != INITPROC.getpid() {
+        return -1;
+    }
+    power_off(failure != 0)
+}
+
+/// Set the kernel log level to `level`, from 0 (off) to 5 (trace), for the modules whose
+/// path starts with the string at `target`, or globally if `target` is null. Only initproc
+/// may do it, or any process with the `klogctl_any` feature. Return the previous level
+pub fn sys_klogctl(level: usize, target: *const u8) -> isize {
+    if !cfg!(feature = "klogctl_any") && current_task().unwrap().getpid() != INITPROC.getpid() {
+        return -1;
+    }
+    let target = if target.is_null() {
+        None
+    } else {
+        match translated_str(current_user_token(), target) {
+            Ok(target) => Some(target),
+            Err(_) => return -1,
+        }
+    };
+    match logging::set_level(level, target.as_deref()) {
+        Some(previous) => previous as isize,
+        None => -1,
+    }
+}
+
+/// Change the permission of the mapped pages in `[start, start + len)`
+pub fn sys_mprotect(start: usize, len: usize, port: usize) -> isize {
+    mprotect(start, len, port)
+}
+
+/// Copy a null-terminated array of user string pointers into kernel strings.
+/// A null `argv` means no strings, and `None` is returned if the strings
+/// and the pointers to them would take more than `max_size` bytes.
+fn translated_args(token: usize, mut argv: *const usize, max_size: usize) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    if argv.is_null() {
+        return Some(args);
+    }
+    // the terminating null pointer
+    let mut size = size_of::<usize>();
+    loop {
+        let arg_ptr: usize = copy_from_user(token, argv).ok()?;
+        if arg_ptr == 0 {
+            break;
+        }
+        let arg = translated_str(token, arg_ptr as *const u8).ok()?;
+        size += size_of::<usize>() + arg.len() + 1;
+        if size > max_size {
+            return None;
+        }
+        args.push(arg);
+        unsafe {
+            argv = argv.add(1);
+        }
+    }
+    Some(args)
+}
+
 //
 // YOUR JOB: 实现 sys_spawn 系统调用
 // ALERT: 注意在实现 SPAWN 时不需要复制父进程地址空间，SPAWN != FORK + EXEC
-pub fn sys_spawn(_path: *const u8) -> isize {
+pub fn sys_spawn(_path: *const u8, argv: *const usize, envp: *const usize) -> isize {
     let token = current_user_token();
-    let path = translated_str(token, _path);
+    let path = match translated_str(token, _path) {
+        Ok(path) => path,
+        Err(_) => return -1,
+    };
+    let args = match translated_args(token, argv, MAX_ARGS_SIZE) {
+        Some(args) => args,
+        None => return -1,
+    };
+    let envs = match translated_args(token, envp, MAX_ENV_SIZE) {
+        Some(envs) => envs,
+        None => return -1,
+    };
+    let task = current_task().unwrap();
+    if !task.inner_exclusive_access().fits_nproc_limit() {
+        return -1;
+    }
     if let Some(data) = get_app_data_by_name(path.as_str()) {
-        let task = Arc::new(TaskControlBlock::new(data));
-        let mut inner = task.inner_exclusive_access();
-        let parent = current_task().unwrap();
-        let mut parent_inner = parent.inner_exclusive_access();
-        inner.parent = Some(Arc::downgrade(&parent));
-        parent_inner.children.push(task.clone());
-        drop(parent_inner);
-        drop(inner);
-        let pid = task.pid.0 as isize;
-        task.exec(data);
-        add_task(task);
-        pid
+        let new_task = match task.spawn(&path, data, args, envs) {
+            Ok(task) => task,
+            Err(err) => return exec_error(err),
+        };
+        let new_pid = new_task.pid.0;
+        add_task(new_task);
+        new_pid as isize
     } else {
         -1
     }
@@ -3,157 +3,193 @@
 //! It is only used to manage processes and schedule process based on ready queue.
 //! Other CPU process monitoring functions are in Processor.
 
-use core::convert::TryFrom;
-
-use super::{current_task, TaskControlBlock};
-use crate::mm::{MapPermission, VirtAddr, VPNRange};
-use crate::sync::UPSafeCell;
-use alloc::collections::VecDeque;
+#[cfg(feature = "sched_mlfq")]
+pub use super::mlfq::TaskManager;
+use super::processor::{current_task, set_need_resched};
+#[cfg(not(feature = "sched_mlfq"))]
+use super::task::TaskControlBlockInner;
+use super::{TaskControlBlock, TASK_NAME_LEN};
+use crate::sync::SpinLock;
+#[cfg(not(feature = "sched_mlfq"))]
+use crate::timer::get_time_us;
+#[cfg(not(feature = "sched_mlfq"))]
+use alloc::collections::BinaryHeap;
 use alloc::sync::Arc;
+#[cfg(not(feature = "sched_mlfq"))]
+use alloc::vec::Vec;
+#[cfg(not(feature = "sched_mlfq"))]
+use core::cmp::{Ordering, Reverse};
 use lazy_static::*;
 
-pub struct TaskManager {
-    ready_queue: VecDeque<Arc<TaskControlBlock>>,
+#[cfg(not(feature = "sched_mlfq"))]
+/// A ready task with the stride it had when it was enqueued.
+///
+/// The stride of a task only moves when it is switched out, and its priority can
+/// only be changed by itself while running, so the snapshot never gets stale:
+/// a new priority takes effect through the pass added at the next switch out.
+struct StrideEntry {
+    stride: usize,
+    /// Enqueue order, keeps tasks with equal strides FIFO
+    seq: usize,
+    task: Arc<TaskControlBlock>,
 }
 
-impl TryFrom<usize> for MapPermission {
-    type Error = ();
+#[cfg(not(feature = "sched_mlfq"))]
+impl PartialEq for StrideEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
 
-    fn try_from(port: usize) -> Result<Self, Self::Error> {
-        // 第 0 位表示是否可读，第 1 位表示是否可写，第 2 位表示是否可执行。其他位无效且必须为 0
-        // port & !0x7 != 0 (port 其余位必须为0)
-        // port & 0x7 = 0 (这样的内存无意义)
-        if (port & !0x7) != 0 || (port & 0x7) == 0 {
-            return Err(());
-        }
-        let mut permission = MapPermission::U;
-        if port & 1 != 0 {
-            permission |= MapPermission::R;
-        }
-        if port & 2 != 0 {
-            permission |= MapPermission::W;
-        }
-        if port & 4 != 0 {
-            permission |= MapPermission::X;
-        }
-        Ok(permission)
+#[cfg(not(feature = "sched_mlfq"))]
+impl Eq for StrideEntry {}
+
+#[cfg(not(feature = "sched_mlfq"))]
+impl PartialOrd for StrideEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(not(feature = "sched_mlfq"))]
+impl Ord for StrideEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        stride_cmp(self.stride, other.stride).then(self.seq.cmp(&other.seq))
     }
 }
 
-// YOUR JOB: FIFO->Stride
-/// A simple FIFO scheduler.
+#[cfg(not(feature = "sched_mlfq"))]
+/// Compare two strides that may have wrapped around.
+///
+/// As the priority is at least 2, a pass is at most `BIG_STRIDE / 2`, and so are the
+/// differences between the strides of ready tasks, which makes the signed difference
+/// tell which task is behind even across a wrap.
+fn stride_cmp(a: usize, b: usize) -> Ordering {
+    (a.wrapping_sub(b) as isize).cmp(&0)
+}
+
+#[cfg(not(feature = "sched_mlfq"))]
+pub struct TaskManager {
+    ready_queue: BinaryHeap<Reverse<StrideEntry>>,
+    next_seq: usize,
+}
+
+#[cfg(not(feature = "sched_mlfq"))]
+/// A stride scheduler: the ready task with the minimal stride runs next.
 impl TaskManager {
     pub fn new() -> Self {
         Self {
-            ready_queue: VecDeque::new(),
+            ready_queue: BinaryHeap::new(),
+            next_seq: 0,
         }
     }
     /// Add process back to ready queue
     pub fn add(&mut self, task: Arc<TaskControlBlock>) {
-        self.ready_queue.push_back(task);
+        let mut inner = task.inner_exclusive_access();
+        inner.sched_stats.last_enqueue_time = get_time_us();
+        let stride = inner.stride;
+        drop(inner);
+        self.ready_queue.push(Reverse(StrideEntry {
+            stride,
+            seq: self.next_seq,
+            task,
+        }));
+        self.next_seq += 1;
     }
-    /// Take a process out of the ready queue
+    /// Take the process with the minimal stride out of the ready queue
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
-        self.ready_queue.pop_front()
+        self.ready_queue.pop().map(|Reverse(entry)| entry.task)
     }
-
-    // LAB2
-    pub fn mmap(&self, start: usize, len: usize, port: usize) -> isize {
-        // TODO
-        // start 需要映射的虚存起始地址，要求按页对齐
-        // len 映射字节长度，可以为 0
-        // port：第 0 位表示是否可读，第 1 位表示是否可写，第 2 位表示是否可执行。其他位无效且必须为 0
-        let start_va = VirtAddr::from(start);
-        // start 没有按页大小对齐
-        if start_va.page_offset() != 0 {
-            return -1;
-        }
-        let end_va = VirtAddr::from(start + len);
-        let permission = MapPermission::try_from(port);
-        if let Err(_) = permission {
-            return -1;
-        }
-        let perm = permission.unwrap();
-
-        // len为0, 直接返回成功
-        if len == 0 {
-            return 0;
-        }
-
-        let current_task = current_task().unwrap();
-        let memory_set = &mut current_task.inner_exclusive_access().memory_set;
-        let vpn_start = start_va.floor();
-        let vpn_end = end_va.ceil();
-        let vpn_range = VPNRange::new(vpn_start, vpn_end);
-
-        // [start, start + len) 中存在已经被映射的页
-        for vpn in vpn_range {
-            if let Some(pte) = memory_set.translate(vpn) {
-                if pte.is_valid() {
-                    return -1;
-                }
-            }
-        }
-
-        memory_set.insert_framed_area(start_va, end_va, perm);
-        0
+    /// The ready processes in the order they will run
+    pub fn ready_tasks(&self) -> Vec<Arc<TaskControlBlock>> {
+        let mut entries: Vec<&StrideEntry> = self
+            .ready_queue
+            .iter()
+            .map(|Reverse(entry)| entry)
+            .collect();
+        entries.sort();
+        entries.iter().map(|entry| entry.task.clone()).collect()
     }
+    /// Whether the ready task `task` is behind the running task `current` by more than one pass
+    pub fn should_preempt(task: &TaskControlBlockInner, current: &TaskControlBlockInner) -> bool {
+        current.stride.wrapping_sub(task.stride) as isize > current.pass as isize
+    }
+    /// Charge a timer tick to the running task, which is always preempted
+    pub fn tick(&mut self, _current: &mut TaskControlBlockInner) -> bool {
+        true
+    }
+}
 
-    pub fn munmap(&self, start: usize, len: usize) -> isize {
-        let start_va = VirtAddr::from(start);
-        // start 没有按页大小对齐
-        if start_va.page_offset() != 0 {
-            return -1;
-        }
-        let end_va = VirtAddr::from(start + len);
-        // len为0, 直接返回成功
-        if len == 0 {
-            return 0;
-        }
-
-        let current_task = current_task().unwrap();
-        let memory_set = &mut current_task.inner_exclusive_access().memory_set;
-        let vpn_start = start_va.floor();
-        let vpn_end = end_va.ceil();
-        let vpn_range = VPNRange::new(vpn_start, vpn_end);
-
-        // [start, start + len) 中存在未被映射的虚存。
-        for vpn in vpn_range {
-            if let Some(pte) = memory_set.translate(vpn) {
-                if !pte.is_valid() {
-                    return -1;
-                }
-            } else {
-                return -1;
-            }
-        }
+/// Snapshot of the scheduler, shared with user space
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SchedStat {
+    pub ready: usize,
+    pub blocked: usize,
+    /// Context switches since boot
+    pub context_switches: usize,
+    pub quantum_us: usize,
+}
 
-        for vpn in vpn_range {
-            memory_set.munmap(vpn);
-        }
-        0
-    }
+/// A task in the scheduler snapshot, shared with user space
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SchedEntry {
+    pub pid: usize,
+    pub priority: usize,
+    pub stride: usize,
+    pub name: [u8; TASK_NAME_LEN],
 }
 
 lazy_static! {
-    /// TASK_MANAGER instance through lazy_static!
-    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
-        unsafe { UPSafeCell::new(TaskManager::new()) };
+    /// TASK_MANAGER instance through lazy_static!, shared by all harts
+    pub static ref TASK_MANAGER: SpinLock<TaskManager> = SpinLock::new(TaskManager::new());
 }
 
 pub fn add_task(task: Arc<TaskControlBlock>) {
-    TASK_MANAGER.exclusive_access().add(task);
+    if let Some(current) = current_task() {
+        let preempt = TaskManager::should_preempt(
+            &task.inner_exclusive_access(),
+            &current.inner_exclusive_access(),
+        );
+        if preempt {
+            set_need_resched();
+        }
+    }
+    trace!("[kernel] pid {} is ready", task.getpid());
+    TASK_MANAGER.lock().add(task);
 }
 
 pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
-    TASK_MANAGER.exclusive_access().fetch()
-}
-
-// LAB2
-pub fn mmap(start: usize, len: usize, port: usize) -> isize {
-    TASK_MANAGER.exclusive_access().mmap(start, len, port)
+    let task = TASK_MANAGER.lock().fetch();
+    if let Some(task) = &task {
+        trace!("[kernel] pid {} is picked to run", task.getpid());
+    }
+    task
 }
 
-pub fn munmap(start: usize, len: usize) -> isize {
-    TASK_MANAGER.exclusive_access().munmap(start, len)
+#[cfg(all(feature = "selftest", not(feature = "sched_mlfq")))]
+/// Check the stride comparison on 8-bit strides kept in the top byte of a usize
+pub fn stride_test() {
+    const SHIFT: u32 = usize::BITS - 8;
+    let stride8 = |s: usize| s << SHIFT;
+    // the example from the handout: 255 + 10 wraps to 9, and the 250 task runs next
+    let a = stride8(255).wrapping_add(stride8(10));
+    let b = stride8(250);
+    assert_eq!(a, stride8(9));
+    assert_eq!(stride_cmp(b, a), Ordering::Less);
+    // a long run wraps many times, and the task with half the pass keeps running twice as often
+    let passes = [stride8(10), stride8(5)];
+    let mut strides = [stride8(255), stride8(250)];
+    let mut runs = [0usize; 2];
+    for _ in 0..30000 {
+        let next = match stride_cmp(strides[0], strides[1]) {
+            Ordering::Greater => 1,
+            _ => 0,
+        };
+        runs[next] += 1;
+        strides[next] = strides[next].wrapping_add(passes[next]);
+    }
+    assert!((runs[1] as isize - 2 * runs[0] as isize).abs() <= 2);
+    info!("stride_test passed!");
 }
@@ -0,0 +1,77 @@
+//! A multilevel feedback queue scheduler, chosen by the `sched_mlfq` feature
+//!
+//! New tasks and tasks giving up the CPU by themselves enter the top queue,
+//! a task using up its quantum moves one queue down, and every task is
+//! boosted back to the top queue periodically so that none starves.
+
+use super::task::TaskControlBlockInner;
+use super::TaskControlBlock;
+use crate::timer::get_time_us;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Quantum (in timer ticks) of each queue, from the top one down
+const QUANTA: [usize; 4] = [1, 2, 4, 8];
+/// Number of timer ticks between two boosts
+const BOOST_TICKS: usize = 100;
+
+pub struct TaskManager {
+    queues: [VecDeque<Arc<TaskControlBlock>>; QUANTA.len()],
+    ticks: usize,
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            queues: Default::default(),
+            ticks: 0,
+        }
+    }
+    /// Add process back to the queue of its level
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        let mut inner = task.inner_exclusive_access();
+        inner.sched_stats.last_enqueue_time = get_time_us();
+        let level = inner.queue_level;
+        drop(inner);
+        self.queues[level].push_back(task);
+    }
+    /// Take the first process out of the highest non-empty queue
+    pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.queues.iter_mut().find_map(|queue| queue.pop_front())
+    }
+    /// The ready processes in the order they will run
+    pub fn ready_tasks(&self) -> Vec<Arc<TaskControlBlock>> {
+        self.queues.iter().flatten().cloned().collect()
+    }
+    /// Whether the ready task `task` is in a higher queue than the running task `current`
+    pub fn should_preempt(task: &TaskControlBlockInner, current: &TaskControlBlockInner) -> bool {
+        task.queue_level < current.queue_level
+    }
+    /// Charge a timer tick to the running task `current`, return whether it has used up
+    /// its quantum, in which case it is moved one queue down
+    pub fn tick(&mut self, current: &mut TaskControlBlockInner) -> bool {
+        self.ticks += 1;
+        if self.ticks % BOOST_TICKS == 0 {
+            self.boost();
+            current.give_up_quantum();
+        }
+        current.quantum_used += 1;
+        if current.quantum_used < QUANTA[current.queue_level] {
+            return false;
+        }
+        current.queue_level = (current.queue_level + 1).min(QUANTA.len() - 1);
+        current.quantum_used = 0;
+        true
+    }
+    /// Move every ready task back to the top queue
+    fn boost(&mut self) {
+        let (top, lower) = self.queues.split_at_mut(1);
+        for queue in lower {
+            for task in queue.drain(..) {
+                task.inner_exclusive_access().give_up_quantum();
+                top[0].push_back(task);
+            }
+        }
+    }
+}
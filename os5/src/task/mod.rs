@@ -4,35 +4,137 @@
 //! (such as syscall or clock interrupt).
 //! By suspending or exiting the current process, you can
 //! modify the process state, manage the process queue through TASK_MANAGER,
-//! and switch the control flow through PROCESSOR.
+//! and switch the control flow through the PROCESSORS of each hart.
 //!
 //! Be careful when you see [`__switch`]. Control flow around this function
 //! might not be what you expect.
+//!
+//! The inner of a task is always taken before those of its children, see
+//! [`TaskControlBlock::child_inner_exclusive_access`]. The only exception is an exiting
+//! task, which takes initproc after itself to hand its children over. The paths looking
+//! at arbitrary tasks, like kill, the scheduler snapshot and the OOM killer, find them
+//! in the registry of live tasks, and use [`TaskControlBlock::try_inner_exclusive_access`]
+//! to skip a task whose inner is held.
+//!
+//! Exec keeps the identity of a process and starts the statistics of the new program afresh:
+//!
+//! - kept: pid, parent and children, process group, priority with the stride and the MLFQ
+//!   queue, resource limits, open files, signal mask, alarm, strace, CPU times and
+//!   resource usage
+//! - reset: syscall counters, time of the first dispatch (to the exec), scheduling
+//!   statistics, heap, signal handlers and pending signals except SIGKILL
+//! - replaced: the name, by the basename of the new program
 
 mod context;
 mod manager;
+#[cfg(feature = "sched_mlfq")]
+mod mlfq;
+mod oom;
 mod pid;
 mod processor;
+mod registry;
+mod rlimit;
+mod signal;
 mod switch;
 #[allow(clippy::module_inception)]
 mod task;
 
+use crate::config::{CLOCK_FREQ, PAGE_SIZE};
 use crate::loader::get_app_data_by_name;
+use crate::mm::{frame_stats, shm_get, shm_segment, VirtAddr};
+use crate::sbi::shutdown;
+#[cfg(feature = "selftest")]
+use crate::sync::UPSafeCell;
+use crate::timer::{add_timer, get_quantum_us, get_time, get_time_us, remove_timer, WakeReason};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::iter;
 use lazy_static::*;
 use manager::fetch_task;
+use registry::{find_task, register_task, registered_tasks};
 use switch::__switch;
-pub use task::{TaskControlBlock, TaskStatus};
+pub use task::{task_name, ResourceUsage, SchedStats, TaskControlBlock, TaskStatus, TASK_NAME_LEN};
 
 pub use context::TaskContext;
 pub use manager::*;
-pub use pid::{pid_alloc, KernelStack, PidHandle};
+pub use oom::retry_on_oom;
+pub use pid::{
+    kernel_stack_containing, live_tasks, pid_alloc, tasks_created, KernelStack, PidHandle,
+};
 pub use processor::{
-    current_task, current_trap_cx, current_user_token, run_tasks, schedule, take_current_task,
+    current_pid, current_task, current_trap_cx, current_user_token, hart_id, online_harts,
+    run_tasks, schedule, take_current_task, take_need_resched, try_current_task,
 };
+pub use rlimit::*;
+pub use signal::*;
+
+/// Make current task blocked and switch to the next task
+///
+/// The caller must have registered the task somewhere it will be woken up from.
+pub fn block_current_and_run_next() {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.task_status = TaskStatus::Blocked;
+    task_inner.interrupted = false;
+    // blocking before the slice is over must not spare the part used
+    task_inner.charge_stride();
+    task_inner.give_up_quantum();
+    task_inner.rusage.nvcsw += 1;
+    task_inner.switch_out();
+    drop(task_inner);
+    schedule(task_cx_ptr);
+}
+
+/// Wake `parent` up if it is blocked in waitpid for the child `pid`
+fn wake_waiting_parent(parent: &Arc<TaskControlBlock>, pid: usize) {
+    // ++++ access parent TCB exclusively
+    let mut parent_inner = parent.inner_exclusive_access();
+    if let Some(waiting_for) = parent_inner.waiting_for {
+        if waiting_for == -1 || waiting_for as usize == pid {
+            parent_inner.waiting_for = None;
+            parent_inner.task_status = TaskStatus::Ready;
+            drop(parent_inner);
+            add_task(parent.clone());
+        }
+    }
+    // ++++ release parent TCB
+}
 
 /// Make current task suspended and switch to the next task
 pub fn suspend_current_and_run_next() {
+    requeue_current_and_run_next(true);
+}
+
+/// Make current task suspended as it has used up its quantum, and switch to the next task
+pub fn preempt_current_and_run_next() {
+    requeue_current_and_run_next(false);
+}
+
+/// Charge a timer tick to the current task, and switch to the next task if it is preempted
+pub fn tick_current_and_run_next() {
+    let task = current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let expired = TASK_MANAGER.lock().tick(&mut task_inner);
+    drop(task_inner);
+    drop(task);
+    if expired {
+        preempt_current_and_run_next();
+    }
+}
+
+/// The MLFQ queue the current task is in, `None` with the stride scheduler
+pub fn current_queue_level() -> Option<usize> {
+    if cfg!(feature = "sched_mlfq") {
+        Some(current_task().unwrap().inner_exclusive_access().queue_level)
+    } else {
+        None
+    }
+}
+
+/// Put current task back to the ready queue and switch to the next task,
+/// back to the top queue of MLFQ if it gives up the CPU by itself
+fn requeue_current_and_run_next(voluntary: bool) {
     // There must be an application running.
     let task = take_current_task().unwrap();
 
@@ -41,6 +143,15 @@ pub fn suspend_current_and_run_next() {
     let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
     // Change status to Ready
     task_inner.task_status = TaskStatus::Ready;
+    // charge the part of the time slice it has just consumed
+    task_inner.charge_stride();
+    if voluntary {
+        task_inner.give_up_quantum();
+        task_inner.rusage.nvcsw += 1;
+    } else {
+        task_inner.rusage.nivcsw += 1;
+    }
+    task_inner.switch_out();
     drop(task_inner);
     // ---- release current PCB
 
@@ -50,33 +161,565 @@ pub fn suspend_current_and_run_next() {
     schedule(task_cx_ptr);
 }
 
+/// Charge the time since the last switch between user and kernel to the current task,
+/// as user time if it is coming from user space
+pub fn account_current_time(from_user: bool) {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().account_time(from_user);
+}
+
+/// Increase the invocation counter of `syscall_id` for the current task.
+///
+/// The inner is only borrowed for the increment, so the handler is free to lock it.
+pub fn record_current_syscall(syscall_id: usize) {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().record_syscall(syscall_id);
+}
+
+/// Scheduling statistics of the current task, including the time slice it is running
+pub fn current_sched_stats() -> SchedStats {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let mut stats = inner.sched_stats;
+    stats.total_running_us += get_time_us() - stats.last_schedule_time;
+    stats
+}
+
+/// Change the scheduling priority of the current task
+pub fn set_current_priority(priority: usize) {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().set_priority(priority);
+}
+
+/// Number of processes in `status`, leaving out a task whose inner is held
+pub fn count_tasks(status: TaskStatus) -> usize {
+    registered_tasks()
+        .iter()
+        .filter(|task| {
+            task.try_inner_exclusive_access()
+                .map_or(false, |inner| inner.task_status == status)
+        })
+        .count()
+}
+
+/// Take a snapshot of the scheduler, with the current task followed by the ready tasks
+/// in the order they will run, leaving out a task whose inner is held
+pub fn sched_snapshot() -> (SchedStat, Vec<SchedEntry>) {
+    let ready = TASK_MANAGER.lock().ready_tasks();
+    let current = current_task().unwrap();
+    let entries = iter::once(&current)
+        .chain(ready.iter())
+        .filter_map(|task| {
+            let inner = task.try_inner_exclusive_access()?;
+            Some(SchedEntry {
+                pid: task.getpid(),
+                priority: inner.priority,
+                stride: inner.stride,
+                name: inner.name,
+            })
+        })
+        .collect();
+    let stat = SchedStat {
+        ready: ready.len(),
+        blocked: count_tasks(TaskStatus::Blocked),
+        context_switches: processor::context_switches(),
+        quantum_us: get_quantum_us(),
+    };
+    (stat, entries)
+}
+
+/// Send `signum` to the process `pid`.
+///
+/// SIGKILL makes the target exit with code `-signum` the next time it is about to return to
+/// user space, and a blocked target is woken up for that. SIGSTOP and SIGCONT freeze and
+/// thaw the target, other signals are left pending until the target returns to user space,
+/// and wake the target up if it is blocked, does not mask them and would not ignore them.
+pub fn kill_task(pid: usize, signum: usize) -> isize {
+    if !valid_signum(signum) || pid == INITPROC.getpid() {
+        return -1;
+    }
+    match find_task(pid) {
+        Some(task) => signal_task(task, signum),
+        None => -1,
+    }
+}
+
+/// Send the valid `signum` to `task` as described in [`kill_task`]
+fn signal_task(task: Arc<TaskControlBlock>, signum: usize) -> isize {
+    // ---- access target TCB exclusively
+    let mut inner = task.inner_exclusive_access();
+    if inner.is_zombie() {
+        return 0;
+    }
+    let wake = match signum {
+        SIGKILL => {
+            inner.killed_by = Some(signum);
+            true
+        }
+        SIGSTOP => {
+            inner.frozen = true;
+            false
+        }
+        _ => {
+            if signum == SIGCONT {
+                inner.frozen = false;
+            }
+            let flag = SignalFlags::from_signum(signum);
+            inner.signals.insert(flag);
+            inner.woken_by(flag)
+        }
+    };
+    drop(inner);
+    // ---- release target TCB
+    if wake {
+        interrupt_blocked(task);
+    }
+    0
+}
+
+/// Kill the children of the current task and all their descendants with SIGKILL, leaving
+/// the zombies among them for initproc to reap once they are orphans. The tree is
+/// collected first, holding one inner at a time, as killing takes the inner of each task
+pub fn kill_current_descendants() {
+    let task = current_task().unwrap();
+    let mut stack = task.inner_exclusive_access().children.clone();
+    let mut descendants = Vec::new();
+    while let Some(task) = stack.pop() {
+        stack.extend(task.inner_exclusive_access().children.iter().cloned());
+        descendants.push(task);
+    }
+    for task in descendants {
+        signal_task(task, SIGKILL);
+    }
+}
+
+/// Make `task` ready again if it is blocked, before what it waits for happens
+fn interrupt_blocked(task: Arc<TaskControlBlock>) {
+    let mut inner = task.inner_exclusive_access();
+    if inner.task_status != TaskStatus::Blocked {
+        return;
+    }
+    inner.waiting_for = None;
+    inner.interrupted = true;
+    inner.task_status = TaskStatus::Ready;
+    drop(inner);
+    remove_timer(&task, WakeReason::Sleep);
+    add_task(task);
+}
+
+#[cfg(feature = "selftest")]
+lazy_static! {
+    /// Extra references to tasks taken by `hold_task`, with the pid of the holder
+    static ref HELD_TASKS: UPSafeCell<Vec<(usize, Arc<TaskControlBlock>)>> =
+        unsafe { UPSafeCell::new(Vec::new()) };
+}
+
+#[cfg(feature = "selftest")]
+/// Keep an extra reference to the child `pid` of the current task if `hold`,
+/// or drop the one it kept otherwise. Only meant for debugging the lifetime of TCBs.
+pub fn hold_task(pid: usize, hold: bool) -> isize {
+    let task = current_task().unwrap();
+    let holder = task.getpid();
+    let mut held = HELD_TASKS.exclusive_access();
+    if hold {
+        let inner = task.inner_exclusive_access();
+        match inner.children.iter().find(|child| child.getpid() == pid) {
+            Some(child) => held.push((holder, child.clone())),
+            None => return -1,
+        }
+    } else {
+        match held
+            .iter()
+            .position(|(by, task)| *by == holder && task.getpid() == pid)
+        {
+            Some(idx) => drop(held.remove(idx)),
+            None => return -1,
+        }
+    }
+    0
+}
+
+#[cfg(feature = "selftest")]
+/// Drop the references kept by the exiting task `holder`
+fn release_held_tasks(holder: usize) {
+    let released: Vec<_> = {
+        let mut held = HELD_TASKS.exclusive_access();
+        let (released, kept) = held.drain(..).partition(|(by, _)| *by == holder);
+        *held = kept;
+        released
+    };
+    // the last reference may go away here, which frees the TCB outside of the borrow
+    drop(released);
+}
+
+/// Turn the syscall tracing of `pid` on or off, -1 unless it is the current task or a child of it
+pub fn set_strace(pid: usize, on: bool) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if task.getpid() == pid {
+        inner.strace = on;
+        return 0;
+    }
+    match inner.children.iter().find(|child| child.getpid() == pid) {
+        Some(child) => {
+            task.child_inner_exclusive_access(child).strace = on;
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Process group of `task`, `None` if its inner is held
+fn pgid_of(task: &TaskControlBlock) -> Option<usize> {
+    task.try_inner_exclusive_access().map(|inner| inner.pgid)
+}
+
+/// Move `pid`, or the current task if 0, into the process group `pgid`, or into a new
+/// group led by itself if `pgid` is 0 or its own pid. Only the current task or a child of
+/// it which has not run exec may be moved, and only into a group which exists already
+/// unless it leads the new one
+pub fn set_pgid(pid: usize, pgid: usize) -> isize {
+    let task = current_task().unwrap();
+    let pid = if pid == 0 { task.getpid() } else { pid };
+    let pgid = if pgid == 0 { pid } else { pgid };
+    if pgid != pid
+        && !registered_tasks()
+            .iter()
+            .any(|member| pgid_of(member) == Some(pgid))
+    {
+        return -1;
+    }
+    let mut inner = task.inner_exclusive_access();
+    if task.getpid() == pid {
+        inner.pgid = pgid;
+        return 0;
+    }
+    let child = match inner.children.iter().find(|child| child.getpid() == pid) {
+        Some(child) => child,
+        None => return -1,
+    };
+    let mut child_inner = task.child_inner_exclusive_access(child);
+    if child_inner.execed {
+        return -1;
+    }
+    child_inner.pgid = pgid;
+    0
+}
+
+/// Process group of `pid`, or of the current task if 0, -1 if there is no such task
+pub fn get_pgid(pid: usize) -> isize {
+    let task = current_task().unwrap();
+    if pid == 0 || pid == task.getpid() {
+        return task.inner_exclusive_access().pgid as isize;
+    }
+    match find_task(pid) {
+        Some(target) => pgid_of(&target).map_or(-1, |pgid| pgid as isize),
+        None => -1,
+    }
+}
+
+/// Send `signum` to every process in the group `pgid` like [`kill_task`], -1 if there is
+/// none. The members are collected from the registry before any of them is signalled, so
+/// that the registry is not held while their inners are taken
+pub fn kill_group(pgid: usize, signum: usize) -> isize {
+    if !valid_signum(signum) {
+        return -1;
+    }
+    let members: Vec<_> = registered_tasks()
+        .into_iter()
+        .filter(|task| !Arc::ptr_eq(task, &INITPROC) && pgid_of(task) == Some(pgid))
+        .collect();
+    if members.is_empty() {
+        return -1;
+    }
+    for task in members {
+        signal_task(task, signum);
+    }
+    0
+}
+
+/// Whether the syscalls of the current task are traced
+pub fn current_strace() -> bool {
+    current_task().map_or(false, |task| task.inner_exclusive_access().strace)
+}
+
+/// mmap in the address space of the current task, -1 if it would exceed `RLIMIT_AS`
+pub fn mmap(start: usize, len: usize, port: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let pages = len / PAGE_SIZE + (len % PAGE_SIZE != 0) as usize;
+    if !inner.fits_as_limit(pages) {
+        return -1;
+    }
+    inner.memory_set.mmap(start, len, port)
+}
+
+/// munmap in the address space of the current task
+pub fn munmap(start: usize, len: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.memory_set.munmap(start, len)
+}
+
+/// Create or look up the shared memory segment of `key`, return its id or -1
+pub fn shm_get_current(key: usize, size: usize) -> isize {
+    match shm_get(key, size) {
+        Ok((shmid, created)) => {
+            if let Some(segment) = created {
+                let task = current_task().unwrap();
+                task.inner_exclusive_access().shm_created.push(segment);
+            }
+            shmid as isize
+        }
+        Err(_) => -1,
+    }
+}
+
+/// Attach the shared memory segment `shmid` to the current task, return its address or -1
+pub fn shm_attach_current(shmid: usize, hint: usize) -> isize {
+    let segment = match shm_segment(shmid) {
+        Some(segment) => segment,
+        None => return -1,
+    };
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    match inner.memory_set.attach_shm(hint, segment) {
+        Ok(start_va) => usize::from(start_va) as isize,
+        Err(_) => -1,
+    }
+}
+
+/// Detach the shared memory segment attached at `addr` from the current task
+pub fn shm_detach_current(addr: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.memory_set.detach_shm(addr) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// mprotect in the address space of the current task
+pub fn mprotect(start: usize, len: usize, port: usize) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.memory_set.mprotect(start, len, port)
+}
+
+/// Map the lazy page of the current task containing `addr` on its first access,
+/// copy the copy-on-write page on its first write, or grow the user stack down to it.
+/// `write` tells a store fault from the others. Return false if the fault is a real one,
+/// a task killed for want of memory meanwhile exits in `trap_return`.
+pub fn handle_page_fault(addr: usize, write: bool) -> bool {
+    let vpn = VirtAddr::from(addr).floor();
+    let handled = retry_on_oom(|| {
+        let task = current_task().unwrap();
+        let mut inner = task.inner_exclusive_access();
+        let stack_pages = inner.rlimits.of(RLIMIT_STACK).cur_pages();
+        let handled = inner.memory_set.handle_lazy_fault(vpn, write)
+            || inner.memory_set.handle_cow_fault(vpn)
+            || inner.memory_set.grow_stack(vpn, stack_pages);
+        if handled {
+            inner.rusage.minflt += 1;
+            inner.update_maxrss();
+            Ok(())
+        } else {
+            Err(())
+        }
+    });
+    handled.is_ok() || current_killed_by().is_some()
+}
+
+/// Whether a fault at `addr` is an overflow of the user stack of the current task,
+/// either into the guard page or somewhere the stack could not grow down to
+pub fn is_current_stack_overflow(addr: usize) -> bool {
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    inner
+        .memory_set
+        .is_below_stack(VirtAddr::from(addr).floor())
+}
+
+/// Prepare `[start, start + len)` of the current task for the kernel to read or `write`,
+/// return false if no frame is left. A bad range is left for the checked translation to reject.
+pub fn populate_current_user(start: usize, len: usize, write: bool) -> bool {
+    let end = match start.checked_add(len) {
+        Some(end) => end,
+        None => return true,
+    };
+    retry_on_oom(|| {
+        let task = current_task().unwrap();
+        let mut inner = task.inner_exclusive_access();
+        if inner
+            .memory_set
+            .populate(VirtAddr::from(start), VirtAddr::from(end), write)
+        {
+            Ok(())
+        } else {
+            Err(())
+        }
+    })
+    .is_ok()
+}
+
+/// Make SIGALRM go off for the current task in `seconds`, or cancel the alarm if it is 0.
+/// Return the seconds left before the previous alarm, rounded up.
+pub fn set_current_alarm(seconds: usize) -> usize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let now = get_time();
+    let remaining = inner.alarm_deadline.map_or(0, |deadline| {
+        (deadline.saturating_sub(now) + CLOCK_FREQ - 1) / CLOCK_FREQ
+    });
+    remove_timer(&task, WakeReason::Alarm);
+    inner.alarm_deadline = match seconds {
+        0 => None,
+        _ => Some(now.saturating_add(seconds.saturating_mul(CLOCK_FREQ))),
+    };
+    if let Some(deadline) = inner.alarm_deadline {
+        add_timer(deadline, &task, WakeReason::Alarm);
+    }
+    remaining
+}
+
+/// Raise SIGALRM for `task` whose alarm has gone off, and wake it up if it is blocked
+/// and does not mask the signal, like [`kill_task`] does
+pub fn raise_alarm(task: Arc<TaskControlBlock>) {
+    let mut inner = task.inner_exclusive_access();
+    if inner.is_zombie() {
+        return;
+    }
+    inner.alarm_deadline = None;
+    inner.signals.insert(SignalFlags::SIGALRM);
+    let wake = inner.woken_by(SignalFlags::SIGALRM);
+    drop(inner);
+    if wake {
+        interrupt_blocked(task);
+    }
+}
+
+/// Block the current task until a signal is pending which runs its handler or kills it.
+///
+/// Only such a signal wakes a blocked task up, see `woken_by`,
+/// the pending signals are looked at again in case the wake up came from elsewhere.
+pub fn pause_current() {
+    loop {
+        let task = current_task().unwrap();
+        // ---- access current TCB exclusively
+        let inner = task.inner_exclusive_access();
+        if inner.has_interrupting_signal() {
+            return;
+        }
+        drop(inner);
+        // ---- release current TCB
+        drop(task);
+        block_current_and_run_next();
+    }
+}
+
+/// The signal which killed the current task, if any
+pub fn current_killed_by() -> Option<usize> {
+    current_task().unwrap().inner_exclusive_access().killed_by
+}
+
+/// Whether a signal has woken the current task up from its last block
+pub fn current_interrupted() -> bool {
+    current_task().unwrap().inner_exclusive_access().interrupted
+}
+
+/// Deliver the pending signals of the current task before it returns to user space.
+///
+/// A frozen task keeps giving up the CPU here until it is continued or killed.
+pub fn handle_current_signals() {
+    loop {
+        let task = current_task().unwrap();
+        // ---- access current TCB exclusively
+        let mut inner = task.inner_exclusive_access();
+        if inner.killed_by.is_some() {
+            return;
+        }
+        if !inner.frozen {
+            inner.deliver_signal();
+            return;
+        }
+        drop(inner);
+        // ---- release current TCB
+        drop(task);
+        suspend_current_and_run_next();
+    }
+}
+
+/// Print a summary of the run and power the machine off, making QEMU exit with a
+/// non-zero status on `failure`. The console needs no flushing as every character has
+/// gone out through SBI by the time `print` returns
+pub fn power_off(failure: bool) -> ! {
+    let frames = frame_stats();
+    println!(
+        "[kernel] Shutting down: {} processes created, peak {} frames, {} context switches",
+        tasks_created(),
+        frames.peak,
+        processor::context_switches(),
+    );
+    shutdown(failure)
+}
+
 /// Exit current task, recycle process resources and switch to the next task
 pub fn exit_current_and_run_next(exit_code: i32) {
     // take from Processor
     let task = take_current_task().unwrap();
+    assert!(
+        !Arc::ptr_eq(&task, &INITPROC),
+        "initproc exited with code {}",
+        exit_code
+    );
     // **** access current TCB exclusively
     let mut inner = task.inner_exclusive_access();
     // Change status to Zombie
     inner.task_status = TaskStatus::Zombie;
     // Record exit code
     inner.exit_code = exit_code;
-    // do not move to its parent but under initproc
+    // a pending alarm would keep the memory of the TCB until it goes off
+    if inner.alarm_deadline.take().is_some() {
+        remove_timer(&task, WakeReason::Alarm);
+    }
+    // do not move to its parent but under initproc, which keeps reaping zombies.
+    // The TCBs are always taken in the order current, initproc, children.
 
     // ++++++ access initproc TCB exclusively
+    let mut orphan_zombie = None;
     {
         let mut initproc_inner = INITPROC.inner_exclusive_access();
         for child in inner.children.iter() {
-            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            let mut child_inner = task.child_inner_exclusive_access(child);
+            child_inner.parent = Some(Arc::downgrade(&INITPROC));
+            if child_inner.is_zombie() {
+                orphan_zombie = Some(child.getpid());
+            }
             initproc_inner.children.push(child.clone());
         }
     }
     // ++++++ release parent PCB
 
     inner.children.clear();
+    // the parent may be blocked in waitpid for us, and initproc for the zombies it adopted
+    if let Some(parent) = inner.parent.as_ref().and_then(|parent| parent.upgrade()) {
+        wake_waiting_parent(&parent, task.getpid());
+    }
+    if let Some(pid) = orphan_zombie {
+        wake_waiting_parent(&INITPROC, pid);
+    }
+    // the parent reads the resident peak from the zombie
+    inner.update_maxrss();
     // deallocate user space
     inner.memory_set.recycle_data_pages();
+    // close the descriptors, so pipes see their ends go away before the zombie is reaped
+    inner.fd_table.clear();
+    inner.shm_created.clear();
     drop(inner);
     // **** release current PCB
+    #[cfg(feature = "selftest")]
+    release_held_tasks(task.getpid());
     // drop task manually to maintain rc correctly
     drop(task);
     // we do not have to save task context
@@ -89,11 +732,13 @@ lazy_static! {
     ///
     /// the name "initproc" may be changed to any other app name like "usertests",
     /// but we have user_shell, so we don't need to change it.
-    pub static ref INITPROC: Arc<TaskControlBlock> = Arc::new(TaskControlBlock::new(
-        get_app_data_by_name("ch5b_initproc").unwrap()
-    ));
+    pub static ref INITPROC: Arc<TaskControlBlock> = Arc::new(
+        TaskControlBlock::new("ch5b_initproc", get_app_data_by_name("ch5b_initproc").unwrap())
+            .unwrap()
+    );
 }
 
 pub fn add_initproc() {
+    register_task(&INITPROC);
     add_task(INITPROC.clone());
 }
@@ -0,0 +1,69 @@
+//! Out-of-memory killer
+//!
+//! When a frame allocation fails somewhere the kernel can back out of, like a page
+//! fault or a fork, the process with the most resident pages is killed with SIGKILL
+//! and its user pages are released right away, then the operation is retried once.
+//! If the victim is the current process, or runs in user space on another hart, it
+//! keeps its pages until it exits on its way back to the kernel.
+
+use super::registry::registered_tasks;
+use super::{current_task, kill_task, TaskControlBlock, TaskStatus, INITPROC, SIGKILL};
+use crate::mm::frame_stats;
+use alloc::sync::Arc;
+
+/// The live process with the most resident pages other than initproc and those killed already
+fn pick_victim() -> Option<(Arc<TaskControlBlock>, usize)> {
+    let mut victim: Option<(Arc<TaskControlBlock>, usize)> = None;
+    for task in registered_tasks() {
+        let inner = match task.try_inner_exclusive_access() {
+            Some(inner) => inner,
+            None => continue,
+        };
+        if Arc::ptr_eq(&task, &INITPROC) || inner.is_zombie() || inner.killed_by.is_some() {
+            continue;
+        }
+        let pages = inner.memory_set.resident_pages();
+        drop(inner);
+        if victim.as_ref().map_or(true, |(_, most)| pages > *most) {
+            victim = Some((task, pages));
+        }
+    }
+    victim
+}
+
+/// Kill a process to make room, return whether the frames of another process than the
+/// current one were released so that retrying may succeed
+fn oom_kill() -> bool {
+    let (victim, pages) = match pick_victim() {
+        Some(victim) => victim,
+        None => return false,
+    };
+    kprintln!(
+        "[kernel] out of memory, killed process {} with {} resident pages",
+        victim.label(),
+        pages
+    );
+    kill_task(victim.getpid(), SIGKILL);
+    let current = current_task().unwrap();
+    if Arc::ptr_eq(&victim, &current) {
+        return false;
+    }
+    let mut inner = victim.inner_exclusive_access();
+    if inner.task_status == TaskStatus::Running {
+        return false;
+    }
+    // it will never return to user space, only the kernel pages are left for its exit
+    inner.memory_set.release_user_pages();
+    true
+}
+
+/// Run `op`, and if it failed because a frame allocation has failed meanwhile, kill a process
+/// and run it once more. `op` must back out cleanly on failure and not hold any TCB
+pub fn retry_on_oom<T, E>(mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let failures = frame_stats().failures;
+    let result = op();
+    if result.is_ok() || frame_stats().failures == failures || !oom_kill() {
+        return result;
+    }
+    op()
+}
@@ -3,50 +3,78 @@
 //! Assign PID to the process here. At the same time, the position of the application KernelStack
 //! is determined according to the PID.
 
-use crate::config::{KERNEL_STACK_SIZE, PAGE_SIZE, TRAMPOLINE};
-use crate::mm::{MapPermission, VirtAddr, KERNEL_SPACE};
-use crate::sync::UPSafeCell;
-use alloc::vec::Vec;
+use super::registry::unregister_task;
+use crate::config::{KERNEL_STACK_SIZE, MAX_PID, NPROC, PAGE_SIZE, TRAMPOLINE};
+use crate::mm::{MapPermission, MemoryError, VirtAddr, KERNEL_SPACE};
+use crate::sync::SpinLock;
+use alloc::collections::BinaryHeap;
+use core::cmp::Reverse;
 use lazy_static::*;
 
-/// Process identifier allocator using stack allocation
+/// Process identifier allocator handing out the smallest free PID
 struct PidAllocator {
     /// A new PID to be assigned
     current: usize,
-    /// Recycled PID sequence
-    recycled: Vec<usize>,
+    /// Recycled PIDs, all below `current`
+    recycled: BinaryHeap<Reverse<usize>>,
+    /// PIDs handed out since boot
+    allocated: usize,
 }
 
 impl PidAllocator {
     pub fn new() -> Self {
         PidAllocator {
             current: 0,
-            recycled: Vec::new(),
+            recycled: BinaryHeap::new(),
+            allocated: 0,
         }
     }
-    pub fn alloc(&mut self) -> PidHandle {
-        if let Some(pid) = self.recycled.pop() {
-            PidHandle(pid)
-        } else {
+    /// Take the smallest free PID, or `None` if there are `NPROC` live processes
+    /// or all of `0..MAX_PID` are in use
+    pub fn alloc(&mut self) -> Option<PidHandle> {
+        let pid = if self.in_use() >= NPROC {
+            None
+        } else if let Some(Reverse(pid)) = self.recycled.pop() {
+            Some(PidHandle(pid))
+        } else if self.current < MAX_PID {
             self.current += 1;
-            PidHandle(self.current - 1)
+            Some(PidHandle(self.current - 1))
+        } else {
+            None
+        };
+        if pid.is_some() {
+            self.allocated += 1;
         }
+        pid
+    }
+    /// Number of PIDs currently held by tasks, which live as long as their TCBs
+    pub fn in_use(&self) -> usize {
+        self.current - self.recycled.len()
     }
     pub fn dealloc(&mut self, pid: usize) {
         assert!(pid < self.current);
         assert!(
-            !self.recycled.iter().any(|ppid| *ppid == pid),
+            !self.recycled.iter().any(|&Reverse(ppid)| ppid == pid),
             "pid {} has been deallocated!",
             pid
         );
-        self.recycled.push(pid);
+        self.recycled.push(Reverse(pid));
     }
 }
 
 lazy_static! {
     /// Pid allocator instance through lazy_static!
-    static ref PID_ALLOCATOR: UPSafeCell<PidAllocator> =
-        unsafe { UPSafeCell::new(PidAllocator::new()) };
+    static ref PID_ALLOCATOR: SpinLock<PidAllocator> = SpinLock::new(PidAllocator::new());
+}
+
+/// Number of live tasks, running, ready, blocked or zombie
+pub fn live_tasks() -> usize {
+    PID_ALLOCATOR.lock().in_use()
+}
+
+/// Number of tasks created since boot, initproc included
+pub fn tasks_created() -> usize {
+    PID_ALLOCATOR.lock().allocated
 }
 
 /// Abstract structure of PID
@@ -55,12 +83,13 @@ pub struct PidHandle(pub usize);
 impl Drop for PidHandle {
     fn drop(&mut self) {
         //println!("drop pid {}", self.0);
-        PID_ALLOCATOR.exclusive_access().dealloc(self.0);
+        unregister_task(self.0);
+        PID_ALLOCATOR.lock().dealloc(self.0);
     }
 }
 
-pub fn pid_alloc() -> PidHandle {
-    PID_ALLOCATOR.exclusive_access().alloc()
+pub fn pid_alloc() -> Option<PidHandle> {
+    PID_ALLOCATOR.lock().alloc()
 }
 
 /// Return (bottom, top) of a kernel stack in kernel space.
@@ -70,21 +99,35 @@ pub fn kernel_stack_position(app_id: usize) -> (usize, usize) {
     (bottom, top)
 }
 
+/// Bounds of the kernel stack holding `addr`, if it is on one
+pub fn kernel_stack_containing(addr: usize) -> Option<(usize, usize)> {
+    if addr >= TRAMPOLINE {
+        return None;
+    }
+    let app_id = (TRAMPOLINE - addr) / (KERNEL_STACK_SIZE + PAGE_SIZE);
+    let (bottom, top) = kernel_stack_position(app_id);
+    if app_id < MAX_PID && (bottom..top).contains(&addr) {
+        Some((bottom, top))
+    } else {
+        None
+    }
+}
+
 /// KernelStack corresponding to PID
 pub struct KernelStack {
     pid: usize,
 }
 
 impl KernelStack {
-    pub fn new(pid_handle: &PidHandle) -> Self {
+    pub fn new(pid_handle: &PidHandle) -> Result<Self, MemoryError> {
         let pid = pid_handle.0;
         let (kernel_stack_bottom, kernel_stack_top) = kernel_stack_position(pid);
         KERNEL_SPACE.exclusive_access().insert_framed_area(
             kernel_stack_bottom.into(),
             kernel_stack_top.into(),
             MapPermission::R | MapPermission::W,
-        );
-        KernelStack { pid: pid_handle.0 }
+        )?;
+        Ok(KernelStack { pid: pid_handle.0 })
     }
     #[allow(unused)]
     /// Push a variable of type T into the top of the KernelStack and return its raw pointer
@@ -3,15 +3,25 @@
 //! Here, the continuous operation of user apps in CPU is maintained,
 //! the current running state of CPU is recorded,
 //! and the replacement and transfer of control flow of different applications are executed.
-
+//!
+//! Each hart has a [`Processor`] of its own, found by the hart id which is kept in `tp`
+//! from the kernel entry on. The trap entry leaves `tp` alone, so user programs must
+//! not change it, which holds as they make no use of thread locals.
 
 use super::__switch;
-use super::{fetch_task, TaskStatus};
+use super::pid::live_tasks;
+use super::{fetch_task, power_off, TaskStatus};
 use super::{TaskContext, TaskControlBlock};
-use crate::sync::UPSafeCell;
-use crate::trap::TrapContext;
+use crate::config::MAX_HARTS;
+use crate::drivers::handle_external_interrupt;
+use crate::sync::{big_kernel_lock, big_kernel_unlock, UPSafeCell};
+use crate::timer::{check_timer, get_time_us, set_next_trigger};
+use crate::trap::{set_handling_trap, TrapContext};
 use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use lazy_static::*;
+use riscv::register::sip;
 
 /// Processor management structure
 pub struct Processor {
@@ -19,6 +29,10 @@ pub struct Processor {
     current: Option<Arc<TaskControlBlock>>,
     /// The basic control flow of each core, helping to select and switch process
     idle_task_cx: TaskContext,
+    /// Number of switches back to the idle control flow since boot
+    context_switches: usize,
+    /// A task which should run before the current one has become ready
+    need_resched: bool,
 }
 
 impl Processor {
@@ -26,12 +40,15 @@ impl Processor {
         Self {
             current: None,
             idle_task_cx: TaskContext::zero_init(),
+            context_switches: 0,
+            need_resched: false,
         }
     }
     fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
         &mut self.idle_task_cx as *mut _
     }
     pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        CURRENT_PID[hart_id()].store(NO_PID, Ordering::Relaxed);
         self.current.take()
     }
     pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
@@ -39,44 +56,133 @@ impl Processor {
     }
 }
 
+/// Id of the hart running this code
+#[cfg(feature = "smp")]
+pub fn hart_id() -> usize {
+    let id;
+    unsafe {
+        core::arch::asm!("mv {}, tp", out(reg) id);
+    }
+    id
+}
+
+/// Id of the hart running this code
+#[cfg(not(feature = "smp"))]
+pub fn hart_id() -> usize {
+    0
+}
+
+/// Pid of the current task of each hart, or `NO_PID` in the idle control flow.
+/// Kept out of `PROCESSORS` so that it can be read even while the processor is borrowed
+static CURRENT_PID: [AtomicUsize; MAX_HARTS] = [NO_PID_ATOMIC; MAX_HARTS];
+const NO_PID: usize = usize::MAX;
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_PID_ATOMIC: AtomicUsize = AtomicUsize::new(NO_PID);
+
+/// Number of harts which have entered [`run_tasks`]
+static ONLINE_HARTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Pid of the current task of this hart, safe to call from the panic and allocation error handlers
+pub fn current_pid() -> Option<usize> {
+    match CURRENT_PID[hart_id()].load(Ordering::Relaxed) {
+        NO_PID => None,
+        pid => Some(pid),
+    }
+}
+
+/// Number of harts running tasks
+pub fn online_harts() -> usize {
+    ONLINE_HARTS.load(Ordering::Relaxed)
+}
+
 lazy_static! {
-    /// PROCESSOR instance through lazy_static!
-    pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+    /// The Processor of each hart, indexed by the hart id
+    pub static ref PROCESSORS: Vec<UPSafeCell<Processor>> = (0..MAX_HARTS)
+        .map(|_| unsafe { UPSafeCell::new(Processor::new()) })
+        .collect();
+}
+
+/// The Processor of the hart running this code
+fn processor() -> &'static UPSafeCell<Processor> {
+    &PROCESSORS[hart_id()]
 }
 
 /// The main part of process execution and scheduling
 ///
 /// Loop fetch_task to get the process that needs to run,
-/// and switch the process through __switch
+/// and switch the process through __switch.
+/// Every hart ends up here holding the big kernel lock
 pub fn run_tasks() {
+    ONLINE_HARTS.fetch_add(1, Ordering::Relaxed);
     loop {
-        let mut processor = PROCESSOR.exclusive_access();
+        let mut processor = processor().exclusive_access();
         if let Some(task) = fetch_task() {
             let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
             // access coming task TCB exclusively
             let mut task_inner = task.inner_exclusive_access();
             let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
             task_inner.task_status = TaskStatus::Running;
+            if task_inner.first_dispatched.is_none() {
+                task_inner.first_dispatched = Some(get_time_us());
+            }
+            task_inner.switch_in();
             drop(task_inner);
             // release coming task TCB manually
+            CURRENT_PID[hart_id()].store(task.getpid(), Ordering::Relaxed);
             processor.current = Some(task);
+            processor.need_resched = false;
             // release processor manually
             drop(processor);
             unsafe {
                 __switch(idle_task_cx_ptr, next_task_cx_ptr);
             }
+        } else {
+            drop(processor);
+            if live_tasks() == 0 {
+                println!("[kernel] No task left.");
+                power_off(false);
+            }
+            idle();
         }
     }
 }
 
+/// Wait for the next interrupt while no task is ready, then wake the sleepers and
+/// the console readers up.
+///
+/// Interrupts stay disabled in S mode as the kernel trap entry cannot return,
+/// but `wfi` still wakes up once an enabled interrupt is pending.
+/// The other harts may enter the kernel meanwhile.
+fn idle() {
+    big_kernel_unlock();
+    unsafe {
+        core::arch::asm!("wfi");
+    }
+    big_kernel_lock();
+    let sip = sip::read();
+    check_timer();
+    if sip.stimer() {
+        // re-arming the timer clears the pending interrupt
+        set_next_trigger();
+    }
+    if sip.sext() {
+        handle_external_interrupt();
+    }
+}
+
 /// Get current task through take, leaving a None in its place
 pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
-    PROCESSOR.exclusive_access().take_current()
+    processor().exclusive_access().take_current()
 }
 
 /// Get a copy of the current task
 pub fn current_task() -> Option<Arc<TaskControlBlock>> {
-    PROCESSOR.exclusive_access().current()
+    processor().exclusive_access().current()
+}
+
+/// Get a copy of the current task unless the processor is borrowed, for the panic handler
+pub fn try_current_task() -> Option<Arc<TaskControlBlock>> {
+    processor().try_exclusive_access()?.current()
 }
 
 /// Get token of the address space of current task
@@ -94,12 +200,34 @@ pub fn current_trap_cx() -> &'static mut TrapContext {
         .get_trap_cx()
 }
 
+/// Ask for the current task to be preempted at the next trap return
+pub fn set_need_resched() {
+    processor().exclusive_access().need_resched = true;
+}
+
+/// Whether the current task should be preempted, clearing the request
+pub fn take_need_resched() -> bool {
+    core::mem::take(&mut processor().exclusive_access().need_resched)
+}
+
+/// Number of context switches on all harts since boot
+pub fn context_switches() -> usize {
+    PROCESSORS
+        .iter()
+        .map(|processor| processor.exclusive_access().context_switches)
+        .sum()
+}
+
 /// Return to idle control flow for new scheduling
 pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
-    let mut processor = PROCESSOR.exclusive_access();
+    let mut processor = processor().exclusive_access();
     let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+    processor.context_switches += 1;
     drop(processor);
+    // the task goes on with its trap when switched back, maybe on another hart
+    let trap = set_handling_trap(None);
     unsafe {
         __switch(switched_task_cx_ptr, idle_task_cx_ptr);
     }
+    set_handling_trap(trap);
 }
@@ -0,0 +1,45 @@
+//! Registry of the live tasks by pid
+//!
+//! A task is registered as soon as its TCB is put into an `Arc`, before it can be
+//! scheduled, and drops out when its pid is freed along with the TCB, before the pid can
+//! be handed out again. Only `Weak` references are kept, so the registry never keeps a
+//! reaped zombie alive, and a TCB being dropped is already missing from the lookups.
+
+use super::TaskControlBlock;
+use crate::sync::SpinLock;
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use lazy_static::*;
+
+lazy_static! {
+    static ref TASK_REGISTRY: SpinLock<BTreeMap<usize, Weak<TaskControlBlock>>> =
+        SpinLock::new(BTreeMap::new());
+}
+
+/// Make `task` reachable by its pid
+pub fn register_task(task: &Arc<TaskControlBlock>) {
+    TASK_REGISTRY
+        .lock()
+        .insert(task.getpid(), Arc::downgrade(task));
+}
+
+/// Forget the task `pid`, whose TCB is being dropped
+pub fn unregister_task(pid: usize) {
+    TASK_REGISTRY.lock().remove(&pid);
+}
+
+/// The live task `pid`, whatever its state
+pub fn find_task(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    TASK_REGISTRY.lock().get(&pid).and_then(Weak::upgrade)
+}
+
+/// All the live tasks in the order of their pids. They are collected before the registry
+/// is released, so that the caller may act on them without holding it
+pub fn registered_tasks() -> Vec<Arc<TaskControlBlock>> {
+    TASK_REGISTRY
+        .lock()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! Resource limits of a process
+//!
+//! A process can only read and lower its own limits, or raise the soft one up
+//! to the hard one. They are inherited by the children and kept across exec.
+
+use crate::config::{DEFAULT_AS_LIMIT, PAGE_SIZE, USER_STACK_LIMIT};
+
+/// Limit of the bytes the user stack may grow to
+pub const RLIMIT_STACK: usize = 3;
+/// Limit of the children a process may have until they are reaped
+pub const RLIMIT_NPROC: usize = 6;
+/// Limit of the bytes a process may map through mmap and its heap
+pub const RLIMIT_AS: usize = 9;
+/// The resources with a limit, in the order they are kept in [`RLimits`]
+const RESOURCES: [usize; 3] = [RLIMIT_STACK, RLIMIT_NPROC, RLIMIT_AS];
+/// No limit at all
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// Soft and hard limit of a resource, shared with user space
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RLimit {
+    /// The limit enforced
+    pub rlim_cur: usize,
+    /// Ceiling of `rlim_cur`, which may not be raised again
+    pub rlim_max: usize,
+}
+
+impl RLimit {
+    /// The default limit of `resource`
+    fn default_of(resource: usize) -> Self {
+        match resource {
+            // the stack cannot grow past the area kept for it
+            RLIMIT_STACK => Self {
+                rlim_cur: USER_STACK_LIMIT,
+                rlim_max: USER_STACK_LIMIT,
+            },
+            RLIMIT_AS => Self {
+                rlim_cur: DEFAULT_AS_LIMIT,
+                rlim_max: RLIM_INFINITY,
+            },
+            _ => Self {
+                rlim_cur: RLIM_INFINITY,
+                rlim_max: RLIM_INFINITY,
+            },
+        }
+    }
+    /// The soft limit in pages, rounded down
+    pub fn cur_pages(&self) -> usize {
+        self.rlim_cur / PAGE_SIZE
+    }
+    /// Whether a process holding `self` may change it to `new`
+    pub fn allows(&self, new: &RLimit) -> bool {
+        new.rlim_cur <= new.rlim_max && new.rlim_max <= self.rlim_max
+    }
+}
+
+/// The limits of a process, one for each of `RESOURCES`
+#[derive(Clone, Copy, Debug)]
+pub struct RLimits([RLimit; RESOURCES.len()]);
+
+impl Default for RLimits {
+    fn default() -> Self {
+        Self(RESOURCES.map(RLimit::default_of))
+    }
+}
+
+impl RLimits {
+    /// The limit of `resource`, `None` if it has none
+    pub fn get(&self, resource: usize) -> Option<RLimit> {
+        let slot = RESOURCES.iter().position(|&id| id == resource)?;
+        Some(self.0[slot])
+    }
+    /// Change the limit of `resource`, which must be one of those [`get`](Self::get) knows
+    pub fn set(&mut self, resource: usize, limit: RLimit) {
+        let slot = RESOURCES.iter().position(|&id| id == resource).unwrap();
+        self.0[slot] = limit;
+    }
+    /// The limit of `resource`, which must be one of those [`get`](Self::get) knows
+    pub fn of(&self, resource: usize) -> RLimit {
+        self.get(resource).unwrap()
+    }
+}
@@ -0,0 +1,88 @@
+//! Implementation of signals
+//!
+//! Each signal `n` is the bit `1 << n` of [`SignalFlags`]. A task keeps the
+//! pending signals and a table of handlers, and the lowest pending signal is
+//! delivered in [`crate::trap::trap_return`] right before going back to user space.
+
+pub const MAX_SIG: usize = 31;
+
+pub const SIGKILL: usize = 9;
+pub const SIGCONT: usize = 18;
+pub const SIGSTOP: usize = 19;
+
+bitflags! {
+    /// Set of signals, the bit `1 << n` stands for signal `n`
+    pub struct SignalFlags: u32 {
+        const SIGDEF = 1;
+        const SIGHUP = 1 << 1;
+        const SIGINT = 1 << 2;
+        const SIGQUIT = 1 << 3;
+        const SIGILL = 1 << 4;
+        const SIGTRAP = 1 << 5;
+        const SIGABRT = 1 << 6;
+        const SIGBUS = 1 << 7;
+        const SIGFPE = 1 << 8;
+        const SIGKILL = 1 << 9;
+        const SIGUSR1 = 1 << 10;
+        const SIGSEGV = 1 << 11;
+        const SIGUSR2 = 1 << 12;
+        const SIGPIPE = 1 << 13;
+        const SIGALRM = 1 << 14;
+        const SIGTERM = 1 << 15;
+        const SIGSTKFLT = 1 << 16;
+        const SIGCHLD = 1 << 17;
+        const SIGCONT = 1 << 18;
+        const SIGSTOP = 1 << 19;
+        const SIGTSTP = 1 << 20;
+        const SIGTTIN = 1 << 21;
+        const SIGTTOU = 1 << 22;
+        const SIGURG = 1 << 23;
+        const SIGXCPU = 1 << 24;
+        const SIGXFSZ = 1 << 25;
+        const SIGVTALRM = 1 << 26;
+        const SIGPROF = 1 << 27;
+        const SIGWINCH = 1 << 28;
+        const SIGIO = 1 << 29;
+        const SIGPWR = 1 << 30;
+        const SIGSYS = 1 << 31;
+    }
+}
+
+impl SignalFlags {
+    /// The set containing only `signum`, which must be in `1..=MAX_SIG`
+    pub fn from_signum(signum: usize) -> Self {
+        Self::from_bits_truncate(1 << signum)
+    }
+    /// The signal with the lowest number in the set
+    pub fn lowest(&self) -> Option<usize> {
+        (1..=MAX_SIG).find(|&signum| self.contains(Self::from_signum(signum)))
+    }
+    /// Whether the default action of the signal is to ignore it rather than to terminate
+    pub fn ignored_by_default(&self) -> bool {
+        self.intersects(Self::SIGCHLD | Self::SIGCONT | Self::SIGURG | Self::SIGWINCH)
+    }
+}
+
+/// Action taken on a signal, shared with user space
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SignalAction {
+    /// Address of the handler in user space, 0 for the default action
+    pub handler: usize,
+    /// Signals blocked while the handler runs
+    pub mask: SignalFlags,
+}
+
+impl Default for SignalAction {
+    fn default() -> Self {
+        Self {
+            handler: 0,
+            mask: SignalFlags::empty(),
+        }
+    }
+}
+
+/// Whether `signum` is a valid signal number
+pub fn valid_signum(signum: usize) -> bool {
+    (1..=MAX_SIG).contains(&signum)
+}
@@ -1,14 +1,31 @@
 //! Types related to task management & Functions for completely changing TCB
 
+use super::registry::register_task;
+use super::rlimit::{RLimits, RLIMIT_AS, RLIMIT_NPROC};
+use super::signal::{SignalAction, SignalFlags, MAX_SIG};
 use super::TaskContext;
 use super::{pid_alloc, KernelStack, PidHandle};
-use crate::config::TRAP_CONTEXT;
-use crate::mm::{MemorySet, PhysPageNum, VirtAddr, KERNEL_SPACE};
+use crate::config::{BIG_STRIDE, DEFAULT_PRIORITY, MAX_FDS, MAX_SYSCALL_NUM, TRAP_CONTEXT};
+use crate::fs::{File, Stdin, Stdout};
+use crate::mm::{
+    translated_refmut, MemoryError, MemorySet, PhysPageNum, ShmSegment, VirtAddr, KERNEL_SPACE,
+};
 use crate::sync::UPSafeCell;
+use crate::syscall::{syscall_slot, SYSCALL_IDS};
+use crate::timer::{get_quantum_us, get_time, get_time_us};
 use crate::trap::{trap_handler, TrapContext};
+use alloc::format;
+use alloc::string::String;
 use alloc::sync::{Arc, Weak};
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefMut;
+use core::mem::size_of;
+
+/// Number of pages the heap `[bottom, brk)` spans
+fn heap_pages(bottom: usize, brk: usize) -> usize {
+    VirtAddr::from(brk).ceil().0 - VirtAddr::from(bottom).floor().0
+}
 
 /// Task control block structure
 ///
@@ -35,6 +52,10 @@ pub struct TaskControlBlockInner {
     pub base_size: usize,
     /// Save task context
     pub task_cx: TaskContext,
+    /// Bottom of the heap, i.e. the initial program break
+    pub heap_bottom: usize,
+    /// Current program break, the heap spans `[heap_bottom, program_brk)`
+    pub program_brk: usize,
     /// Maintain the execution status of the current process
     pub task_status: TaskStatus,
     /// Application address space
@@ -46,6 +67,115 @@ pub struct TaskControlBlockInner {
     pub children: Vec<Arc<TaskControlBlock>>,
     /// It is set when active exit or execution error occurs
     pub exit_code: i32,
+    /// Name of the program for diagnostics, nul-padded
+    pub name: [u8; TASK_NAME_LEN],
+    /// Number of times each syscall has been invoked by the current program,
+    /// indexed by the slot of the syscall in `SYSCALL_IDS`
+    pub syscall_times: [u32; SYSCALL_IDS.len()],
+    /// Number of invocations of unsupported syscalls
+    pub unknown_syscalls: u32,
+    /// Time (in us) when the task was first dispatched, or when it last ran exec
+    pub first_dispatched: Option<usize>,
+    /// Scheduling priority, a larger value gets more CPU time
+    pub priority: usize,
+    /// Accumulated stride, the ready task with the minimal stride runs next
+    pub stride: usize,
+    /// Stride increment charged per time slice, equals `BIG_STRIDE / priority`
+    pub pass: usize,
+    /// Queue of the task in the MLFQ scheduler, 0 for the top one
+    pub queue_level: usize,
+    /// Timer ticks used of the quantum of the current MLFQ queue
+    pub quantum_used: usize,
+    /// The pid (or -1 for any child) the task is blocked in waitpid for
+    pub waiting_for: Option<isize>,
+    /// Whether a signal has woken the task up from its last block before what it waited for
+    pub interrupted: bool,
+    /// The signal that killed the task, it exits before returning to user space
+    pub killed_by: Option<usize>,
+    /// Signals sent to the task but not delivered yet
+    pub signals: SignalFlags,
+    /// Signals kept pending instead of being delivered
+    pub signal_mask: SignalFlags,
+    /// Handler of each signal
+    pub signal_actions: [SignalAction; MAX_SIG + 1],
+    /// The signal whose handler is running
+    pub handling_sig: Option<usize>,
+    /// Trap context interrupted by the running handler, restored by sigreturn
+    pub trap_ctx_backup: Option<TrapContext>,
+    /// Signal mask the running handler was entered with, restored by sigreturn
+    pub signal_mask_backup: SignalFlags,
+    /// Stopped by SIGSTOP until SIGCONT arrives
+    pub frozen: bool,
+    /// `mtime` of the last switch between user and kernel, or of being scheduled in
+    pub time_stamp: usize,
+    /// `mtime` cycles spent in user space
+    pub user_time: usize,
+    /// `mtime` cycles spent in the kernel
+    pub kernel_time: usize,
+    /// Total user time of the reaped children
+    pub children_user_time: usize,
+    /// Total kernel time of the reaped children
+    pub children_kernel_time: usize,
+    /// `mtime` when SIGALRM is raised, set by alarm
+    pub alarm_deadline: Option<usize>,
+    /// How the scheduler has treated the task
+    pub sched_stats: SchedStats,
+    /// Page faults, resident peak and context switches, reported by wait4 and getrusage
+    pub rusage: ResourceUsage,
+    /// Total resource usage of the reaped children, the peak being the largest of theirs
+    pub children_rusage: ResourceUsage,
+    /// Objects referred to by the file descriptors, `None` for a closed descriptor
+    pub fd_table: Vec<Option<Arc<dyn File>>>,
+    /// Shared memory segments created by the process, kept until it exits
+    pub shm_created: Vec<Arc<ShmSegment>>,
+    /// Limits of the stack size, the children and the bytes mapped through mmap and the heap
+    pub rlimits: RLimits,
+    /// Log every syscall with its arguments and result, inherited by the children
+    pub strace: bool,
+    /// Process group, inherited by the children and kept across exec
+    pub pgid: usize,
+    /// Whether the process has run exec, after which its parent cannot move it to
+    /// another group
+    pub execed: bool,
+}
+
+/// Scheduling statistics of a task, shared with user space
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct SchedStats {
+    /// Number of times the task has been switched in
+    pub times_scheduled: usize,
+    /// Time (in us) spent running on the CPU
+    pub total_running_us: usize,
+    /// Time (in us) spent waiting in the ready queue
+    pub total_ready_wait_us: usize,
+    /// Time (in us) when the task was last put into the ready queue
+    pub last_enqueue_time: usize,
+    /// Time (in us) when the task was last switched in
+    pub last_schedule_time: usize,
+}
+
+/// What a task has consumed beside CPU time
+#[derive(Clone, Copy, Default)]
+pub struct ResourceUsage {
+    /// Page faults served, all of them minor as no page is ever read from a disk
+    pub minflt: usize,
+    /// Largest number of frames owned at once
+    pub maxrss: usize,
+    /// Times the task was switched out by yielding or blocking
+    pub nvcsw: usize,
+    /// Times the task was switched out by the timer
+    pub nivcsw: usize,
+}
+
+impl ResourceUsage {
+    /// Fold the usage of a reaped child into the total of the children
+    pub fn add(&mut self, child: &ResourceUsage) {
+        self.minflt += child.minflt;
+        self.maxrss = self.maxrss.max(child.maxrss);
+        self.nvcsw += child.nvcsw;
+        self.nivcsw += child.nivcsw;
+    }
 }
 
 /// Simple access to its internal fields
@@ -67,27 +197,298 @@ impl TaskControlBlockInner {
     pub fn is_zombie(&self) -> bool {
         self.get_status() == TaskStatus::Zombie
     }
+    /// The lowest closed file descriptor, the table grows if every descriptor is in use.
+    /// `None` if the process already has `MAX_FDS` descriptors open.
+    pub fn alloc_fd(&mut self) -> Option<usize> {
+        if let Some(fd) = self.fd_table.iter().position(|file| file.is_none()) {
+            Some(fd)
+        } else if self.fd_table.len() < MAX_FDS {
+            self.fd_table.push(None);
+            Some(self.fd_table.len() - 1)
+        } else {
+            None
+        }
+    }
+    /// The name of the task, up to the first invalid UTF-8 byte
+    pub fn name(&self) -> &str {
+        let len = self
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(TASK_NAME_LEN);
+        match core::str::from_utf8(&self.name[..len]) {
+            Ok(name) => name,
+            Err(e) => core::str::from_utf8(&self.name[..e.valid_up_to()]).unwrap(),
+        }
+    }
+    /// Count one invocation of `syscall_id`, in the unknown bucket if it is unsupported
+    pub fn record_syscall(&mut self, syscall_id: usize) {
+        match syscall_slot(syscall_id) {
+            Some(slot) => self.syscall_times[slot] += 1,
+            None => self.unknown_syscalls += 1,
+        }
+    }
+    /// The syscall counters indexed by syscall id, as reported in `TaskInfo`
+    pub fn syscall_times_by_id(&self) -> [u32; MAX_SYSCALL_NUM] {
+        let mut times = [0; MAX_SYSCALL_NUM];
+        for (slot, &id) in SYSCALL_IDS.iter().enumerate() {
+            times[id] = self.syscall_times[slot];
+        }
+        times
+    }
+    /// Push `args` and then `envs` onto the user stack,
+    /// and pass `argc`, `argv` and `envp` in `a0`, `a1` and `a2`
+    pub fn push_args(&mut self, args: &[String], envs: &[String]) {
+        let token = self.get_user_token();
+        let trap_cx = self.get_trap_cx();
+        let mut user_sp = trap_cx.x[2];
+        let argv_base = push_strings(token, &mut user_sp, args);
+        let envp_base = push_strings(token, &mut user_sp, envs);
+        // make the user stack aligned to 8 bytes
+        user_sp -= user_sp % size_of::<usize>();
+        trap_cx.x[2] = user_sp;
+        trap_cx.x[10] = args.len();
+        trap_cx.x[11] = argv_base;
+        trap_cx.x[12] = envp_base;
+    }
+    /// Move the program break by `size` bytes and return the old one,
+    /// or `None` if the break would go below the heap bottom or the heap cannot grow
+    pub fn change_program_brk(&mut self, size: isize) -> Option<usize> {
+        let old_break = self.program_brk;
+        let new_brk = old_break as isize + size;
+        if new_brk < self.heap_bottom as isize {
+            return None;
+        }
+        if size > 0 {
+            let more = heap_pages(self.heap_bottom, new_brk as usize) - self.heap_pages();
+            if !self.fits_as_limit(more) {
+                return None;
+            }
+        }
+        let result = if size < 0 {
+            self.memory_set
+                .shrink_to(self.heap_bottom.into(), (new_brk as usize).into())
+        } else {
+            self.memory_set
+                .append_to(self.heap_bottom.into(), (new_brk as usize).into())
+        };
+        if result {
+            self.program_brk = new_brk as usize;
+            Some(old_break)
+        } else {
+            None
+        }
+    }
+    /// Number of pages taken by the heap
+    fn heap_pages(&self) -> usize {
+        heap_pages(self.heap_bottom, self.program_brk)
+    }
+    /// Whether `pages` more pages may be mapped through mmap or the heap
+    pub fn fits_as_limit(&self, pages: usize) -> bool {
+        let used = self.memory_set.mmap_pages() + self.heap_pages();
+        used.saturating_add(pages) <= self.rlimits.of(RLIMIT_AS).cur_pages()
+    }
+    /// Whether one more child may be created, the zombies not reaped yet count
+    pub fn fits_nproc_limit(&self) -> bool {
+        self.children.len() < self.rlimits.of(RLIMIT_NPROC).rlim_cur
+    }
+    /// Whether a pending signal which is not blocked would run its handler or kill the
+    /// task once delivered, rather than be ignored
+    pub fn has_interrupting_signal(&self) -> bool {
+        let pending = self.signals - self.signal_mask;
+        self.killed_by.is_some()
+            || (1..=MAX_SIG).any(|signum| {
+                let flag = SignalFlags::from_signum(signum);
+                pending.contains(flag)
+                    && (self.signal_actions[signum].handler != 0 || !flag.ignored_by_default())
+            })
+    }
+    /// Whether the pending `flag` wakes the task up if it is blocked, which only a signal
+    /// that is not blocked and would run its handler or kill the task does
+    pub fn woken_by(&self, flag: SignalFlags) -> bool {
+        let signum = match flag.lowest() {
+            Some(signum) => signum,
+            None => return false,
+        };
+        !self.signal_mask.contains(flag)
+            && (self.signal_actions[signum].handler != 0 || !flag.ignored_by_default())
+    }
+    /// Deliver the lowest pending signal which is not blocked by `signal_mask`.
+    ///
+    /// A signal with a handler redirects the trap context to it and blocks the signal
+    /// itself and the `mask` of its action until sigreturn, a signal without one either
+    /// is ignored or kills the task. No handler is entered while another runs.
+    pub fn deliver_signal(&mut self) {
+        if self.handling_sig.is_some() {
+            return;
+        }
+        while let Some(signum) = (self.signals - self.signal_mask).lowest() {
+            let flag = SignalFlags::from_signum(signum);
+            self.signals.remove(flag);
+            let action = self.signal_actions[signum];
+            if action.handler != 0 {
+                let trap_cx = self.get_trap_cx();
+                self.trap_ctx_backup = Some(*trap_cx);
+                trap_cx.sepc = action.handler;
+                trap_cx.x[10] = signum;
+                self.handling_sig = Some(signum);
+                self.signal_mask_backup = self.signal_mask;
+                self.signal_mask |=
+                    (action.mask | flag) - (SignalFlags::SIGKILL | SignalFlags::SIGSTOP);
+                return;
+            }
+            if !flag.ignored_by_default() {
+                self.killed_by = Some(signum);
+                return;
+            }
+        }
+    }
+    /// Charge the time since the last stamp to user time if `in_user`, or to kernel time
+    pub fn account_time(&mut self, in_user: bool) {
+        let now = get_time();
+        let elapsed = now - self.time_stamp;
+        if in_user {
+            self.user_time += elapsed;
+        } else {
+            self.kernel_time += elapsed;
+        }
+        self.time_stamp = now;
+    }
+    /// Update the statistics when the task is switched in
+    pub fn switch_in(&mut self) {
+        let now = get_time_us();
+        let stats = &mut self.sched_stats;
+        stats.times_scheduled += 1;
+        stats.total_ready_wait_us += now.saturating_sub(stats.last_enqueue_time);
+        stats.last_schedule_time = now;
+        // the time spent waiting in the ready queue is not charged
+        self.time_stamp = get_time();
+    }
+    /// Update the statistics when the task gives up the CPU
+    pub fn switch_out(&mut self) {
+        self.account_time(false);
+        let stats = &mut self.sched_stats;
+        stats.total_running_us += get_time_us() - stats.last_schedule_time;
+    }
+    /// Add the pass scaled by the part of the time slice used since it was scheduled in,
+    /// so that a task yielding early pays less than one preempted by the timer
+    pub fn charge_stride(&mut self) {
+        let quantum = get_quantum_us();
+        let used = (get_time_us() - self.sched_stats.last_schedule_time).min(quantum);
+        // always charge something to guarantee the others make progress
+        let charge = (self.pass * used / quantum).max(1);
+        self.stride = self.stride.wrapping_add(charge);
+    }
+    /// Raise the resident peak to the frames owned now
+    pub fn update_maxrss(&mut self) {
+        self.rusage.maxrss = self.rusage.maxrss.max(self.memory_set.resident_pages());
+    }
+    /// Give up the CPU before the quantum is used up, which moves back to the top MLFQ queue
+    pub fn give_up_quantum(&mut self) {
+        self.queue_level = 0;
+        self.quantum_used = 0;
+    }
+    pub fn set_priority(&mut self, priority: usize) {
+        self.priority = priority;
+        // a huge priority must still make progress in stride
+        self.pass = (BIG_STRIDE / priority).max(1);
+    }
+}
+
+/// Length of the name of a task, including the trailing nul
+pub const TASK_NAME_LEN: usize = 16;
+
+/// `name` truncated to fit a task name, zero-padded
+pub fn task_name(name: &str) -> [u8; TASK_NAME_LEN] {
+    let mut buf = [0; TASK_NAME_LEN];
+    let len = name.len().min(TASK_NAME_LEN - 1);
+    buf[..len].copy_from_slice(&name.as_bytes()[..len]);
+    buf
+}
+
+/// The name of the program at `path`, i.e. its basename
+fn program_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Push `strings` and a null-terminated array of pointers to them below `user_sp`,
+/// return the address of the array
+fn push_strings(token: usize, user_sp: &mut usize, strings: &[String]) -> usize {
+    // the pointers are aligned to 8 bytes
+    *user_sp -= *user_sp % size_of::<usize>();
+    *user_sp -= (strings.len() + 1) * size_of::<usize>();
+    let base = *user_sp;
+    let mut ptrs: Vec<_> = (0..=strings.len())
+        .map(|i| translated_refmut(token, (base + i * size_of::<usize>()) as *mut usize))
+        .collect();
+    *ptrs[strings.len()] = 0;
+    for (i, string) in strings.iter().enumerate() {
+        *user_sp -= string.len() + 1;
+        *ptrs[i] = *user_sp;
+        let mut p = *user_sp;
+        for c in string.as_bytes() {
+            *translated_refmut(token, p as *mut u8) = *c;
+            p += 1;
+        }
+        *translated_refmut(token, p as *mut u8) = 0;
+    }
+    base
 }
 
 impl TaskControlBlock {
     /// Get the mutex to get the RefMut TaskControlBlockInner
+    #[cfg_attr(feature = "debug_upsafecell", track_caller)]
     pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
         self.inner.exclusive_access()
     }
+    /// Like `inner_exclusive_access`, but None if the inner is held already,
+    /// for the paths looking at other tasks which must skip a busy one rather than panic
+    #[cfg_attr(feature = "debug_upsafecell", track_caller)]
+    pub fn try_inner_exclusive_access(&self) -> Option<RefMut<'_, TaskControlBlockInner>> {
+        self.inner.try_exclusive_access()
+    }
+    /// Take the inner of `child` while the inner of `self` is held, checking in debug builds
+    /// that this follows the lock order: `self` is held already and is the parent of `child`
+    #[cfg_attr(feature = "debug_upsafecell", track_caller)]
+    pub fn child_inner_exclusive_access<'a>(
+        &self,
+        child: &'a TaskControlBlock,
+    ) -> RefMut<'a, TaskControlBlockInner> {
+        debug_assert!(
+            self.inner.is_borrowed(),
+            "the inner of pid {} must be held before those of its children",
+            self.getpid()
+        );
+        let inner = child.inner_exclusive_access();
+        debug_assert!(
+            inner
+                .parent
+                .as_ref()
+                .and_then(Weak::upgrade)
+                .map_or(false, |parent| core::ptr::eq(parent.as_ref(), self)),
+            "pid {} is not a child of pid {}",
+            child.getpid(),
+            self.getpid()
+        );
+        inner
+    }
 
     /// Create a new process
     ///
-    /// At present, it is only used for the creation of initproc
-    pub fn new(elf_data: &[u8]) -> Self {
+    /// It is used for the creation of initproc and of spawned children
+    pub fn new(path: &str, elf_data: &[u8]) -> Result<Self, MemoryError> {
         // memory_set with elf program headers/trampoline/trap context/user stack
-        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data)?;
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
             .ppn();
-        // alloc a pid and a kernel stack in kernel space
-        let pid_handle = pid_alloc();
-        let kernel_stack = KernelStack::new(&pid_handle);
+        // alloc a pid and a kernel stack in kernel space,
+        // running out of pids is reported like running out of memory
+        let pid_handle = pid_alloc().ok_or(MemoryError::OutOfMemory)?;
+        // the new process leads a group of its own unless its parent puts it into its own
+        let pgid = pid_handle.0;
+        let kernel_stack = KernelStack::new(&pid_handle)?;
         let kernel_stack_top = kernel_stack.get_top();
         // push a task context which goes to trap_return to the top of kernel stack
         let task_control_block = Self {
@@ -98,11 +499,54 @@ impl TaskControlBlock {
                     trap_cx_ppn,
                     base_size: user_sp,
                     task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    heap_bottom: user_sp,
+                    program_brk: user_sp,
                     task_status: TaskStatus::Ready,
                     memory_set,
                     parent: None,
                     children: Vec::new(),
                     exit_code: 0,
+                    name: task_name(program_name(path)),
+                    syscall_times: [0; SYSCALL_IDS.len()],
+                    unknown_syscalls: 0,
+                    first_dispatched: None,
+                    priority: DEFAULT_PRIORITY,
+                    stride: 0,
+                    pass: BIG_STRIDE / DEFAULT_PRIORITY,
+                    queue_level: 0,
+                    quantum_used: 0,
+                    waiting_for: None,
+                    interrupted: false,
+                    killed_by: None,
+                    signals: SignalFlags::empty(),
+                    signal_mask: SignalFlags::empty(),
+                    signal_actions: [SignalAction::default(); MAX_SIG + 1],
+                    handling_sig: None,
+                    trap_ctx_backup: None,
+                    signal_mask_backup: SignalFlags::empty(),
+                    frozen: false,
+                    time_stamp: 0,
+                    user_time: 0,
+                    kernel_time: 0,
+                    children_user_time: 0,
+                    children_kernel_time: 0,
+                    alarm_deadline: None,
+                    sched_stats: SchedStats::default(),
+                    rusage: ResourceUsage::default(),
+                    children_rusage: ResourceUsage::default(),
+                    fd_table: vec![
+                        // 0 -> stdin
+                        Some(Arc::new(Stdin)),
+                        // 1 -> stdout
+                        Some(Arc::new(Stdout)),
+                        // 2 -> stderr
+                        Some(Arc::new(Stdout)),
+                    ],
+                    shm_created: Vec::new(),
+                    rlimits: RLimits::default(),
+                    strace: false,
+                    pgid,
+                    execed: false,
                 })
             },
         };
@@ -115,12 +559,19 @@ impl TaskControlBlock {
             kernel_stack_top,
             trap_handler as usize,
         );
-        task_control_block
+        Ok(task_control_block)
     }
-    /// Load a new elf to replace the original application address space and start execution
-    pub fn exec(&self, elf_data: &[u8]) {
+    /// Load a new elf to replace the original application address space and start execution.
+    /// The original address space is kept if the new one cannot be built.
+    pub fn exec(
+        &self,
+        path: &str,
+        elf_data: &[u8],
+        args: Vec<String>,
+        envs: Vec<String>,
+    ) -> Result<(), MemoryError> {
         // memory_set with elf program headers/trampoline/trap context/user stack
-        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data)?;
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
@@ -128,10 +579,38 @@ impl TaskControlBlock {
 
         // **** access inner exclusively
         let mut inner = self.inner_exclusive_access();
-        // substitute memory_set
-        inner.memory_set = memory_set;
+        // the peak of the old program stays in the resource usage of the process
+        inner.update_maxrss();
+        // substitute memory_set, the new tree is complete before it is installed and
+        // satp only switches to it in trap_return
+        let old_memory_set = core::mem::replace(&mut inner.memory_set, memory_set);
+        // the kernel runs on its own page table, so the old areas and page-table frames
+        // (the old trap context included) can be freed right away, exactly once
+        drop(old_memory_set);
         // update trap_cx ppn
         inner.trap_cx_ppn = trap_cx_ppn;
+        // the heap of the new program is empty
+        inner.heap_bottom = user_sp;
+        inner.program_brk = user_sp;
+        // the new program starts counting syscalls and its running time from scratch,
+        // see the module doc for what is kept
+        inner.syscall_times = [0; SYSCALL_IDS.len()];
+        inner.unknown_syscalls = 0;
+        let now = get_time_us();
+        inner.first_dispatched = Some(now);
+        inner.sched_stats = SchedStats {
+            last_schedule_time: now,
+            ..SchedStats::default()
+        };
+        // handlers of the old program are gone, and so are the signals it has not handled
+        // yet, but a kill stays fatal
+        inner.signal_actions = [SignalAction::default(); MAX_SIG + 1];
+        inner.handling_sig = None;
+        inner.trap_ctx_backup = None;
+        inner.signals &= SignalFlags::SIGKILL;
+        inner.name = task_name(program_name(path));
+        inner.execed = true;
+        // the file descriptors stay open for the new program
         // initialize trap_cx
         let trap_cx = inner.get_trap_cx();
         *trap_cx = TrapContext::app_init_context(
@@ -141,21 +620,25 @@ impl TaskControlBlock {
             self.kernel_stack.get_top(),
             trap_handler as usize,
         );
+        // arguments of the new program go onto its user stack
+        inner.push_args(&args, &envs);
+        Ok(())
         // **** release inner automatically
     }
     /// Fork from parent to child
-    pub fn fork(self: &Arc<TaskControlBlock>) -> Arc<TaskControlBlock> {
+    pub fn fork(self: &Arc<TaskControlBlock>) -> Result<Arc<TaskControlBlock>, MemoryError> {
         // ---- access parent PCB exclusively
         let mut parent_inner = self.inner_exclusive_access();
         // copy user space(include trap context)
-        let memory_set = MemorySet::from_existed_user(&parent_inner.memory_set);
+        let memory_set = MemorySet::from_existed_user(&mut parent_inner.memory_set)?;
         let trap_cx_ppn = memory_set
             .translate(VirtAddr::from(TRAP_CONTEXT).into())
             .unwrap()
             .ppn();
-        // alloc a pid and a kernel stack in kernel space
-        let pid_handle = pid_alloc();
-        let kernel_stack = KernelStack::new(&pid_handle);
+        // alloc a pid and a kernel stack in kernel space,
+        // running out of pids is reported like running out of memory
+        let pid_handle = pid_alloc().ok_or(MemoryError::OutOfMemory)?;
+        let kernel_stack = KernelStack::new(&pid_handle)?;
         let kernel_stack_top = kernel_stack.get_top();
         let task_control_block = Arc::new(TaskControlBlock {
             pid: pid_handle,
@@ -165,35 +648,117 @@ impl TaskControlBlock {
                     trap_cx_ppn,
                     base_size: parent_inner.base_size,
                     task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    heap_bottom: parent_inner.heap_bottom,
+                    program_brk: parent_inner.program_brk,
                     task_status: TaskStatus::Ready,
                     memory_set,
                     parent: Some(Arc::downgrade(self)),
                     children: Vec::new(),
                     exit_code: 0,
+                    name: parent_inner.name,
+                    syscall_times: [0; SYSCALL_IDS.len()],
+                    unknown_syscalls: 0,
+                    first_dispatched: None,
+                    // the child inherits the priority of its parent
+                    priority: parent_inner.priority,
+                    // starting at zero would put the child ahead of every task that has run
+                    // for a while, the running parent is never ahead of the ready queue
+                    stride: parent_inner.stride,
+                    pass: parent_inner.pass,
+                    queue_level: 0,
+                    quantum_used: 0,
+                    waiting_for: None,
+                    interrupted: false,
+                    killed_by: None,
+                    signals: SignalFlags::empty(),
+                    signal_mask: parent_inner.signal_mask,
+                    // handlers are inherited but pending signals are not
+                    signal_actions: parent_inner.signal_actions,
+                    handling_sig: None,
+                    trap_ctx_backup: None,
+                    signal_mask_backup: SignalFlags::empty(),
+                    frozen: false,
+                    time_stamp: 0,
+                    user_time: 0,
+                    kernel_time: 0,
+                    children_user_time: 0,
+                    children_kernel_time: 0,
+                    alarm_deadline: None,
+                    sched_stats: SchedStats::default(),
+                    rusage: ResourceUsage::default(),
+                    children_rusage: ResourceUsage::default(),
+                    // both processes share the objects behind the descriptors
+                    fd_table: parent_inner.fd_table.clone(),
+                    // the attached segments are shared through the address space
+                    shm_created: Vec::new(),
+                    rlimits: parent_inner.rlimits,
+                    strace: parent_inner.strace,
+                    pgid: parent_inner.pgid,
+                    execed: false,
                 })
             },
         });
         // add child
         parent_inner.children.push(task_control_block.clone());
+        register_task(&task_control_block);
         // modify kernel_sp in trap_cx
         // **** access children PCB exclusively
         let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
         trap_cx.kernel_sp = kernel_stack_top;
         // return
-        task_control_block
+        Ok(task_control_block)
         // ---- release parent PCB automatically
         // **** release children PCB automatically
     }
+    /// Spawn a child running a new elf with `args`, without copying the address space of the parent
+    pub fn spawn(
+        self: &Arc<TaskControlBlock>,
+        path: &str,
+        elf_data: &[u8],
+        args: Vec<String>,
+        envs: Vec<String>,
+    ) -> Result<Arc<TaskControlBlock>, MemoryError> {
+        let task_control_block = Arc::new(TaskControlBlock::new(path, elf_data)?);
+        // **** access children PCB exclusively
+        let mut inner = task_control_block.inner_exclusive_access();
+        inner.parent = Some(Arc::downgrade(self));
+        let parent_inner = self.inner_exclusive_access();
+        inner.rlimits = parent_inner.rlimits;
+        inner.strace = parent_inner.strace;
+        inner.pgid = parent_inner.pgid;
+        // like a forked child, start at the stride of the parent
+        inner.stride = parent_inner.stride;
+        drop(parent_inner);
+        inner.push_args(&args, &envs);
+        drop(inner);
+        // **** release children PCB
+        // add child before it can be scheduled
+        self.inner_exclusive_access()
+            .children
+            .push(task_control_block.clone());
+        register_task(&task_control_block);
+        Ok(task_control_block)
+    }
     pub fn getpid(&self) -> usize {
         self.pid.0
     }
+    /// `pid <pid> [<name>]`, identifying the task in diagnostics
+    pub fn label(&self) -> String {
+        format!(
+            "pid {} [{}]",
+            self.getpid(),
+            self.inner_exclusive_access().name()
+        )
+    }
 }
 
 #[derive(Copy, Clone, PartialEq)]
-/// task status: UnInit, Ready, Running, Exited
+/// task status: UnInit, Ready, Running, Exited, Blocked
 pub enum TaskStatus {
     UnInit,
     Ready,
     Running,
     Zombie,
+    /// Waiting for an event and kept out of the ready queue
+    Blocked,
 }
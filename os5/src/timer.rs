@@ -1,23 +1,238 @@
 //! RISC-V timer-related functionality
 
-use crate::config::CLOCK_FREQ;
+use crate::config::{CLOCK_FREQ, MAX_HARTS};
 use crate::sbi::set_timer;
+use crate::sync::UPSafeCell;
+use crate::task::{add_task, hart_id, raise_alarm, TaskControlBlock, TaskStatus};
+use alloc::collections::BinaryHeap;
+use alloc::sync::{Arc, Weak};
+use core::cmp::Ordering;
+use core::sync::atomic::{self, AtomicUsize};
+use lazy_static::*;
 use riscv::register::time;
 
 const TICKS_PER_SEC: usize = 100;
+const MILLI_PER_SEC: usize = 1_000;
 const MICRO_PER_SEC: usize = 1_000_000;
+pub const NANO_PER_SEC: usize = 1_000_000_000;
+/// Default length of the time slice between two timer interrupts, in microseconds
+const DEFAULT_QUANTUM_US: usize = MICRO_PER_SEC / TICKS_PER_SEC;
+/// Bounds of the time slice, in microseconds
+const MIN_QUANTUM_US: usize = 1_000;
+const MAX_QUANTUM_US: usize = MICRO_PER_SEC;
 
 /// read the `mtime` register
 pub fn get_time() -> usize {
     time::read()
 }
 
+/// get current time in nanoseconds. The product is taken in 128 bits, as `CLOCK_FREQ`
+/// needs not be a multiple of the units, 12.5 MHz on QEMU is not one of 1 MHz
+pub fn get_time_ns() -> usize {
+    (time::read() as u128 * NANO_PER_SEC as u128 / CLOCK_FREQ as u128) as usize
+}
+
 /// get current time in microseconds
 pub fn get_time_us() -> usize {
-    time::read() / (CLOCK_FREQ / MICRO_PER_SEC)
+    get_time_ns() / (NANO_PER_SEC / MICRO_PER_SEC)
+}
+
+/// get current time in milliseconds
+pub fn get_time_ms() -> usize {
+    get_time_ns() / (NANO_PER_SEC / MILLI_PER_SEC)
+}
+
+/// Convert nanoseconds into `mtime` cycles, rounding up so that a deadline is never early
+pub fn ns_to_cycles(ns: usize) -> usize {
+    ((ns as u128 * CLOCK_FREQ as u128 + NANO_PER_SEC as u128 - 1) / NANO_PER_SEC as u128) as usize
+}
+
+/// Convert `mtime` cycles into microseconds
+pub fn cycles_to_us(cycles: usize) -> usize {
+    (cycles as u128 * MICRO_PER_SEC as u128 / CLOCK_FREQ as u128) as usize
+}
+
+/// Convert microseconds into `mtime` cycles
+fn us_to_cycles(us: usize) -> usize {
+    (us as u128 * CLOCK_FREQ as u128 / MICRO_PER_SEC as u128) as usize
+}
+
+lazy_static! {
+    /// Wall-clock time (in ns) at boot, set by settimeofday
+    static ref BOOT_EPOCH_NS: UPSafeCell<usize> = unsafe { UPSafeCell::new(0) };
+}
+
+lazy_static! {
+    /// Length of the time slice (in us), read each time the timer is re-armed
+    static ref QUANTUM_US: UPSafeCell<usize> = unsafe { UPSafeCell::new(DEFAULT_QUANTUM_US) };
+}
+
+/// get the length of the time slice in microseconds
+pub fn get_quantum_us() -> usize {
+    *QUANTUM_US.exclusive_access()
+}
+
+/// Set the length of the time slice to `us` clamped to [1 ms, 1 s], return the length set
+pub fn set_quantum_us(us: usize) -> usize {
+    let us = us.clamp(MIN_QUANTUM_US, MAX_QUANTUM_US);
+    *QUANTUM_US.exclusive_access() = us;
+    us
+}
+
+/// get the wall-clock time in nanoseconds
+pub fn get_realtime_ns() -> usize {
+    *BOOT_EPOCH_NS.exclusive_access() + get_time_ns()
+}
+
+/// Set the wall-clock time to `ns`, which may not be earlier than the time since boot
+pub fn set_realtime_ns(ns: usize) -> bool {
+    match ns.checked_sub(get_time_ns()) {
+        Some(epoch) => {
+            *BOOT_EPOCH_NS.exclusive_access() = epoch;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Convert `mtime` cycles into the clock ticks reported by times
+pub fn clock_ticks(cycles: usize) -> usize {
+    cycles / (CLOCK_FREQ / TICKS_PER_SEC)
+}
+
+/// `mtime` at which the current time slice of each hart ends
+static SLICE_END: [AtomicUsize; MAX_HARTS] = [NO_SLICE; MAX_HARTS];
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_SLICE: AtomicUsize = AtomicUsize::new(0);
+
+/// Re-arm the timer of this hart and return whether its time slice is over.
+///
+/// A new time slice starts one quantum after the end of the last one, so that the
+/// lateness of handling an interrupt does not add up over the ticks. A hart which has
+/// fallen behind by more than a time slice, like after a long stretch in the kernel,
+/// starts over from the current time.
+pub fn set_next_trigger() -> bool {
+    let now = get_time();
+    let slice_end = &SLICE_END[hart_id()];
+    let mut end = slice_end.load(atomic::Ordering::Relaxed);
+    let expired = end <= now;
+    if expired {
+        let quantum = us_to_cycles(get_quantum_us());
+        end += quantum;
+        if end <= now {
+            end = now + quantum;
+        }
+        slice_end.store(end, atomic::Ordering::Relaxed);
+    }
+    arm_timer();
+    expired
+}
+
+/// Program the timer interrupt of this hart at the end of its time slice or at the
+/// earliest deadline in the timer queue, whichever comes first, so that a short sleep
+/// does not wait for the next tick
+fn arm_timer() {
+    let slice_end = SLICE_END[hart_id()].load(atomic::Ordering::Relaxed);
+    let next = TIMERS
+        .exclusive_access()
+        .peek()
+        .map_or(slice_end, |timer| timer.deadline.min(slice_end));
+    set_timer(next);
+}
+
+/// What happens to a task when its deadline in the timer queue passes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WakeReason {
+    /// The task has blocked itself until the deadline, like in sleep, and is made ready
+    Sleep,
+    /// SIGALRM is raised for the task, set by alarm
+    Alarm,
+}
+
+/// A task waiting for its deadline in the timer queue
+struct TimerEntry {
+    /// `mtime` at which the entry expires
+    deadline: usize,
+    /// The task is gone if the upgrade fails, which cancels the entry
+    task: Weak<TaskControlBlock>,
+    reason: WakeReason,
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+impl Eq for TimerEntry {}
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, so the earliest deadline must compare greatest
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+lazy_static! {
+    /// The timer queue of all harts ordered by deadline
+    static ref TIMERS: UPSafeCell<BinaryHeap<TimerEntry>> =
+        unsafe { UPSafeCell::new(BinaryHeap::<TimerEntry>::new()) };
+}
+
+/// Act on `task` for `reason` once the time reaches `deadline` in `mtime`
+pub fn add_timer(deadline: usize, task: &Arc<TaskControlBlock>, reason: WakeReason) {
+    TIMERS.exclusive_access().push(TimerEntry {
+        deadline,
+        task: Arc::downgrade(task),
+        reason,
+    });
+    arm_timer();
+}
+
+/// Drop the entries of `task` for `reason` from the timer queue before their deadline,
+/// along with the entries of the tasks which are gone
+pub fn remove_timer(task: &Arc<TaskControlBlock>, reason: WakeReason) {
+    let mut timers = TIMERS.exclusive_access();
+    let remaining: BinaryHeap<TimerEntry> = timers
+        .drain()
+        .filter(|timer| {
+            timer.task.strong_count() > 0
+                && !(timer.task.as_ptr() == Arc::as_ptr(task) && timer.reason == reason)
+        })
+        .collect();
+    *timers = remaining;
 }
 
-/// set the next timer interrupt
-pub fn set_next_trigger() {
-    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+/// Pop the entries whose deadline has passed and act on their tasks
+pub fn check_timer() {
+    let now = get_time();
+    loop {
+        let timer = {
+            let mut timers = TIMERS.exclusive_access();
+            match timers.peek() {
+                Some(timer) if timer.deadline <= now => timers.pop().unwrap(),
+                _ => break,
+            }
+        };
+        let task = match timer.task.upgrade() {
+            Some(task) => task,
+            None => continue,
+        };
+        match timer.reason {
+            WakeReason::Sleep => {
+                // the task may have been woken up already by a kill
+                let mut task_inner = task.inner_exclusive_access();
+                if task_inner.task_status == TaskStatus::Blocked {
+                    task_inner.task_status = TaskStatus::Ready;
+                    drop(task_inner);
+                    add_task(task);
+                }
+            }
+            WakeReason::Alarm => raise_alarm(task),
+        }
+    }
 }
@@ -14,20 +14,54 @@
 
 mod context;
 
-use crate::config::{TRAMPOLINE, TRAP_CONTEXT};
+use crate::config::{MAX_HARTS, TRAMPOLINE, TRAP_CONTEXT};
+use crate::drivers::handle_external_interrupt;
+use crate::mm::copy_from_user;
+use crate::sync::{big_kernel_lock, big_kernel_unlock};
 use crate::syscall::syscall;
 use crate::task::{
-    current_trap_cx, current_user_token, exit_current_and_run_next, suspend_current_and_run_next,
+    account_current_time, current_killed_by, current_task, current_trap_cx, current_user_token,
+    exit_current_and_run_next, handle_current_signals, handle_page_fault, hart_id,
+    is_current_stack_overflow, preempt_current_and_run_next, take_need_resched,
+    tick_current_and_run_next,
 };
-use crate::timer::set_next_trigger;
+use crate::timer::{check_timer, set_next_trigger};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use log::Level;
 use riscv::register::{
     mtvec::TrapMode,
     scause::{self, Exception, Interrupt, Trap},
-    sie, stval, stvec,
+    sie,
+    sstatus::{self, SPP},
+    stval, stvec,
 };
 
 core::arch::global_asm!(include_str!("trap.S"));
 
+/// `scause` of the trap each hart is handling, or `NO_TRAP`
+static HANDLING_TRAP: [AtomicUsize; MAX_HARTS] = [NO_TRAP_ATOMIC; MAX_HARTS];
+/// Not a valid `scause`, the exception codes are much smaller
+const NO_TRAP: usize = usize::MAX;
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_TRAP_ATOMIC: AtomicUsize = AtomicUsize::new(NO_TRAP);
+
+/// `scause` of the trap this hart is handling, if any
+pub fn handling_trap() -> Option<usize> {
+    match HANDLING_TRAP[hart_id()].load(Ordering::Relaxed) {
+        NO_TRAP => None,
+        scause => Some(scause),
+    }
+}
+
+/// Set the trap this hart is handling and return the previous one. A task switched in
+/// the middle of a trap takes it along, see [`crate::task::schedule`]
+pub fn set_handling_trap(scause: Option<usize>) -> Option<usize> {
+    match HANDLING_TRAP[hart_id()].swap(scause.unwrap_or(NO_TRAP), Ordering::Relaxed) {
+        NO_TRAP => None,
+        scause => Some(scause),
+    }
+}
+
 pub fn init() {
     set_kernel_trap_entry();
 }
@@ -52,57 +86,211 @@ pub fn enable_timer_interrupt() {
 
 #[no_mangle]
 pub fn trap_handler() -> ! {
+    big_kernel_lock();
     set_kernel_trap_entry();
+    account_current_time(true);
     let scause = scause::read();
     let stval = stval::read();
+    set_handling_trap(Some(scause.bits()));
     match scause.cause() {
         Trap::Exception(Exception::UserEnvCall) => {
             // jump to next instruction anyway
             let mut cx = current_trap_cx();
             cx.sepc += 4;
             // get system call return value
-            let result = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]);
+            let result = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12], cx.x[13]]);
             // cx is changed during sys_exec, so we have to call it again
             cx = current_trap_cx();
             cx.x[10] = result as usize;
         }
+        Trap::Exception(Exception::StorePageFault)
+        | Trap::Exception(Exception::LoadPageFault)
+        | Trap::Exception(Exception::InstructionPageFault)
+            if handle_page_fault(
+                stval,
+                scause.cause() == Trap::Exception(Exception::StorePageFault),
+            ) => {}
+        Trap::Exception(Exception::StorePageFault) | Trap::Exception(Exception::LoadPageFault)
+            if is_current_stack_overflow(stval) =>
+        {
+            kprintln!(
+                "[kernel] user stack overflow in {}, bad addr = {:#x}, core dumped.",
+                current_task().unwrap().label(),
+                stval,
+            );
+            // stack overflow exit code, as SIGSEGV
+            exit_current_and_run_next(-11);
+        }
         Trap::Exception(Exception::StoreFault)
         | Trap::Exception(Exception::StorePageFault)
         | Trap::Exception(Exception::InstructionFault)
         | Trap::Exception(Exception::InstructionPageFault)
         | Trap::Exception(Exception::LoadFault)
         | Trap::Exception(Exception::LoadPageFault) => {
-            println!(
-                "[kernel] {:?} in application, bad addr = {:#x}, bad instruction = {:#x}, core dumped.",
+            kprintln!(
+                "[kernel] {:?} in application ({}), bad addr = {:#x}, bad instruction = {:#x}, core dumped.",
                 scause.cause(),
+                current_task().unwrap().label(),
                 stval,
                 current_trap_cx().sepc,
             );
+            dump_user_fault(scause.cause(), stval);
             // page fault exit code
             exit_current_and_run_next(-2);
         }
         Trap::Exception(Exception::IllegalInstruction) => {
-            println!("[kernel] IllegalInstruction in application, core dumped.");
+            let sepc = current_trap_cx().sepc;
+            let token = current_user_token();
+            let label = current_task().unwrap().label();
+            if let Some(inst) = user_instruction(token, sepc) {
+                kprintln!(
+                    "[kernel] IllegalInstruction in application ({}), instruction {:#x} at {:#x}, core dumped.",
+                    label, inst, sepc,
+                );
+            } else {
+                kprintln!(
+                    "[kernel] IllegalInstruction in application ({}), unreadable instruction at {:#x}, core dumped.",
+                    label, sepc,
+                );
+            }
+            dump_user_fault(scause.cause(), stval);
             // illegal instruction exit code
             exit_current_and_run_next(-3);
         }
+        Trap::Interrupt(Interrupt::SupervisorExternal) => {
+            handle_external_interrupt();
+        }
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
-            set_next_trigger();
-            suspend_current_and_run_next();
+            check_timer();
+            // the interrupt may only be for a deadline in the timer queue
+            if set_next_trigger() {
+                tick_current_and_run_next();
+            }
         }
         _ => {
-            panic!(
-                "Unsupported trap {:?}, stval = {:#x}!",
+            if sstatus::read().spp() == SPP::Supervisor {
+                panic!(
+                    "Unsupported trap {:?} while running {}, stval = {:#x}!",
+                    scause.cause(),
+                    current_task().unwrap().label(),
+                    stval
+                );
+            }
+            kprintln!(
+                "[kernel] Unsupported trap {:?} in application ({}), stval = {:#x}, sepc = {:#x}, core dumped.",
                 scause.cause(),
-                stval
+                current_task().unwrap().label(),
+                stval,
+                current_trap_cx().sepc,
             );
+            dump_user_fault(scause.cause(), stval);
+            exit_current_and_run_next(-1);
         }
     }
+    // a task that should run first has become ready, give way at the trap boundary
+    if take_need_resched() {
+        preempt_current_and_run_next();
+    }
     trap_return();
 }
 
+/// What went wrong in a trap killing a task, in words
+fn describe_trap(cause: Trap) -> &'static str {
+    match cause {
+        Trap::Exception(Exception::InstructionMisaligned) => "misaligned instruction fetch",
+        Trap::Exception(Exception::InstructionFault) => "instruction access fault",
+        Trap::Exception(Exception::IllegalInstruction) => "illegal instruction",
+        Trap::Exception(Exception::Breakpoint) => "breakpoint",
+        Trap::Exception(Exception::LoadFault) => "load access fault",
+        Trap::Exception(Exception::StoreMisaligned) => "misaligned store",
+        Trap::Exception(Exception::StoreFault) => "store access fault",
+        Trap::Exception(Exception::InstructionPageFault) => {
+            "instruction fetch from an unmapped page"
+        }
+        Trap::Exception(Exception::LoadPageFault) => "load from an unmapped page",
+        Trap::Exception(Exception::StorePageFault) => "store to an unmapped or read-only page",
+        _ => "unexpected trap",
+    }
+}
+
+/// Log the registers of the current task and the user areas nearest to `stval`
+/// at the debug level, before the task is killed for `cause`
+fn dump_user_fault(cause: Trap, stval: usize) {
+    if Level::Debug > log::max_level() {
+        return;
+    }
+    let task = current_task().unwrap();
+    let cx = *current_trap_cx();
+    debug!(
+        "[kernel] {} killed by {} ({:?}), stval = {:#x}",
+        task.label(),
+        describe_trap(cause),
+        cause,
+        stval
+    );
+    debug!(
+        "[kernel]   sepc = {:#x}, ra = {:#x}, sp = {:#x}",
+        cx.sepc, cx.x[1], cx.x[2]
+    );
+    debug!(
+        "[kernel]   a0 = {:#x}, a1 = {:#x}, a2 = {:#x}, a3 = {:#x}",
+        cx.x[10], cx.x[11], cx.x[12], cx.x[13]
+    );
+    debug!(
+        "[kernel]   a4 = {:#x}, a5 = {:#x}, a6 = {:#x}, a7 = {:#x}",
+        cx.x[14], cx.x[15], cx.x[16], cx.x[17]
+    );
+    let areas = task
+        .inner_exclusive_access()
+        .memory_set
+        .nearest_areas(stval.into(), 3);
+    for (start, end, perm) in areas {
+        let (start, end): (usize, usize) = (start.into(), end.into());
+        if stval < start {
+            debug!(
+                "[kernel]   area [{:#x}, {:#x}) {:?}, starting {} bytes above stval",
+                start,
+                end,
+                perm,
+                start - stval
+            );
+        } else if stval >= end {
+            debug!(
+                "[kernel]   area [{:#x}, {:#x}) {:?}, stval {} bytes past its end",
+                start,
+                end,
+                perm,
+                stval - end
+            );
+        } else {
+            debug!(
+                "[kernel]   area [{:#x}, {:#x}) {:?}, holding stval",
+                start, end, perm
+            );
+        }
+    }
+}
+
+/// Fetch the instruction at `sepc` through the user page table, either 16 or 32 bits long
+fn user_instruction(token: usize, sepc: usize) -> Option<u32> {
+    let low = copy_from_user(token, sepc as *const u16).ok()? as u32;
+    // compressed instructions do not end with 0b11
+    if low & 0b11 != 0b11 {
+        return Some(low);
+    }
+    let high = copy_from_user(token, (sepc + 2) as *const u16).ok()? as u32;
+    Some(high << 16 | low)
+}
+
 #[no_mangle]
 pub fn trap_return() -> ! {
+    handle_current_signals();
+    // a killed task never goes back to user space
+    if let Some(signum) = current_killed_by() {
+        exit_current_and_run_next(-(signum as i32));
+    }
+    account_current_time(false);
+    set_handling_trap(None);
     set_user_trap_entry();
     let trap_cx_ptr = TRAP_CONTEXT;
     let user_satp = current_user_token();
@@ -111,6 +299,7 @@ pub fn trap_return() -> ! {
         fn __restore();
     }
     let restore_va = __restore as usize - __alltraps as usize + TRAMPOLINE;
+    big_kernel_unlock();
     unsafe {
         core::arch::asm!(
             "fence.i",
@@ -125,6 +314,7 @@ pub fn trap_return() -> ! {
 
 #[no_mangle]
 pub fn trap_from_kernel() -> ! {
+    set_handling_trap(Some(scause::read().bits()));
     panic!("a trap {:?} from kernel!", scause::read().cause());
 }
 
@@ -0,0 +1,53 @@
+# Write corrupted copies of an app image for ch5_bad_elf, which the loader must reject
+import struct
+import sys
+
+src, out_dir = sys.argv[1], sys.argv[2]
+with open(src, "rb") as f:
+    elf = f.read()
+
+
+def patch(data, offset, value):
+    return data[:offset] + value + data[offset + len(value):]
+
+
+# offsets of the program headers of type PT_LOAD
+(ph_offset,) = struct.unpack_from("<Q", elf, 32)
+(ph_entry_size, ph_count) = struct.unpack_from("<HH", elf, 54)
+loads = [
+    ph_offset + i * ph_entry_size
+    for i in range(ph_count)
+    if struct.unpack_from("<I", elf, ph_offset + i * ph_entry_size)[0] == 1
+]
+load = loads[0]
+(vaddr,) = struct.unpack_from("<Q", elf, load + 16)
+# a segment with some data in the file, which is not executable
+data_load = next(
+    ph for ph in loads
+    if struct.unpack_from("<I", elf, ph + 4)[0] & 1 == 0
+    and struct.unpack_from("<Q", elf, ph + 32)[0] > 0
+)
+(data_vaddr, _, file_size) = struct.unpack_from("<QQQ", elf, data_load + 16)
+
+images = {
+    "corrupt_magic": patch(elf, 0, b"\x7fBAD"),
+    # the program headers are cut off
+    "corrupt_truncated": elf[: ph_offset + 8],
+    "corrupt_class": patch(elf, 4, b"\x01"),
+    # EM_X86_64
+    "corrupt_machine": patch(elf, 18, struct.pack("<H", 62)),
+    # p_vaddr in the mmap window
+    "corrupt_segment": patch(elf, load + 16, struct.pack("<Q", 0x10_0000_0000)),
+    # p_flags asking for a segment both writable and executable
+    "corrupt_wx": patch(elf, load + 4, struct.pack("<I", 7)),
+    # the second segment moved onto the first one
+    "corrupt_overlap": patch(elf, loads[1] + 16, struct.pack("<Q", vaddr)),
+    # e_entry in a segment which is not executable
+    "corrupt_entry": patch(elf, 24, struct.pack("<Q", data_vaddr)),
+    # p_memsz smaller than p_filesz
+    "corrupt_memsz": patch(elf, data_load + 40, struct.pack("<Q", file_size - 1)),
+}
+for name, data in images.items():
+    with open("%s/%s.elf" % (out_dir, name), "wb") as f:
+        f.write(data)
+    print("[corrupt_elf.py] %s written from %s" % (name, src))
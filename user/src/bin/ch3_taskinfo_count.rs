@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    get_time, task_info, yield_, TaskInfo, SYSCALL_GETTIMEOFDAY, SYSCALL_TASK_INFO, SYSCALL_WRITE,
+    SYSCALL_YIELD,
+};
+
+/*
+程序行为：恰好调用 7 次 yield 和 3 次 get_time，再用 task_info 查询，次数应与调用次数完全一致。
+理想结果：输出 Test task_info count OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..7 {
+        yield_();
+    }
+    for _ in 0..3 {
+        get_time();
+    }
+    let info = TaskInfo::new();
+    assert_eq!(0, task_info(&info));
+    assert_eq!(7, info.syscall_times[SYSCALL_YIELD]);
+    assert_eq!(3, info.syscall_times[SYSCALL_GETTIMEOFDAY]);
+    assert_eq!(1, info.syscall_times[SYSCALL_TASK_INFO]);
+    assert_eq!(0, info.syscall_times[SYSCALL_WRITE]);
+    println!("Test task_info count OK!");
+    0
+}
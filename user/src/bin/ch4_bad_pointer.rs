@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, spawn_with_args, sys_get_time, write, TimeVal, STDOUT};
+
+/*
+程序行为：向 write、get_time、exec、spawn 传入未映射的低地址和内核地址作为用户指针，
+系统调用都应返回 -1，内核保持正常运行。
+理想结果：输出 Test bad pointer OK!
+*/
+
+const BAD_ADDRS: [usize; 2] = [0x1000, 0x80200000];
+
+#[no_mangle]
+fn main() -> i32 {
+    for addr in BAD_ADDRS {
+        let buf = unsafe { core::slice::from_raw_parts(addr as *const u8, 16) };
+        assert_eq!(write(STDOUT, buf), -1);
+        assert_eq!(sys_get_time(unsafe { &*(addr as *const TimeVal) }, 0), -1);
+        let path = unsafe { core::str::from_utf8_unchecked(buf) };
+        assert_eq!(exec(path, &[core::ptr::null::<u8>()]), -1);
+        assert_eq!(spawn_with_args(path, &[core::ptr::null::<u8>()]), -1);
+        // 参数指针本身有效，但指向的字符串无效
+        assert_eq!(
+            spawn_with_args("ch5_argv\0", &[addr as *const u8, core::ptr::null::<u8>()]),
+            -1
+        );
+    }
+    println!("Test bad pointer OK!");
+    0
+}
@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    clock_gettime, settimeofday, sys_clock_gettime, yield_, TimeSpec, TimeVal, CLOCK_MONOTONIC,
+    CLOCK_REALTIME,
+};
+
+/*
+程序行为：连续读取 CLOCK_MONOTONIC，即使中间 yield 也不会倒退；
+未知的时钟和非法指针返回 -1，普通进程不能设置时间。
+理想结果：输出 Test clock_gettime OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut last = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut last), 0);
+    for i in 0..1000 {
+        if i % 10 == 0 {
+            yield_();
+        }
+        let mut now = TimeSpec::default();
+        assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut now), 0);
+        assert!(now.nsec < 1_000_000_000);
+        assert!(now >= last, "monotonic clock goes backwards");
+        last = now;
+    }
+    let mut real = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_REALTIME, &mut real), 0);
+    assert!(real >= last);
+    assert_eq!(clock_gettime(2, &mut real), -1);
+    let bad = unsafe { &mut *(0x80200000 as *mut TimeSpec) };
+    assert_eq!(sys_clock_gettime(CLOCK_MONOTONIC, bad), -1);
+    let tv = TimeVal {
+        sec: 1_700_000_000,
+        usec: 0,
+    };
+    assert_eq!(settimeofday(&tv), -1);
+    println!("Test clock_gettime OK!");
+    0
+}
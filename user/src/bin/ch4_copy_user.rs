@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, mmap, sys_get_time, sys_waitpid, task_info, waitpid, TaskInfo, TimeVal,
+};
+
+/*
+程序行为：向 get_time、task_info、waitpid 传入未映射或只读的用户指针，系统调用应返回 -1 而不是让内核崩溃；
+跨越页边界的 TimeVal 也能被正确写入。
+理想结果：输出 Test copy user OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let page: usize = 4096;
+    // 未映射
+    assert_eq!(sys_get_time(unsafe { &*(start as *const TimeVal) }, 0), -1);
+    assert_eq!(task_info(unsafe { &*(start as *const TaskInfo) }), -1);
+    // 只读
+    assert_eq!(start as isize, mmap(start, page, 1));
+    assert_eq!(sys_get_time(unsafe { &*(start as *const TimeVal) }, 0), -1);
+    // 跨越页边界
+    let straddle = start + 3 * page - 8;
+    assert_eq!((start + page) as isize, mmap(start + page, 2 * page, 3));
+    unsafe {
+        (straddle as *mut u8).write_volatile(0);
+    }
+    let tv = unsafe { &*(straddle as *const TimeVal) };
+    assert_eq!(sys_get_time(tv, 0), 0);
+    assert!(tv.sec > 0 || tv.usec > 0);
+    assert!(tv.usec < 1_000_000);
+    // 子进程的退出码无处可存时它仍是僵尸进程，可以再次等待
+    let pid = fork();
+    if pid == 0 {
+        exit(3);
+    }
+    assert_eq!(sys_waitpid(pid, start as *mut i32), -1);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 3);
+    println!("Test copy user OK!");
+    0
+}
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, sys_get_time, TimeVal};
+
+/*
+程序行为：在两个相邻页的交界处（第一页末尾 8 字节处）放置 TimeVal，调用 get_time 后检查两个字段都被正确写入，
+且交界两侧的其他内存没有被改写。
+理想结果：输出 Test get_time across pages OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 8192;
+    assert_eq!(start as isize, mmap(start, len, 3));
+    for i in start..(start + len) {
+        unsafe {
+            *(i as *mut u8) = 0xaa;
+        }
+    }
+    let addr = start + 4096 - 8;
+    let tv = unsafe { &mut *(addr as *mut TimeVal) };
+    assert_eq!(0, sys_get_time(tv, 0));
+    let (sec, usec) = (tv.sec, tv.usec);
+    assert_ne!(sec, 0xaaaa_aaaa_aaaa_aaaa);
+    assert!(usec < 1_000_000);
+    let now = TimeVal::new();
+    assert_eq!(0, sys_get_time(&now, 0));
+    assert!(now.sec * 1_000_000 + now.usec >= sec * 1_000_000 + usec);
+    for i in (start..addr).chain((addr + 16)..(start + len)) {
+        unsafe {
+            assert_eq!(*(i as *const u8), 0xaa);
+        }
+    }
+    assert_eq!(0, munmap(start, len));
+    println!("Test get_time across pages OK!");
+    0
+}
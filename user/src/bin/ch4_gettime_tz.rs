@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{syscall, TimeVal, TimeZone, SYSCALL_GETTIMEOFDAY};
+
+/*
+程序行为：get_time 的 ts 和 tz 分别取空指针和非空指针的四种组合，
+空指针对应的结构不被写入并返回 0，非空的 tz 被写为全 0；非法的 tz 指针返回 -1。
+理想结果：输出 Test get_time tz OK!
+*/
+
+fn get_time(ts: *mut TimeVal, tz: *mut TimeZone) -> isize {
+    syscall(SYSCALL_GETTIMEOFDAY, [ts as usize, tz as usize, 0])
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    let null_ts = core::ptr::null_mut::<TimeVal>();
+    let null_tz = core::ptr::null_mut::<TimeZone>();
+    assert_eq!(get_time(null_ts, null_tz), 0);
+
+    let mut tv = TimeVal {
+        sec: usize::MAX,
+        usec: usize::MAX,
+    };
+    assert_eq!(get_time(&mut tv, null_tz), 0);
+    assert!(tv.sec != usize::MAX && tv.usec < 1_000_000);
+
+    let mut tz = TimeZone {
+        minuteswest: -1,
+        dsttime: -1,
+    };
+    assert_eq!(get_time(null_ts, &mut tz), 0);
+    assert_eq!(tz, TimeZone::default());
+
+    let mut tv = TimeVal {
+        sec: usize::MAX,
+        usec: usize::MAX,
+    };
+    let mut tz = TimeZone {
+        minuteswest: -1,
+        dsttime: -1,
+    };
+    assert_eq!(get_time(&mut tv, &mut tz), 0);
+    assert!(tv.sec != usize::MAX && tv.usec < 1_000_000);
+    assert_eq!(tz, TimeZone::default());
+
+    assert_eq!(get_time(&mut tv, 0x80200000 as *mut TimeZone), -1);
+    println!("Test get_time tz OK!");
+    0
+}
@@ -7,6 +7,8 @@ extern crate user_lib;
 use user_lib::mmap;
 
 /*
+程序行为：只写不可读的映射在 RISC-V 中是保留的，mmap 返回 -1 且不建立映射，
+之后访问该地址会触发访存异常。
 理想结果：程序触发访存异常，被杀死。不输出 error 就算过。
 */
 
@@ -15,10 +17,10 @@ fn main() -> i32 {
     let start: usize = 0x10000000;
     let len: usize = 4096;
     let prot: usize = 2;
-    assert_eq!(0, mmap(start, len, prot));
+    assert_eq!(-1, mmap(start, len, prot));
     let addr: *mut u8 = start as *mut u8;
     unsafe {
-        // *addr = start as u8; // can't write, R == 0 && W == 1 is illegal in riscv
+        // R == 0 && W == 1 is illegal in riscv, so nothing is mapped here
         assert!(*addr != 0);
     }
     println!("Should cause error, Test 04_2 fail!");
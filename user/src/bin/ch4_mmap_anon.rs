@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap};
+
+/*
+程序行为：以 start = 0 连续调用两次 mmap，由内核选择地址，
+返回的地址应按页对齐、互不重叠且可以立即读写；释放后再次映射应能复用。
+理想结果：输出 Test mmap anon OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let page: usize = 4096;
+    let a = mmap(0, 3 * page, 3);
+    let b = mmap(0, page + 1, 3);
+    assert!(a > 0 && b > 0);
+    let (a, b) = (a as usize, b as usize);
+    assert_eq!(a % page, 0);
+    assert_eq!(b % page, 0);
+    assert!(a + 3 * page <= b || b + 2 * page <= a);
+    for addr in [a, a + 2 * page, b, b + page] {
+        unsafe {
+            (addr as *mut usize).write_volatile(addr);
+            assert_eq!((addr as *const usize).read_volatile(), addr);
+        }
+    }
+    assert_eq!(0, munmap(a, 3 * page));
+    let c = mmap(0, page, 3);
+    assert!(c > 0);
+    assert!(c as usize + page <= b || b + 2 * page <= c as usize);
+    println!("Test mmap anon OK!");
+    0
+}
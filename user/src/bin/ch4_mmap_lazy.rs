@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, prlimit, RLimit, RLIMIT_AS, RLIM_INFINITY};
+
+/*
+程序行为：映射总计 256 MiB（超过物理内存）的区域，只访问每段的首尾两页，
+物理页帧在第一次访问时才分配，因此不会耗尽内存。
+理想结果：输出 Test mmap lazy OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    // 默认的 RLIMIT_AS 只有 1024 页
+    let unlimited = RLimit {
+        rlim_cur: RLIM_INFINITY,
+        rlim_max: RLIM_INFINITY,
+    };
+    assert_eq!(0, prlimit(0, RLIMIT_AS, Some(&unlimited), None));
+    let len: usize = 64 << 20;
+    let page: usize = 4096;
+    for i in 0..4 {
+        let start: usize = 0x20000000 + i * len;
+        assert_eq!(start as isize, mmap(start, len, 3));
+        let first = start as *mut usize;
+        let last = (start + len - page) as *mut usize;
+        unsafe {
+            // 未写过的页读出来是 0
+            assert_eq!(first.read_volatile(), 0);
+            first.write_volatile(start);
+            last.write_volatile(start + len);
+            assert_eq!(first.read_volatile(), start);
+            assert_eq!(last.read_volatile(), start + len);
+        }
+        // 映射区域内的页都视为已映射
+        assert_eq!(mmap(start + page, page, 3), -1);
+        assert_eq!(0, munmap(start, len));
+    }
+    println!("Test mmap lazy OK!");
+    0
+}
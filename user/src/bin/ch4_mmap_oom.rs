@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, prlimit, RLimit, RLIMIT_AS, RLIM_INFINITY};
+
+/*
+程序行为：把 RLIMIT_AS 设为 32 MiB，每次映射 1 MiB 并写满，直到 mmap 返回 -1，
+之后检查写入的数据、释放全部映射，再映射一次，确认进程仍能正常运行并退出。
+mmap 不预留物理页帧，上限由 RLIMIT_AS 给出，32 MiB 在物理内存之内。
+会用掉大量物理内存，需单独运行。
+理想结果：输出 Test mmap oom OK!
+*/
+
+const MIB: usize = 1 << 20;
+const LIMIT_MIB: usize = 32;
+
+#[no_mangle]
+fn main() -> i32 {
+    let limit = RLimit {
+        rlim_cur: LIMIT_MIB * MIB,
+        rlim_max: RLIM_INFINITY,
+    };
+    assert_eq!(0, prlimit(0, RLIMIT_AS, Some(&limit), None));
+    let start: usize = 0x10000000;
+    let mut count = 0;
+    loop {
+        let addr = start + count * MIB;
+        let ret = mmap(addr, MIB, 3);
+        if ret < 0 {
+            assert_eq!(ret, -1);
+            break;
+        }
+        assert_eq!(ret, addr as isize);
+        let buf = unsafe { core::slice::from_raw_parts_mut(addr as *mut usize, MIB / 8) };
+        buf.fill(count);
+        count += 1;
+    }
+    println!("mapped {} MiB before mmap failed", count);
+    assert_eq!(count, LIMIT_MIB);
+    for i in 0..count {
+        let buf =
+            unsafe { core::slice::from_raw_parts((start + i * MIB) as *const usize, MIB / 8) };
+        assert!(buf.iter().all(|&x| x == i));
+        assert_eq!(0, munmap(start + i * MIB, MIB));
+    }
+    assert_eq!(start as isize, mmap(start, MIB, 3));
+    let buf = unsafe { core::slice::from_raw_parts_mut(start as *mut usize, MIB / 8) };
+    buf.fill(1);
+    println!("Test mmap oom OK!");
+    0
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, mprotect, munmap};
+
+/*
+程序行为：以会导致 start + len 回绕或超出用户地址空间的参数调用 mmap/munmap/mprotect，
+都应返回 -1 且不改变地址空间；之后正常的映射仍然可用。
+理想结果：输出 Test mmap overflow OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let page: usize = 4096;
+    let top: usize = 1 << 38;
+    let wrap = usize::MAX - page + 1;
+    assert_eq!(mmap(wrap, 2 * page, 3), -1);
+    assert_eq!(munmap(wrap, 2 * page), -1);
+    assert_eq!(mprotect(wrap, 2 * page, 1), -1);
+    assert_eq!(mmap(page, usize::MAX - page, 3), -1);
+    assert_eq!(mmap(0, usize::MAX - page + 1, 3), -1);
+    assert_eq!(mmap(usize::MAX, 0, 3), -1);
+    assert_eq!(munmap(usize::MAX, 0), -1);
+    assert_eq!(mmap(top - page, 2 * page, 3), -1);
+    assert_eq!(mmap(top, page, 3), -1);
+    // 以上调用都没有建立映射
+    assert_eq!(munmap(top - page, page), -1);
+    assert_eq!((top - page) as isize, mmap(top - page, page, 3));
+    unsafe {
+        ((top - page) as *mut usize).write_volatile(1);
+    }
+    assert_eq!(0, munmap(top - page, page));
+    println!("Test mmap overflow OK!");
+    0
+}
@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, waitpid};
+
+/*
+程序行为：子进程映射 2 页后写到映射末尾之后 16 字节处，应被内核以 -2 杀死。
+以 LOG=DEBUG 构建内核时，内核还会输出子进程的寄存器和该地址附近的内存区域，
+其中应有这次映射的区域，并注明地址越过其末尾 16 字节。
+理想结果：输出 Test mmap overrun OK!
+*/
+
+const PAGE_SIZE: usize = 4096;
+const LEN: usize = 2 * PAGE_SIZE;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x30000000;
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(mmap(start, LEN, 3), start as isize);
+        unsafe {
+            ((start + LEN - 8) as *mut u64).write_volatile(1);
+            ((start + LEN + 16) as *mut u64).write_volatile(1);
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+    println!("Test mmap overrun OK!");
+    0
+}
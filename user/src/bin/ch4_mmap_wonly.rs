@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, mprotect, munmap};
+
+/*
+程序行为：port 为 2 或 6（可写但不可读）的 mmap 与 mprotect 都返回 -1，
+且不改变地址空间；port 为 3 的映射照常可以读写。
+理想结果：输出 Test mmap write-only OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let page: usize = 4096;
+    assert_eq!(-1, mmap(start, page, 2));
+    assert_eq!(-1, mmap(start, page, 6));
+    assert_eq!(-1, mmap(0, page, 2));
+    // 被拒绝的映射没有占用这段地址
+    assert_eq!(start as isize, mmap(start, page, 3));
+    assert_eq!((start + page) as isize, mmap(start + page, page, 3));
+    for addr in [start, start + page] {
+        unsafe {
+            (addr as *mut usize).write_volatile(addr);
+            assert_eq!((addr as *const usize).read_volatile(), addr);
+        }
+    }
+    assert_eq!(-1, mprotect(start, page, 2));
+    assert_eq!(-1, mprotect(start + page, page, 6));
+    // 失败的 mprotect 不改变原有权限
+    unsafe {
+        (start as *mut usize).write_volatile(1);
+        ((start + page) as *mut usize).write_volatile(2);
+        assert_eq!((start as *const usize).read_volatile(), 1);
+        assert_eq!(((start + page) as *const usize).read_volatile(), 2);
+    }
+    assert_eq!(0, munmap(start, 2 * page));
+    println!("Test mmap write-only OK!");
+    0
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, mprotect, munmap};
+
+/*
+程序行为：内核不允许同时可写又可执行的页（W^X），port 为 7 的 mmap 返回 -1 且不占用地址，
+mprotect 也不能把可写的页改为可执行或反过来；可执行但不可写（port 5）的页则没有问题，
+可在 RW 与 RX 之间来回切换。各测例程序本身也都没有可写又可执行的段，内核编译时打开
+allow_wx 特性后本测例不适用。
+理想结果：输出 Test mmap W^X OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let page: usize = 4096;
+    assert_eq!(-1, mmap(start, page, 7));
+    assert_eq!(-1, mmap(0, page, 7));
+    // 被拒绝的映射没有占用这段地址
+    assert_eq!(start as isize, mmap(start, page, 3));
+    unsafe {
+        (start as *mut usize).write_volatile(start);
+    }
+    assert_eq!(-1, mprotect(start, page, 7));
+    // 失败的 mprotect 不改变原有权限
+    unsafe {
+        (start as *mut usize).write_volatile(1);
+    }
+    assert_eq!(0, mprotect(start, page, 5));
+    assert_eq!(unsafe { (start as *const usize).read_volatile() }, 1);
+    assert_eq!(-1, mprotect(start, page, 7));
+    assert_eq!(0, mprotect(start, page, 3));
+    unsafe {
+        (start as *mut usize).write_volatile(2);
+    }
+    assert_eq!((start + page) as isize, mmap(start + page, page, 5));
+    assert_eq!(0, munmap(start, 2 * page));
+    println!("Test mmap W^X OK!");
+    0
+}
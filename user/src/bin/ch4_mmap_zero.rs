@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    mem_stats, mmap, munmap, prlimit, sysinfo, MemStats, RLimit, SysInfo, RLIMIT_AS, RLIM_INFINITY,
+};
+
+/*
+程序行为：mmap 1024 页并全部读一遍，读到的都是 0，这些页都映射到同一个零页，
+只多用了几个页表页；随后写其中 3 页，正好多分配 3 个物理页帧，其余页仍然是 0。
+会统计全局的物理页帧，需单独运行。
+理想结果：输出 Test mmap zero OK!
+*/
+
+const PAGES: usize = 1024;
+const PAGE_SIZE: usize = 4096;
+/// 写入的页
+const WRITTEN: [usize; 3] = [0, PAGES / 2, PAGES - 1];
+
+fn allocated() -> usize {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    info.total_frames - info.free_frames
+}
+
+fn resident() -> usize {
+    let mut stats = MemStats::default();
+    assert_eq!(mem_stats(&mut stats), 0);
+    stats.resident_pages
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    // 默认的 RLIMIT_AS 只有 1024 页
+    let unlimited = RLimit {
+        rlim_cur: RLIM_INFINITY,
+        rlim_max: RLIM_INFINITY,
+    };
+    assert_eq!(0, prlimit(0, RLIMIT_AS, Some(&unlimited), None));
+    let start: usize = 0x20000000;
+    let len = PAGES * PAGE_SIZE;
+    let frames = allocated();
+    let pages = resident();
+    assert_eq!(start as isize, mmap(start, len, 3));
+    for i in 0..PAGES {
+        let p = (start + i * PAGE_SIZE + i % PAGE_SIZE) as *const u8;
+        assert_eq!(unsafe { p.read_volatile() }, 0);
+    }
+    // 只有页表页
+    let read = allocated();
+    assert!(read < frames + 4);
+    assert_eq!(resident(), pages);
+    for i in WRITTEN {
+        unsafe {
+            ((start + i * PAGE_SIZE) as *mut usize).write_volatile(i + 1);
+        }
+    }
+    assert_eq!(allocated(), read + 3);
+    assert_eq!(resident(), pages + 3);
+    for i in 0..PAGES {
+        let expected = if WRITTEN.contains(&i) { i + 1 } else { 0 };
+        let p = (start + i * PAGE_SIZE) as *const usize;
+        assert_eq!(unsafe { p.read_volatile() }, expected);
+    }
+    assert_eq!(0, munmap(start, len));
+    assert!(allocated() <= read);
+    println!("Test mmap zero OK!");
+    0
+}
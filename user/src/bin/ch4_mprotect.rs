@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, mmap, mprotect, munmap, waitpid};
+
+/*
+程序行为：mmap 三页后把中间一页改为只读，读取仍然正常，非法参数与未映射区域返回 -1，
+随后整段 munmap 成功；子进程写只读页会被内核杀死。
+理想结果：输出 Test mprotect OK!
+*/
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 4096 * 3;
+    assert_eq!(start as isize, mmap(start, len, 3));
+    let middle = start + 4096;
+    unsafe {
+        *(middle as *mut u8) = 42;
+    }
+    assert_eq!(-1, mprotect(start + 1, 4096, 1));
+    assert_eq!(-1, mprotect(middle, 4096, 0));
+    assert_eq!(-1, mprotect(middle, 4096, 8));
+    assert_eq!(-1, mprotect(start + len, 4096, 1));
+    assert_eq!(-1, mprotect(middle, len, 1));
+    assert_eq!(0, mprotect(middle, 4096, 1));
+    unsafe {
+        assert_eq!(*(middle as *const u8), 42);
+        *(start as *mut u8) = 1;
+        *((start + 4096 * 2) as *mut u8) = 3;
+    }
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            *(middle as *mut u8) = 0;
+        }
+        panic!("Should cause a store page fault!");
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(pid, waitpid(pid as usize, &mut exit_code));
+    assert_eq!(exit_code, -2);
+    assert_eq!(0, munmap(start, len));
+    println!("Test mprotect OK!");
+    0
+}
@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, mprotect, munmap, waitpid};
+
+/*
+程序行为：写过的页被 munmap 或改为只读后，TLB 中不能留下旧的表项：
+子进程读取已 munmap 的页、写入刚改为只读的页都会被内核杀死，
+在同一地址重新 mmap 的页读到的是 0 而不是旧页的内容。
+理想结果：输出 Test tlb stale OK!
+*/
+
+const START: usize = 0x10000000;
+const LEN: usize = 4096;
+
+fn touch(write: bool) {
+    let p = START as *mut u8;
+    unsafe {
+        if write {
+            p.write_volatile(0x5a);
+        } else {
+            println!("read {} after munmap", p.read_volatile());
+        }
+    }
+}
+
+/// 在子进程中运行 `f`，返回子进程的退出码
+fn run_child(f: fn()) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        f();
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+fn read_after_munmap() {
+    assert_eq!(START as isize, mmap(START, LEN, 3));
+    touch(true);
+    assert_eq!(unsafe { (START as *const u8).read_volatile() }, 0x5a);
+    assert_eq!(munmap(START, LEN), 0);
+    touch(false);
+}
+
+fn write_after_mprotect() {
+    assert_eq!(START as isize, mmap(START, LEN, 3));
+    touch(true);
+    assert_eq!(mprotect(START, LEN, 1), 0);
+    touch(true);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    assert_eq!(run_child(read_after_munmap), -2);
+    assert_eq!(run_child(write_after_mprotect), -2);
+    for _ in 0..16 {
+        assert_eq!(START as isize, mmap(START, LEN, 3));
+        assert_eq!(unsafe { (START as *const u8).read_volatile() }, 0);
+        touch(true);
+        assert_eq!(munmap(START, LEN), 0);
+    }
+    println!("Test tlb stale OK!");
+    0
+}
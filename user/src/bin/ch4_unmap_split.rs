@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap};
+
+/*
+程序行为：映射 10 页并在第 i 页写入 i，依次释放开头、结尾、中间的页，
+再以只读权限重新映射中间的空洞；最后释放跨越多个区域的范围和整个区域。
+理想结果：输出 Test unmap split OK!
+*/
+
+const PAGE: usize = 4096;
+
+fn read(start: usize, page: usize) -> usize {
+    unsafe { ((start + page * PAGE) as *const usize).read_volatile() }
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(start as isize, mmap(start, 10 * PAGE, 3));
+    for i in 0..10 {
+        unsafe {
+            ((start + i * PAGE) as *mut usize).write_volatile(i);
+        }
+    }
+    // 开头
+    assert_eq!(0, munmap(start, 2 * PAGE));
+    assert_eq!(munmap(start, 3 * PAGE), -1);
+    assert_eq!(start as isize, mmap(start, PAGE, 1));
+    assert_eq!(read(start, 0), 0);
+    // 结尾
+    assert_eq!(0, munmap(start + 8 * PAGE, 2 * PAGE));
+    assert_eq!(munmap(start + 7 * PAGE, 2 * PAGE), -1);
+    // 中间
+    assert_eq!(0, munmap(start + 4 * PAGE, 2 * PAGE));
+    assert_eq!(mmap(start + 3 * PAGE, 2 * PAGE, 3), -1);
+    assert_eq!(
+        (start + 4 * PAGE) as isize,
+        mmap(start + 4 * PAGE, 2 * PAGE, 1)
+    );
+    assert_eq!(read(start, 4), 0);
+    assert_eq!(read(start, 5), 0);
+    for i in [2, 3, 6, 7] {
+        assert_eq!(read(start, i), i);
+    }
+    // 跨越多个区域
+    assert_eq!(0, munmap(start + 2 * PAGE, 6 * PAGE));
+    assert_eq!(munmap(start + PAGE, PAGE), -1);
+    // 整个区域
+    assert_eq!(0, munmap(start, PAGE));
+    assert_eq!(munmap(start, PAGE), -1);
+    assert_eq!(start as isize, mmap(start, 10 * PAGE, 3));
+    assert_eq!(read(start, 9), 0);
+    println!("Test unmap split OK!");
+    0
+}
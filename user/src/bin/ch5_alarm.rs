@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{alarm, fork, get_time, sigaction, sigreturn, waitpid, SignalAction, SIGALRM};
+
+/// 程序行为：注册 SIGALRM 的处理函数后设置 1 秒的闹钟并空转，处理函数应在 1.1 秒内执行；
+/// alarm 返回之前的闹钟剩余的秒数，alarm(0) 取消闹钟；
+/// 没有处理函数时子进程被 SIGALRM 杀死，退出码为 -14。需要单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test alarm OK!
+
+static mut HANDLED: bool = false;
+
+fn func(_signum: usize) {
+    unsafe {
+        HANDLED = true;
+    }
+    sigreturn();
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(alarm(1), 0);
+        loop {}
+    }
+    let action = SignalAction {
+        handler: func as usize,
+        ..Default::default()
+    };
+    assert_eq!(sigaction(SIGALRM, Some(&action), None), 0);
+    assert_eq!(alarm(5), 0);
+    assert_eq!(alarm(0), 5);
+    assert_eq!(alarm(0), 0);
+    let start = get_time();
+    assert_eq!(alarm(1), 0);
+    while !unsafe { core::ptr::read_volatile(&HANDLED) } && get_time() - start <= 1100 {}
+    let elapsed = get_time() - start;
+    assert!(unsafe { HANDLED }, "SIGALRM is not delivered");
+    assert!(
+        (1000..=1100).contains(&elapsed),
+        "SIGALRM after {} ms",
+        elapsed
+    );
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -14);
+    println!("Test alarm OK!");
+    0
+}
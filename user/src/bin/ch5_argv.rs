@@ -0,0 +1,17 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+
+/*
+辅助测例，检查收到的参数为 ch5_argv hello cross，正确时返回 0。
+*/
+
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc == 3 && argv == ["ch5_argv", "hello", "cross"] {
+        0
+    } else {
+        -1
+    }
+}
@@ -0,0 +1,62 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, spawn, sysinfo, SysInfo};
+
+/// 程序行为：exec 和 spawn 构建时生成的几个损坏镜像（本程序的拷贝）：魔数错误、被截断或
+/// 段的 p_memsz 小于 p_filesz 返回 -2，32 位或非 RISC-V 返回 -3，段落在 mmap 区域中返回 -4，
+/// 段既可写又可执行返回 -5（内核打开 allow_wx 特性时不适用），两个段重叠返回 -6，
+/// 入口不在可执行段中返回 -7，不存在的程序返回 -1。
+/// 每次失败后本进程的地址空间应完好，全局变量和栈上数据不变，也没有泄漏物理页帧。
+/// 会统计全局的物理页帧，需单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test bad elf OK!
+
+static mut COUNTER: usize = 0;
+
+const IMAGES: [(&str, isize); 10] = [
+    ("corrupt_magic\0", -2),
+    ("corrupt_truncated\0", -2),
+    ("corrupt_class\0", -3),
+    ("corrupt_machine\0", -3),
+    ("corrupt_segment\0", -4),
+    ("corrupt_memsz\0", -2),
+    ("corrupt_wx\0", -5),
+    ("corrupt_overlap\0", -6),
+    ("corrupt_entry\0", -7),
+    ("no_such_app\0", -1),
+];
+
+fn free_frames() -> usize {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    info.free_frames
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let local = [0x5au8; 64];
+    // 先各执行一次，让内核堆等一次性的分配完成
+    for &(path, _) in IMAGES.iter() {
+        let args = [path.as_ptr(), core::ptr::null()];
+        exec(path, &args);
+    }
+    let free = free_frames();
+    for (i, &(path, code)) in IMAGES.iter().enumerate() {
+        let args = [path.as_ptr(), core::ptr::null()];
+        assert_eq!(exec(path, &args), code, "exec {}", path);
+        assert_eq!(spawn(path), code, "spawn {}", path);
+        unsafe {
+            COUNTER += 1;
+            assert_eq!(COUNTER, i + 1);
+        }
+        assert!(local.iter().all(|&x| x == 0x5a));
+    }
+    assert_eq!(free_frames(), free);
+    println!("Test bad elf OK!");
+    0
+}
@@ -0,0 +1,69 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, exit, fork, pipe, read, sleep, waitpid, write};
+
+/// 程序行为：重复关闭或关闭越界的描述符返回 -1，读写已关闭的描述符返回 -1；
+/// 关闭标准输出后 println 的内容被丢弃而不会出错；
+/// 子进程显式关闭写端后，即使它还没有退出，父进程也能读到 EOF。
+
+/// 正确输出：（无报错信息，不会出现 this line is discarded）
+/// Test close OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buffer = [0u8; 16];
+
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(close(pipe_fd[0]), -1);
+    assert_eq!(read(pipe_fd[0], &mut buffer), -1);
+    assert_eq!(write(pipe_fd[0], b"x"), -1);
+    assert_eq!(close(pipe_fd[1]), 0);
+    assert_eq!(close(1000), -1);
+    assert_eq!(close(usize::MAX), -1);
+
+    // 读写方向不对的描述符
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(read(pipe_fd[1], &mut buffer), -1);
+    assert_eq!(write(pipe_fd[0], b"x"), -1);
+    assert_eq!(write(0, b"x"), -1);
+    assert_eq!(read(1, &mut buffer), -1);
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(close(pipe_fd[1]), 0);
+
+    // 关闭标准输出，之后的输出被丢弃，再由 2 恢复
+    assert_eq!(close(1), 0);
+    println!("this line is discarded");
+    assert_eq!(write(1, b"x"), -1);
+    assert_eq!(dup(2), 1);
+    // 关闭标准输入
+    assert_eq!(close(0), 0);
+    assert_eq!(read(0, &mut buffer), -1);
+    assert_eq!(close(0), -1);
+
+    // 写端被关闭而不是随进程退出
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(close(pipe_fd[0]), 0);
+        assert_eq!(write(pipe_fd[1], b"done"), 4);
+        assert_eq!(close(pipe_fd[1]), 0);
+        sleep(100);
+        exit(0);
+    }
+    assert_eq!(close(pipe_fd[1]), 0);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 4);
+    assert_eq!(&buffer[..4], b"done");
+    assert_eq!(read(pipe_fd[0], &mut buffer), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+    println!("Test close OK!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, mmap, waitpid, TimeVal};
+
+/// 程序行为：fork 之后父子进程共享物理页，子进程写 mmap 区域、全局变量，
+/// 以及让内核写入栈上的 TimeVal，父进程都不应看到这些修改。
+/// 另外 fork 32 个子进程，每个子进程只写 mmap 区域中的一页。
+
+/// 正确输出：（无报错信息）
+/// Test cow OK!
+
+static mut GLOBAL: usize = 1;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let len: usize = 4096 * 16;
+    assert_eq!(start as isize, mmap(start, len, 3));
+    let buf = unsafe { core::slice::from_raw_parts_mut(start as *mut usize, len / 8) };
+    for (i, x) in buf.iter_mut().enumerate() {
+        *x = i;
+    }
+    let time = TimeVal::new();
+    let pid = fork();
+    if pid == 0 {
+        for x in buf.iter_mut() {
+            *x = 0;
+        }
+        unsafe {
+            GLOBAL = 2;
+        }
+        user_lib::sys_get_time(&time, 0);
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    for (i, x) in buf.iter().enumerate() {
+        assert_eq!(*x, i);
+    }
+    assert_eq!(unsafe { GLOBAL }, 1);
+    assert_eq!(unsafe { core::ptr::read_volatile(&time.sec) }, 0);
+    let mut pids = [0; 32];
+    for (i, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            buf[i % 16 * 512] = get_time() as usize;
+            exit(i as i32);
+        }
+    }
+    for (i, &pid) in pids.iter().enumerate() {
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, i as i32);
+    }
+    for (i, x) in buf.iter().enumerate() {
+        assert_eq!(*x, i);
+    }
+    println!("Test cow OK!");
+    0
+}
@@ -0,0 +1,70 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+extern crate alloc;
+
+use alloc::format;
+use user_lib::{
+    exit, fork, syslog, waitpid, SYSLOG_ACTION_READ_ALL, SYSLOG_ACTION_SIZE_BUFFER,
+    SYSLOG_ACTION_SIZE_UNREAD,
+};
+
+/*
+程序行为：fork 出的子进程写空指针被内核杀死后，读出内核日志缓冲区，其中应有杀死该子进程的记录，
+且带有时间戳和 pid；缓冲区太小时只读出最新的完整行。
+理想结果：输出 Test dmesg OK!
+*/
+
+const LOG_SIZE: usize = 64 * 1024;
+static mut LOG: [u8; LOG_SIZE] = [0; LOG_SIZE];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let size = syslog(SYSLOG_ACTION_SIZE_BUFFER, &mut []);
+    assert_eq!(size, LOG_SIZE as isize);
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            (0 as *mut u8).write_volatile(1);
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+
+    let used = syslog(SYSLOG_ACTION_SIZE_UNREAD, &mut []);
+    assert!(used > 0 && used <= size);
+    let buf = unsafe { &mut LOG };
+    let len = syslog(SYSLOG_ACTION_READ_ALL, buf);
+    assert!(len > 0 && len <= size);
+    let log = core::str::from_utf8(&buf[..len as usize]).unwrap();
+    let label = format!("(pid {} [", pid);
+    let record = log
+        .lines()
+        .filter(|line| line.contains(label.as_str()) && line.contains("core dumped"))
+        .last()
+        .expect("no kill record in the kernel log");
+    // 时间戳
+    assert!(record.starts_with('[') && record.contains("us] "));
+    println!("found: {}", record);
+
+    // 只装得下最后一部分时，从一行的开头读起
+    let mut small = [0u8; 100];
+    let len = syslog(SYSLOG_ACTION_READ_ALL, &mut small) as usize;
+    assert!(len <= small.len());
+    if len > 0 {
+        assert_eq!(small[0], b'[');
+        assert_eq!(small[len - 1], b'\n');
+    }
+    assert_eq!(
+        syslog(SYSLOG_ACTION_READ_ALL, unsafe {
+            core::slice::from_raw_parts_mut(0x10 as *mut u8, 16)
+        }),
+        -1
+    );
+    println!("Test dmesg OK!");
+    0
+}
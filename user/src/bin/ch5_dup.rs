@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, write, MAX_FDS};
+
+/// 程序行为：dup 标准输出得到最小的空闲描述符 3，经由它写出的内容照常输出；
+/// 关闭 1 后仍能经由 3 输出，再次 dup 得到 1，标准输出恢复。
+/// dup 未打开或越界的描述符返回 -1，描述符至多 MAX_FDS 个。
+
+/// 正确输出：
+/// written through a dup of stdout
+/// written after stdout is closed
+/// Test dup OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let fd = dup(1);
+    assert_eq!(fd, 3);
+    let fd = fd as usize;
+    let msg = b"written through a dup of stdout\n";
+    assert_eq!(write(fd, msg), msg.len() as isize);
+    assert_eq!(close(1), 0);
+    let msg = b"written after stdout is closed\n";
+    assert_eq!(write(fd, msg), msg.len() as isize);
+    assert_eq!(dup(fd), 1);
+    assert_eq!(close(fd), 0);
+
+    assert_eq!(dup(fd), -1);
+    assert_eq!(dup(MAX_FDS), -1);
+    assert_eq!(dup(usize::MAX), -1);
+    // 0..3 已打开，剩下的描述符都能分配出去
+    for i in 3..MAX_FDS {
+        assert_eq!(dup(2), i as isize);
+    }
+    assert_eq!(dup(2), -1);
+    for i in 3..MAX_FDS {
+        assert_eq!(close(i), 0);
+    }
+    println!("Test dup OK!");
+    0
+}
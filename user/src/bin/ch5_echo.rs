@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::console::{flush, getchar};
+use user_lib::{read, times, Tms};
+
+/// 程序行为：需要手动输入，因此不在 usertests 中运行。读入一行并回显，等待输入期间进程阻塞，
+/// 不会占满 CPU：最后比较等待期间用掉的 CPU 时间（主要是 stime）与经过的时间。
+/// 之后按 Ctrl-D，读到 EOF 后结束。
+
+/// 正确输出：
+/// echo: <输入的一行>
+/// Test echo OK!
+
+const LF: u8 = 0x0a;
+const CR: u8 = 0x0d;
+const LINE_LEN: usize = 128;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    println!("Type a line:");
+    let mut t0 = Tms::default();
+    let start = times(&mut t0);
+    let mut line = [0u8; LINE_LEN];
+    let mut len = 0;
+    loop {
+        let c = getchar();
+        if c == LF || c == CR {
+            println!("");
+            break;
+        }
+        if len < LINE_LEN {
+            line[len] = c;
+            len += 1;
+        }
+        print!("{}", c as char);
+        flush();
+    }
+    let mut t1 = Tms::default();
+    let end = times(&mut t1);
+    println!("echo: {}", core::str::from_utf8(&line[..len]).unwrap());
+    let elapsed = (end - start) as usize;
+    let used = (t1.utime + t1.stime) - (t0.utime + t0.stime);
+    println!("waited {} ticks, used {} ticks of CPU", elapsed, used);
+    // 阻塞等待时几乎不占用 CPU
+    assert!(used * 2 <= elapsed + 2);
+    println!("Press Ctrl-D to finish");
+    let mut buf = [0u8; 16];
+    while read(0, &mut buf) != 0 {}
+    println!("Test echo OK!");
+    0
+}
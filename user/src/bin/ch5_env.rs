@@ -0,0 +1,19 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+
+use user_lib::getenv;
+
+/*
+辅助测例，检查环境变量 PATH 为 foo 且不存在 PAT，正确时返回 0。
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    if getenv("PATH") == Some("foo") && getenv("PAT").is_none() {
+        0
+    } else {
+        -1
+    }
+}
@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, exit, fork, mmap, waitpid};
+
+/*
+程序行为：fork 出的子进程 exec 时传入参数，其中一个参数跨越页边界，新程序检查参数正确后返回 0；
+不传参数时新程序收到的 argc 为 0，返回 -1。
+理想结果：输出 Test exec argv OK!
+*/
+
+fn exec_argv(args: &[*const u8]) -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exec("ch5_argv\0", args);
+        exit(-2);
+    }
+    let mut exit_code: i32 = -3;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(start as isize, mmap(start, 4096 * 2, 3));
+    // "cross" starts 3 bytes before the end of the first page
+    let cross = start + 4096 - 3;
+    for (i, c) in b"cross\0".iter().enumerate() {
+        unsafe {
+            *((cross + i) as *mut u8) = *c;
+        }
+    }
+    let args = [
+        "ch5_argv\0".as_ptr(),
+        "hello\0".as_ptr(),
+        cross as *const u8,
+        core::ptr::null::<u8>(),
+    ];
+    assert_eq!(exec_argv(&args), 0, "child got wrong args");
+    assert_eq!(exec_argv(&[core::ptr::null::<u8>()]), -1);
+    println!("Test exec argv OK!");
+    0
+}
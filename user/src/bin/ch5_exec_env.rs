@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec_with_env, exit, fork, mmap, spawn, spawn_with_env, waitpid};
+
+/*
+程序行为：exec 和 spawn 时传入环境变量 PATH=foo，新程序通过 getenv 读到后返回 0；
+不传环境变量时新程序返回 -1，环境变量总长度超过 4 KiB 时 spawn 返回 -1。
+理想结果：输出 Test exec env OK!
+*/
+
+const NO_ARGS: [*const u8; 1] = [core::ptr::null::<u8>()];
+
+fn wait_for(pid: isize) -> i32 {
+    assert!(pid > 0);
+    let mut exit_code: i32 = -3;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let envs = [
+        "HOME=/\0".as_ptr(),
+        "PATH=foo\0".as_ptr(),
+        core::ptr::null::<u8>(),
+    ];
+    let pid = fork();
+    if pid == 0 {
+        exec_with_env("ch5_env\0", &NO_ARGS, &envs);
+        exit(-2);
+    }
+    assert_eq!(wait_for(pid), 0, "exec got wrong envs");
+    assert_eq!(wait_for(spawn_with_env("ch5_env\0", &NO_ARGS, &envs)), 0);
+    assert_eq!(wait_for(spawn("ch5_env\0")), -1);
+    // a single env longer than 4 KiB
+    let start: usize = 0x10000000;
+    assert_eq!(start as isize, mmap(start, 4096 * 2, 3));
+    for i in start..(start + 4096 * 2 - 1) {
+        unsafe {
+            *(i as *mut u8) = b'a';
+        }
+    }
+    unsafe {
+        *((start + 4096 * 2 - 1) as *mut u8) = 0;
+    }
+    let envs = [start as *const u8, core::ptr::null::<u8>()];
+    assert_eq!(spawn_with_env("ch5_env\0", &NO_ARGS, &envs), -1);
+    println!("Test exec env OK!");
+    0
+}
@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate user_lib;
+
+use alloc::format;
+use user_lib::{exec_with_env, getenv, sysinfo, SysInfo};
+
+/// 程序行为：进程反复 exec 自己 500 次，每次通过参数传递剩余次数和第一次 exec 后的空闲物理页帧数，
+/// 每一轮的空闲物理页帧数都应与第一轮相同（允许极少量误差），说明 exec 没有泄漏旧的地址空间。
+/// 会统计全局的物理页帧，需单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test exec loop OK!
+
+const ROUNDS: usize = 500;
+
+fn free_frames() -> usize {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    info.free_frames
+}
+
+fn exec_self(left: usize, baseline: usize) -> i32 {
+    let left = format!("{}\0", left);
+    let baseline = format!("{}\0", baseline);
+    let args = [
+        "ch5_exec_loop\0".as_ptr(),
+        left.as_ptr(),
+        baseline.as_ptr(),
+        core::ptr::null(),
+    ];
+    let envs = ["EXEC_LOOP=1\0".as_ptr(), core::ptr::null::<u8>()];
+    exec_with_env("ch5_exec_loop\0", &args, &envs);
+    panic!("exec failed");
+}
+
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    // usertests 启动时不带参数和环境变量，环境变量不经过 a0，
+    // 即使 argc 传错也能认出 exec 之后的程序，测例失败而不是无限 exec 下去
+    if getenv("EXEC_LOOP").is_none() {
+        return exec_self(ROUNDS, 0);
+    }
+    assert_eq!(argc, 3, "exec passed {} args", argc);
+    let left: usize = argv[1].parse().unwrap();
+    assert!(left <= ROUNDS);
+    let mut baseline: usize = argv[2].parse().unwrap();
+    let free = free_frames();
+    if left == ROUNDS {
+        baseline = free;
+    }
+    assert!(
+        free + 2 >= baseline && free <= baseline + 2,
+        "free frames drifted from {} to {} after {} execs",
+        baseline,
+        free,
+        ROUNDS - left
+    );
+    if left > 1 {
+        return exec_self(left - 1, baseline);
+    }
+    println!("Test exec loop OK!");
+    0
+}
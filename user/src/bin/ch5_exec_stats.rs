@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exec_with_env, get_name, get_time, getenv, getpid, sched_stat, set_name, set_priority, sleep,
+    task_info, SchedEntry, SchedStat, TaskInfo, SYSCALL_EXEC, SYSCALL_GETTIMEOFDAY,
+    SYSCALL_SET_PRIORITY, SYSCALL_TASK_INFO, SYSCALL_WRITE, SYSCALL_YIELD, TASK_NAME_LEN,
+};
+
+/*
+程序行为：改名、设置优先级并 sleep 300 ms 后 exec 自己，新程序的 syscall 计数和运行时间从零开始，
+优先级保持不变，名字换成新程序的名字。
+理想结果：输出 Test exec stats OK!
+*/
+
+const PRIORITY: isize = 7;
+const SLEEP_MS: usize = 300;
+
+fn after_exec() -> i32 {
+    let info = TaskInfo::new();
+    assert_eq!(0, task_info(&info));
+    assert_eq!(1, info.syscall_times[SYSCALL_TASK_INFO]);
+    for id in [
+        SYSCALL_GETTIMEOFDAY,
+        SYSCALL_YIELD,
+        SYSCALL_WRITE,
+        SYSCALL_SET_PRIORITY,
+        SYSCALL_EXEC,
+    ] {
+        assert_eq!(0, info.syscall_times[id], "syscall {} kept its count", id);
+    }
+    // the sleep before the exec is not counted
+    assert!(info.time < SLEEP_MS, "running for {} ms", info.time);
+    let mut name = [0u8; TASK_NAME_LEN];
+    assert_eq!(get_name(&mut name), 0);
+    assert_eq!(&name[..15], b"ch5_exec_stats\0");
+    let mut stat = SchedStat::default();
+    let mut entries = [SchedEntry::default(); 1];
+    assert!(sched_stat(&mut stat, &mut entries) >= 1);
+    assert_eq!(entries[0].pid, getpid() as usize);
+    assert_eq!(entries[0].priority, PRIORITY as usize);
+    println!("Test exec stats OK!");
+    0
+}
+
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    // 环境变量不经过 a0，即使 argc 传错也能认出 exec 之后的程序，而不会无限 exec 下去
+    if getenv("EXEC_STATS").is_some() {
+        assert!(argc == 2 && argv[1] == "exec", "exec passed {} args", argc);
+        return after_exec();
+    }
+    assert_eq!(set_priority(PRIORITY), PRIORITY);
+    assert_eq!(set_name("renamed\0"), 0);
+    get_time();
+    sleep(SLEEP_MS);
+    let args = [
+        "ch5_exec_stats\0".as_ptr(),
+        "exec\0".as_ptr(),
+        core::ptr::null::<u8>(),
+    ];
+    let envs = ["EXEC_STATS=1\0".as_ptr(), core::ptr::null::<u8>()];
+    exec_with_env("ch5_exec_stats\0", &args, &envs);
+    panic!("exec failed");
+}
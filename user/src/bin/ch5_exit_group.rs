@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit_group, fork, get_time, sleep_ms, sysinfo, waitpid, SysInfo};
+
+/// 程序行为：子进程逐层 fork 出三代空转的后代进程后调用 exit_group，
+/// 所有后代都应被杀死并由 initproc 回收，1 秒内活跃进程数回到测试开始时的值。需要单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test exit group OK!
+
+fn procs() -> usize {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    info.procs
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let before = procs();
+    let top = fork();
+    if top == 0 {
+        // each generation forks the next one and spins
+        let mut depth = 0;
+        while depth < 3 && fork() == 0 {
+            depth += 1;
+        }
+        if depth > 0 {
+            loop {}
+        }
+        while procs() != before + 4 {
+            sleep_ms(10);
+        }
+        exit_group(7);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(top as usize, &mut exit_code), top);
+    assert_eq!(exit_code, 7);
+    let start = get_time();
+    while procs() != before {
+        assert!(
+            get_time() - start < 1000,
+            "{} processes left",
+            procs() - before
+        );
+        sleep_ms(10);
+    }
+    println!("Test exit group OK!");
+    0
+}
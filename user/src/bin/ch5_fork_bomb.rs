@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, sleep_blocking, sysinfo, waitpid, SysInfo, NPROC};
+
+/// 程序行为：不断 fork 睡眠中的子进程，直到活跃进程数达到 NPROC 后 fork 返回 -1，
+/// 此时 sysinfo 报告的进程数恰为 NPROC；之后父进程能回收所有子进程，进程数恢复原状。
+
+/// 正确输出：（无报错信息）
+/// Test fork bomb OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    let before = info.procs;
+    let mut pids = [0; NPROC];
+    let mut forked = 0;
+    loop {
+        let pid = fork();
+        if pid == 0 {
+            sleep_blocking(100);
+            exit(0);
+        }
+        if pid < 0 {
+            break;
+        }
+        pids[forked] = pid;
+        forked += 1;
+    }
+    assert_eq!(sysinfo(&mut info), 0);
+    println!("forked {} children, {} processes", forked, info.procs);
+    assert_eq!(info.procs, NPROC);
+    assert_eq!(before + forked, NPROC);
+    for &pid in pids.iter().take(forked) {
+        let mut exit_code = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    assert_eq!(sysinfo(&mut info), 0);
+    assert_eq!(info.procs, before);
+    println!("Test fork bomb OK!");
+    0
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mem_stats, sysinfo, waitpid, MemStats, SysInfo};
+
+/// 程序行为：带着 64 页只读数据的进程 fork，子进程的代码段和只读数据段与父进程共用物理页帧，
+/// 子进程开始运行时新分配的物理页帧远少于父进程驻留的页数；
+/// 两者退出并被回收后，已分配的物理页帧回到开始时的数量附近。
+/// 会统计全局的物理页帧，需单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test fork share OK!
+
+const PAGE_SIZE: usize = 4096;
+const RODATA_PAGES: usize = 64;
+
+static RODATA: [u8; RODATA_PAGES * PAGE_SIZE] = [0x5a; RODATA_PAGES * PAGE_SIZE];
+
+fn allocated(info: &SysInfo) -> usize {
+    info.total_frames - info.free_frames
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let rodata = RODATA.as_ptr();
+    assert_eq!(
+        unsafe { rodata.add(RODATA.len() - 1).read_volatile() },
+        0x5a
+    );
+    let mut stats = MemStats::default();
+    assert_eq!(mem_stats(&mut stats), 0);
+    assert!(stats.resident_pages >= RODATA_PAGES);
+    let mut before = SysInfo::default();
+    assert_eq!(sysinfo(&mut before), 0);
+    let pid = fork();
+    if pid == 0 {
+        let mut info = SysInfo::default();
+        assert_eq!(sysinfo(&mut info), 0);
+        // 页表、内核栈、陷入上下文以及写时复制的栈页
+        let forked = allocated(&info) - allocated(&before);
+        assert!(forked < stats.resident_pages / 2);
+        assert_eq!(unsafe { rodata.read_volatile() }, 0x5a);
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let mut after = SysInfo::default();
+    assert_eq!(sysinfo(&mut after), 0);
+    // 页表页和内核堆可能留下少量差异
+    assert!(allocated(&after) < allocated(&before) + 4);
+    println!("Test fork share OK!");
+    0
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, sched_stat, waitpid, yield_, SchedEntry, SchedStat};
+
+/*
+程序行为：父进程让出几次 CPU 累积 stride 后 fork，子进程的 stride 不小于 fork 前父进程的 stride。
+理想结果：输出 Test fork stride OK!
+*/
+
+fn my_stride() -> usize {
+    let mut stat = SchedStat::default();
+    let mut entries = [SchedEntry::default(); 1];
+    assert!(sched_stat(&mut stat, &mut entries) >= 1);
+    assert_eq!(entries[0].pid, getpid() as usize);
+    entries[0].stride
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..10 {
+        yield_();
+    }
+    let parent_stride = my_stride();
+    assert!(parent_stride > 0);
+    let pid = fork();
+    if pid == 0 {
+        let behind = (my_stride().wrapping_sub(parent_stride) as isize) < 0;
+        exit(behind as i32);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0, "the child started behind its parent");
+    println!("Test fork stride OK!");
+    0
+}
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, sysinfo, waitpid, SysInfo};
+
+/// 程序行为：子进程映射并写满 200 页，再 fork 出孙进程改写其中一半，两者退出并被回收后，
+/// 已分配的物理页帧回到开始时的数量附近（检查 fork/exit 是否泄漏物理页帧），
+/// 峰值至少比开始时多 200 页，且这期间没有分配失败。
+/// 会统计全局的物理页帧，需单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test frame stats OK!
+
+const PAGES: usize = 200;
+const PAGE_SIZE: usize = 4096;
+
+fn child() -> i32 {
+    let start: usize = 0x20000000;
+    assert_eq!(mmap(start, PAGES * PAGE_SIZE, 3), start as isize);
+    for i in 0..PAGES {
+        unsafe {
+            ((start + i * PAGE_SIZE) as *mut usize).write_volatile(i);
+        }
+    }
+    let pid = fork();
+    if pid == 0 {
+        for i in (0..PAGES).step_by(2) {
+            unsafe {
+                ((start + i * PAGE_SIZE) as *mut usize).write_volatile(0);
+            }
+        }
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    0
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = SysInfo::default();
+    assert_eq!(sysinfo(&mut before), 0);
+    assert!(before.peak_frames >= before.total_frames - before.free_frames);
+    let pid = fork();
+    if pid == 0 {
+        exit(child());
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    let mut after = SysInfo::default();
+    assert_eq!(sysinfo(&mut after), 0);
+    let allocated = |info: &SysInfo| info.total_frames - info.free_frames;
+    // 页表页和内核堆可能留下少量差异
+    assert!(allocated(&after) < allocated(&before) + 4);
+    assert!(after.peak_frames >= allocated(&before) + PAGES);
+    assert_eq!(after.frame_failures, before.frame_failures);
+    println!("Test frame stats OK!");
+    0
+}
@@ -0,0 +1,31 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, getppid, spawn, waitpid};
+
+/// 程序行为：fork 出的子进程和 spawn 出的子进程（ch5_ppid）都以 getppid 的结果作为退出码，
+/// 父进程检查退出码等于自己的 getpid。
+
+/// 正确输出：（无报错信息）
+/// Test getppid OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = getpid() as i32;
+    let child = fork();
+    if child == 0 {
+        exit(getppid() as i32);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    assert_eq!(exit_code, pid);
+    let child = spawn("ch5_ppid\0");
+    assert!(child > 0);
+    assert_eq!(waitpid(child as usize, &mut exit_code), child);
+    assert_eq!(exit_code, pid);
+    println!("Test getppid OK!");
+    0
+}
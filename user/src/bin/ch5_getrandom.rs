@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getrandom, waitpid, GRND_NONBLOCK};
+
+/// 程序行为：连续两次取 32 字节随机数，结果不同；fork 出的两个子进程各取 32 字节，
+/// 通过退出码传回的前 4 字节也不同。一次最多取 256 字节，跨页的缓冲区也能填满，
+/// 非法标志或地址返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test getrandom OK!
+
+static mut PAGES: [u8; 8192] = [0; 8192];
+
+fn draw() -> [u8; 32] {
+    let mut buf = [0u8; 32];
+    assert_eq!(getrandom(&mut buf, 0), 32);
+    buf
+}
+
+fn child_draw() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let buf = draw();
+        exit(i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]));
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_ne!(draw(), draw());
+    assert_ne!(child_draw(), child_draw());
+
+    // the buffer crosses a page boundary
+    let pages = unsafe { &mut PAGES };
+    let boundary = 4096 - pages.as_ptr() as usize % 4096;
+    let start = if boundary >= 100 {
+        boundary - 100
+    } else {
+        boundary + 4096 - 100
+    };
+    let buf = &mut pages[start..start + 300];
+    assert_eq!(getrandom(buf, GRND_NONBLOCK), 256);
+    assert!(buf[..256].iter().any(|&b| b != 0));
+    assert!(buf[256..].iter().all(|&b| b == 0));
+
+    let mut buf = [0u8; 16];
+    assert_eq!(getrandom(&mut buf, 4), -1);
+    let bad = unsafe { core::slice::from_raw_parts_mut(0x10 as *mut u8, 16) };
+    assert_eq!(getrandom(bad, 0), -1);
+    println!("Test getrandom OK!");
+    0
+}
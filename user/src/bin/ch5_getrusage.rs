@@ -0,0 +1,81 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, getrusage, waitpid, yield_, Rusage, RUSAGE_CHILDREN, RUSAGE_SELF,
+};
+
+/// 程序行为：一个子进程 yield 200 次，主动切换次数应不少于 200；另一个子进程空转 400 ms，
+/// 被时钟抢占的次数应远多于主动切换（MLFQ 最低一级的时间片也只有 80 ms）。
+/// 两个子进程被回收后，RUSAGE_CHILDREN 应包含它们的切换次数，未知的 who 返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test getrusage OK!
+
+const YIELDS: usize = 200;
+const SPIN_MS: isize = 400;
+const MIN_PREEMPTS: usize = 3;
+
+fn self_usage() -> Rusage {
+    let mut usage = Rusage::default();
+    assert_eq!(getrusage(RUSAGE_SELF, &mut usage), 0);
+    usage
+}
+
+fn run_child(body: fn()) {
+    let pid = fork();
+    assert!(pid >= 0);
+    if pid == 0 {
+        body();
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+}
+
+fn yielder() {
+    for _ in 0..YIELDS {
+        yield_();
+    }
+    let usage = self_usage();
+    println!(
+        "yielder: {} voluntary, {} involuntary",
+        usage.nvcsw, usage.nivcsw
+    );
+    assert!(usage.nvcsw >= YIELDS);
+}
+
+fn spinner() {
+    let start = get_time();
+    while get_time() - start < SPIN_MS {}
+    let usage = self_usage();
+    println!(
+        "spinner: {} voluntary, {} involuntary",
+        usage.nvcsw, usage.nivcsw
+    );
+    assert!(usage.nivcsw >= MIN_PREEMPTS);
+    assert!(usage.nivcsw > usage.nvcsw);
+    // 空转的时间都在用户态
+    assert!(usage.utime.sec * 1000 + usage.utime.usec / 1000 > 0);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut before = Rusage::default();
+    assert_eq!(getrusage(RUSAGE_CHILDREN, &mut before), 0);
+    run_child(yielder);
+    run_child(spinner);
+    let mut children = Rusage::default();
+    assert_eq!(getrusage(RUSAGE_CHILDREN, &mut children), 0);
+    assert!(children.nvcsw - before.nvcsw >= YIELDS);
+    assert!(children.nivcsw - before.nivcsw >= MIN_PREEMPTS);
+    let mut usage = Rusage::default();
+    assert_eq!(getrusage(1, &mut usage), -1);
+    assert_eq!(getrusage(-2, &mut usage), -1);
+    println!("Test getrusage OK!");
+    0
+}
@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sleep_blocking};
+
+/// 程序行为：单独运行时阻塞睡眠 1 s，期间就绪队列可能为空，内核应进入空闲等待而不是 panic，
+/// 醒来后检查经过的时间不少于 1 s 并输出。
+
+/// 正确输出：（无报错信息）
+/// Test idle sleep OK!
+
+#[no_mangle]
+fn main() -> i32 {
+    let start = get_time();
+    sleep_blocking(1000);
+    let elapsed = get_time() - start;
+    assert!(elapsed >= 1000, "woke up too early: {}ms", elapsed);
+    println!("Test idle sleep OK!");
+    0
+}
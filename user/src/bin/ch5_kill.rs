@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{fork, kill, sleep_blocking, waitpid, SIGKILL};
+
+/// 程序行为：fork 出一个死循环的子进程和一个睡眠的子进程，用 SIGKILL 杀死它们后 waitpid 得到退出码 -9；
+/// 杀死 initproc（pid 0）或不存在的进程返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test kill OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(kill(0, SIGKILL), -1);
+    assert_eq!(kill(0x7fff_ffff, SIGKILL), -1);
+    let looping = fork();
+    if looping == 0 {
+        #[allow(clippy::empty_loop)]
+        loop {}
+    }
+    let sleeping = fork();
+    if sleeping == 0 {
+        sleep_blocking(1_000_000);
+        return 0;
+    }
+    sleep_blocking(20);
+    assert_eq!(kill(looping as usize, SIGKILL), 0);
+    assert_eq!(kill(sleeping as usize, SIGKILL), 0);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(looping as usize, &mut exit_code), looping);
+    assert_eq!(exit_code, -(SIGKILL as i32));
+    assert_eq!(waitpid(sleeping as usize, &mut exit_code), sleeping);
+    assert_eq!(exit_code, -(SIGKILL as i32));
+    println!("Test kill OK!");
+    0
+}
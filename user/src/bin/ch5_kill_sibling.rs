@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpgid, kill, pause, sleep_blocking, waitpid, SIGKILL};
+
+/// 程序行为：第一个子进程阻塞在 pause 中，第二个子进程通过 pid 找到这个兄弟进程，
+/// 读取它的进程组并用 SIGKILL 杀死它；阻塞中的进程也能被找到。
+/// 之后再找已被回收的 pid 会失败。
+
+/// 正确输出：（无报错信息）
+/// Test kill sibling OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let sleeper = fork();
+    if sleeper == 0 {
+        pause();
+        exit(0);
+    }
+    let killer = fork();
+    if killer == 0 {
+        // let the sibling block first
+        sleep_blocking(50);
+        assert_eq!(getpgid(sleeper as usize), getpgid(0));
+        assert_eq!(kill(sleeper as usize, SIGKILL), 0);
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(killer as usize, &mut exit_code), killer);
+    assert_eq!(exit_code, 0);
+    assert_eq!(waitpid(sleeper as usize, &mut exit_code), sleeper);
+    assert_eq!(exit_code, -9);
+    assert_eq!(getpgid(sleeper as usize), -1);
+    assert_eq!(kill(sleeper as usize, SIGKILL), -1);
+    println!("Test kill sibling OK!");
+    0
+}
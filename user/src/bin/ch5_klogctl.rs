@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{klogctl, yield_};
+
+/*
+程序行为：把内核日志级别调到 TRACE 后 yield 一次再调回，之后只对 task::manager 开启 TRACE 再 yield 一次。
+两次 yield 期间内核应输出调度的 TRACE 日志，调回之后不再输出。
+只有 pid 1 或开启 klogctl_any 特性的内核允许调整，否则调用返回 -1，测例只检查这一点。
+需要单独运行。
+理想结果：输出 Test klogctl OK!
+*/
+
+const TRACE: usize = 5;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let previous = klogctl(TRACE, None);
+    if previous < 0 {
+        assert_eq!(klogctl(0, Some("task::manager\0")), -1);
+        println!("klogctl not permitted, skipped");
+        println!("Test klogctl OK!");
+        return 0;
+    }
+    yield_();
+    assert_eq!(klogctl(previous as usize, None), TRACE as isize);
+    // 超出范围的级别
+    assert_eq!(klogctl(TRACE + 1, None), -1);
+
+    assert_eq!(klogctl(TRACE, Some("task::manager\0")), 0);
+    yield_();
+    assert_eq!(klogctl(0, Some("task::manager\0")), TRACE as isize);
+    println!("Test klogctl OK!");
+    0
+}
@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, exit, fork, list_apps, waitpid};
+
+/// 程序行为：list_apps 返回完整列表的长度，缓冲区不够时只截断复制；列表按字母序排列，
+/// 每个名字后跟一个换行，应包含本程序和 ch5b_initproc。名字末尾多一个空格或中间有空格的
+/// 程序不存在，exec 返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test list apps OK!
+
+const LIST_LEN: usize = 4096;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; LIST_LEN];
+    let len = list_apps(&mut buf);
+    assert!(len > 0 && len as usize <= LIST_LEN);
+    let len = len as usize;
+    assert_eq!(buf[len - 1], b'\n');
+    let list = core::str::from_utf8(&buf[..len]).unwrap();
+    let mut count = 0;
+    let mut prev = "";
+    for name in list.lines() {
+        assert!(!name.is_empty() && !name.contains(' '));
+        assert!(prev < name);
+        prev = name;
+        count += 1;
+    }
+    assert!(list.lines().any(|name| name == "ch5_list_apps"));
+    assert!(list.lines().any(|name| name == "ch5b_initproc"));
+    // 截断时返回的仍是完整长度，缓冲区之外不写
+    let mut short = [0xffu8; 9];
+    assert_eq!(list_apps(&mut short[..8]), len as isize);
+    assert_eq!(&short[..8], &buf[..8]);
+    assert_eq!(short[8], 0xff);
+    assert_eq!(list_apps(&mut []), len as isize);
+    for path in [
+        "ch2b_hello_world \0",
+        "ch2b_hello world\0",
+        " ch2b_hello_world\0",
+    ] {
+        let pid = fork();
+        if pid == 0 {
+            let args = [path.as_ptr(), core::ptr::null()];
+            exit(if exec(path, &args) == -1 { 0 } else { -1 });
+        }
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0, "exec of {:?} did not fail", path);
+    }
+    println!("{} apps", count);
+    println!("Test list apps OK!");
+    0
+}
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mem_stats, mmap, munmap, waitpid, MemStats};
+
+/// 程序行为：mmap 50 页只访问其中 10 页，常驻页数只增加 10，其余 40 页仍是未分配的懒页；
+/// fork 之后子进程与父进程共享这些物理页，它们在子进程中计为常驻页和共享页；
+/// munmap 之后两项计数都回到 mmap 之前。
+
+/// 正确输出：（无报错信息）
+/// Test mem stats OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    let page: usize = 4096;
+    let mut before = MemStats::default();
+    let mut after = MemStats::default();
+    assert_eq!(0, mem_stats(&mut before));
+    assert_eq!(start as isize, mmap(start, 50 * page, 3));
+    assert_eq!(0, mem_stats(&mut after));
+    assert_eq!(after.resident_pages, before.resident_pages);
+    assert_eq!(after.lazy_pages, before.lazy_pages + 50);
+    for i in 0..10 {
+        unsafe {
+            ((start + i * 5 * page) as *mut usize).write_volatile(i);
+        }
+    }
+    assert_eq!(0, mem_stats(&mut after));
+    assert_eq!(after.resident_pages, before.resident_pages + 10);
+    assert_eq!(after.lazy_pages, before.lazy_pages + 40);
+    let pid = fork();
+    if pid == 0 {
+        let mut child = MemStats::default();
+        assert_eq!(0, mem_stats(&mut child));
+        assert_eq!(child.resident_pages, after.resident_pages);
+        assert_eq!(child.lazy_pages, after.lazy_pages);
+        assert!(child.shared_pages >= 10);
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(pid, waitpid(pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    assert_eq!(0, munmap(start, 50 * page));
+    assert_eq!(0, mem_stats(&mut after));
+    assert_eq!(after.resident_pages, before.resident_pages);
+    assert_eq!(after.lazy_pages, before.lazy_pages);
+    println!("Test mem stats OK!");
+    0
+}
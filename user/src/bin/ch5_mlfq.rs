@@ -0,0 +1,33 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sched_queue, yield_};
+
+/// 程序行为：在 MLFQ 调度下忙等，任务用完时间片后应逐级降到最低一级队列；
+/// 主动 yield 后回到最高一级队列（之后最多被时钟再降一级）。
+/// 使用 stride 调度时 sched_queue 返回 -1，测例直接通过。
+
+/// 正确输出：（无报错信息）
+/// Test mlfq OK!
+
+const LOWEST: isize = 3;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    if sched_queue() == -1 {
+        println!("stride scheduler, skip");
+        println!("Test mlfq OK!");
+        return 0;
+    }
+    let start = get_time();
+    while sched_queue() != LOWEST {
+        assert!(get_time() - start < 3000);
+    }
+    yield_();
+    assert!(sched_queue() <= 1);
+    println!("Test mlfq OK!");
+    0
+}
@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, kill, nanosleep, sigaction, sigreturn, sleep_ms, waitpid_nohang,
+    SignalAction, TimeSpec, SIGCHLD, SIGUSR1,
+};
+
+/// 程序行为：子进程 nanosleep 250 ms，父进程在约 50 ms 后用信号打断它，
+/// 子进程的 nanosleep 应返回 -1 并报告合理的剩余时间；非法的 nsec 返回 -1，
+/// 不被打断的 nanosleep 返回 0 且剩余时间为 0，默认忽略的 SIGCHLD 不会打断 nanosleep。
+
+/// 正确输出：（无报错信息）
+/// Test nanosleep OK!
+
+static mut HANDLED: bool = false;
+
+fn func(_signum: usize) {
+    unsafe {
+        HANDLED = true;
+    }
+    sigreturn();
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let bad = TimeSpec {
+        sec: 0,
+        nsec: 1_000_000_000,
+    };
+    assert_eq!(nanosleep(&bad, None), -1);
+    let req = TimeSpec {
+        sec: 0,
+        nsec: 20_000_000,
+    };
+    let mut rem = TimeSpec { sec: 1, nsec: 1 };
+    let start = get_time();
+    assert_eq!(nanosleep(&req, Some(&mut rem)), 0);
+    assert!(get_time() - start >= 20);
+    assert_eq!(rem, TimeSpec::default());
+    // 不足一个时钟周期的睡眠也会让出 CPU 并正常返回
+    assert_eq!(sleep_ms(0), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        let action = SignalAction {
+            handler: func as usize,
+            ..Default::default()
+        };
+        assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+        let req = TimeSpec {
+            sec: 0,
+            nsec: 250_000_000,
+        };
+        let mut rem = TimeSpec::default();
+        assert_eq!(nanosleep(&req, Some(&mut rem)), -1);
+        assert!(unsafe { HANDLED });
+        let rem_ms = rem.sec * 1000 + rem.nsec / 1_000_000;
+        println!("nanosleep interrupted, {} ms remaining", rem_ms);
+        let plausible = rem.sec == 0 && rem_ms > 0 && rem_ms < 250;
+        exit(if plausible { 0 } else { -1 });
+    }
+    assert_eq!(signal_until_exit(pid, SIGUSR1), 0);
+
+    let pid = fork();
+    if pid == 0 {
+        let req = TimeSpec {
+            sec: 0,
+            nsec: 250_000_000,
+        };
+        let mut rem = TimeSpec { sec: 1, nsec: 1 };
+        let start = get_time();
+        let ret = nanosleep(&req, Some(&mut rem));
+        let slept = get_time() - start >= 250;
+        exit(if ret == 0 && slept && rem == TimeSpec::default() {
+            0
+        } else {
+            -1
+        });
+    }
+    assert_eq!(signal_until_exit(pid, SIGCHLD), 0);
+    println!("Test nanosleep OK!");
+    0
+}
+
+/// 子进程可能还没开始睡眠，因此每隔约 50 ms 发送一次信号，直到它退出，返回它的退出码
+fn signal_until_exit(pid: isize, signum: usize) -> i32 {
+    let mut exit_code: i32 = -1;
+    loop {
+        sleep_ms(50);
+        assert_eq!(kill(pid as usize, signum), 0);
+        match waitpid_nohang(pid, &mut exit_code) {
+            0 => continue,
+            ret => {
+                assert_eq!(ret, pid);
+                return exit_code;
+            }
+        }
+    }
+}
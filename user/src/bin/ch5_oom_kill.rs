@@ -0,0 +1,81 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    fork, mmap, pipe, prlimit, read, sleep_blocking, sysinfo, waitpid, write, RLimit, SysInfo,
+    RLIMIT_AS, RLIM_INFINITY,
+};
+
+/// 程序行为：子进程写满几乎全部空闲的物理页帧后睡眠，父进程再写入事先映射好的 64 页，
+/// 缺页时物理页帧耗尽，内核杀死占用最多的子进程（退出码 -9）腾出内存，父进程正常完成。
+/// 会耗尽物理内存，需单独运行。
+
+/// 正确输出：（内核输出杀死子进程的信息）
+/// Test oom kill OK!
+
+const PAGE_SIZE: usize = 4096;
+const PAGES: usize = 64;
+/// 子进程留下的空闲物理页帧数，不够父进程写完 PAGES 页
+const LEFT: usize = 8;
+
+fn free_frames() -> usize {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    info.free_frames
+}
+
+fn hog(ready: usize) -> ! {
+    let start: usize = 0x40000000;
+    let pages = free_frames();
+    assert_eq!(mmap(start, pages * PAGE_SIZE, 3), start as isize);
+    let mut used = 0;
+    while used < pages && free_frames() > LEFT {
+        unsafe {
+            ((start + used * PAGE_SIZE) as *mut usize).write_volatile(used);
+        }
+        used += 1;
+    }
+    println!("hog took {} pages", used);
+    assert_eq!(write(ready, b"!"), 1);
+    loop {
+        sleep_blocking(1000);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let unlimited = RLimit {
+        rlim_cur: RLIM_INFINITY,
+        rlim_max: RLIM_INFINITY,
+    };
+    assert_eq!(0, prlimit(0, RLIMIT_AS, Some(&unlimited), None));
+    // 先映射，物理页帧在写入时才分配
+    let start: usize = 0x10000000;
+    assert_eq!(mmap(start, PAGES * PAGE_SIZE, 3), start as isize);
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        hog(pipe_fd[1]);
+    }
+    let mut buf = [0u8; 1];
+    assert_eq!(read(pipe_fd[0], &mut buf), 1);
+    assert!(free_frames() <= LEFT);
+    for i in 0..PAGES {
+        unsafe {
+            ((start + i * PAGE_SIZE) as *mut usize).write_volatile(i);
+        }
+    }
+    for i in 0..PAGES {
+        let value = unsafe { ((start + i * PAGE_SIZE) as *const usize).read_volatile() };
+        assert_eq!(value, i);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -9);
+    println!("Test oom kill OK!");
+    0
+}
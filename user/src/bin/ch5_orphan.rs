@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, sleep, spawn, waitpid};
+
+/*
+程序行为：子进程 fork 出一个睡眠的孙进程后立即退出，孙进程被过继给 initproc。
+孙进程退出后 initproc 回收它，其 pid 应能被新进程重新使用。需要单独运行。
+理想结果：输出 Test orphan OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let orphan = fork();
+        if orphan == 0 {
+            sleep(100);
+            exit(0);
+        }
+        // 把孙进程的 pid 作为退出码交给父进程
+        exit(orphan as i32);
+    }
+    let mut orphan: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut orphan), pid);
+    assert!(orphan > 0 && orphan as isize != getpid());
+    // 等待孙进程退出并被 initproc 回收
+    sleep(300);
+    let mut reused = false;
+    for _ in 0..16 {
+        let new_pid = spawn("ch2b_hello_world\0");
+        assert!(new_pid > 0);
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(new_pid as usize, &mut exit_code), new_pid);
+        if new_pid == orphan as isize {
+            reused = true;
+            break;
+        }
+        sleep(50);
+    }
+    assert!(reused, "pid {} of the orphan is never recycled", orphan);
+    println!("Test orphan OK!");
+    0
+}
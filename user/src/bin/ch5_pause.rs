@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, getpid, kill, pause, sigaction, sigreturn, sleep_ms, waitpid, waitpid_nohang,
+    SignalAction, SIGCONT, SIGUSR1,
+};
+
+/// 程序行为：子进程 pause，父进程 100 ms 后先发送默认忽略的 SIGCONT，子进程不应醒来；
+/// 再发送有处理函数的 SIGUSR1，子进程的处理函数执行后 pause 返回 -1，子进程正常退出。
+/// 信号在 pause 之前就已到达时 pause 立即返回。
+
+/// 正确输出：（无报错信息）
+/// Test pause OK!
+
+static mut HANDLED: usize = 0;
+
+fn func(_signum: usize) {
+    unsafe {
+        HANDLED += 1;
+    }
+    sigreturn();
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let action = SignalAction {
+        handler: func as usize,
+        ..Default::default()
+    };
+    assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(pause(), -1);
+        assert_eq!(unsafe { core::ptr::read_volatile(&HANDLED) }, 1);
+        exit(0);
+    }
+    sleep_ms(100);
+    assert_eq!(kill(pid as usize, SIGCONT), 0);
+    sleep_ms(50);
+    let mut exit_code: i32 = 1;
+    assert_eq!(waitpid_nohang(pid, &mut exit_code), 0);
+    assert_eq!(kill(pid as usize, SIGUSR1), 0);
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    // a signal pending before pause is not lost
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+        assert_eq!(pause(), -1);
+        exit(0);
+    }
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test pause OK!");
+    0
+}
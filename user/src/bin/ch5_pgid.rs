@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, fork, getpgid, killpg, setpgid, sleep_ms, waitpid, SIGKILL};
+
+/// 程序行为：子进程继承父进程的进程组；父进程把三个空转的子进程放进以第一个子进程为首的新进程组，
+/// 一次 killpg 杀死全部三个，回收到的退出码都是 -9，父进程自己不受影响。
+/// 不能加入不存在的进程组，不能移动 exec 过的子进程。
+
+/// 正确输出：（无报错信息）
+/// Test pgid OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let my_pgid = getpgid(0);
+    assert!(my_pgid >= 0);
+    let mut pids = [0isize; 3];
+    for pid in pids.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            loop {}
+        }
+        assert_eq!(getpgid(*pid as usize), my_pgid);
+    }
+    let leader = pids[0] as usize;
+    assert_eq!(setpgid(leader, 0), 0);
+    for &pid in pids[1..].iter() {
+        assert_eq!(setpgid(pid as usize, leader), 0);
+    }
+    for &pid in pids.iter() {
+        assert_eq!(getpgid(pid as usize), leader as isize);
+    }
+    assert_eq!(killpg(leader, SIGKILL), 0);
+    for &pid in pids.iter() {
+        let mut exit_code: i32 = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, -9);
+    }
+    assert_eq!(getpgid(0), my_pgid);
+    // the group is gone with its members
+    assert_eq!(killpg(leader, SIGKILL), -1);
+    assert_eq!(setpgid(0, leader), -1);
+
+    let pid = fork();
+    if pid == 0 {
+        exec("ch2b_hello_world\0", &[core::ptr::null::<u8>()]);
+        unreachable!();
+    }
+    sleep_ms(50);
+    assert_eq!(setpgid(pid as usize, 0), -1);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    println!("Test pgid OK!");
+    0
+}
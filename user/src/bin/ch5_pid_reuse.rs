@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, waitpid};
+
+/// 程序行为：依次 fork 并等待 1000 个子进程，pid 总是分配最小的空闲值，
+/// 子进程的 pid 应始终在少数几个值之间，而不是一路增长到 1000 以上。
+
+/// 正确输出：（无报错信息）
+/// Test pid reuse OK!
+
+const ROUNDS: usize = 1000;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut min = isize::MAX;
+    let mut max = 0;
+    for _ in 0..ROUNDS {
+        let pid = fork();
+        if pid == 0 {
+            exit(0);
+        }
+        assert!(pid > 0);
+        let mut exit_code = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+        min = min.min(pid);
+        max = max.max(pid);
+    }
+    println!("child pids in [{}, {}]", min, max);
+    assert!(max - min < 8);
+    println!("Test pid reuse OK!");
+    0
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, pipe, read, waitpid, write};
+
+/// 程序行为：所有写端关闭后，读端读完剩余数据再读返回 0；子进程退出时自动关闭它持有的写端；
+/// 所有读端关闭后写管道返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test pipe eof OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buffer = [0u8; 16];
+
+    // 关闭写端后，先读出剩余的数据，再读到 EOF
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(write(pipe_fd[1], b"bye"), 3);
+    assert_eq!(close(pipe_fd[1]), 0);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 3);
+    assert_eq!(&buffer[..3], b"bye");
+    assert_eq!(read(pipe_fd[0], &mut buffer), 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+    // 已关闭的描述符不能再关闭
+    assert_eq!(close(pipe_fd[0]), -1);
+
+    // 子进程退出时关闭它的写端，父进程关闭自己的写端后读到 EOF
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(close(pipe_fd[0]), 0);
+        assert_eq!(write(pipe_fd[1], b"child"), 5);
+        exit(0);
+    }
+    assert_eq!(close(pipe_fd[1]), 0);
+    assert_eq!(read(pipe_fd[0], &mut buffer), 5);
+    assert_eq!(&buffer[..5], b"child");
+    assert_eq!(read(pipe_fd[0], &mut buffer), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+
+    // 没有读端的管道不能写
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(close(pipe_fd[0]), 0);
+    assert_eq!(write(pipe_fd[1], b"lost"), -1);
+    assert_eq!(close(pipe_fd[1]), 0);
+    println!("Test pipe eof OK!");
+    0
+}
@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, fork, pipe, read, waitpid, write};
+
+/// 程序行为：父进程通过管道向 fork 出的子进程写入超过管道缓冲区大小的数据，
+/// 子进程分多次读出后逐字节校验。
+
+/// 正确输出：（无报错信息）
+/// Test pipe OK!
+
+const LEN: usize = 3000;
+
+fn byte_at(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    // 0/1/2 已被标准输入输出占用
+    assert_eq!(pipe_fd[0], 3);
+    assert_eq!(pipe_fd[1], 4);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(close(pipe_fd[1]), 0);
+        let mut buffer = [0u8; 100];
+        let mut received = 0;
+        loop {
+            let len = read(pipe_fd[0], &mut buffer);
+            assert!(len >= 0);
+            if len == 0 {
+                break;
+            }
+            for &byte in buffer.iter().take(len as usize) {
+                assert_eq!(byte, byte_at(received));
+                received += 1;
+            }
+        }
+        assert_eq!(received, LEN);
+        return 0;
+    }
+    assert_eq!(close(pipe_fd[0]), 0);
+    let mut data = [0u8; LEN];
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = byte_at(i);
+    }
+    assert_eq!(write(pipe_fd[1], &data), LEN as isize);
+    assert_eq!(close(pipe_fd[1]), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test pipe OK!");
+    0
+}
@@ -0,0 +1,14 @@
+#![no_std]
+#![no_main]
+
+extern crate user_lib;
+use user_lib::getppid;
+
+/*
+辅助测例，以父进程的 pid 作为退出码。
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    getppid() as i32
+}
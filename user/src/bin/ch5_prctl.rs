@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_name, set_name, waitpid, TASK_NAME_LEN};
+
+/// 程序行为：进程名初始为程序名；改名为 worker-3 后能读回，过长的名字被截断为 15 字节；
+/// fork 出的子进程继承进程名，并故意访问空指针触发缺页，内核的报错信息中带有 [worker-3]。
+
+/// 正确输出：
+/// [kernel] StorePageFault in application (pid <pid> [worker-3]), ...
+/// Test prctl OK!
+
+fn name_of(buf: &[u8; TASK_NAME_LEN]) -> &str {
+    let len = buf.iter().position(|&b| b == 0).unwrap();
+    core::str::from_utf8(&buf[..len]).unwrap()
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = [0u8; TASK_NAME_LEN];
+    assert_eq!(get_name(&mut buf), 0);
+    assert_eq!(name_of(&buf), "ch5_prctl");
+    assert_eq!(set_name("a-very-long-process-name\0"), 0);
+    assert_eq!(get_name(&mut buf), 0);
+    assert_eq!(name_of(&buf), "a-very-long-pro");
+    assert_eq!(set_name("worker-3\0"), 0);
+    assert_eq!(get_name(&mut buf), 0);
+    assert_eq!(name_of(&buf), "worker-3");
+    let pid = fork();
+    if pid == 0 {
+        let mut buf = [0u8; TASK_NAME_LEN];
+        assert_eq!(get_name(&mut buf), 0);
+        assert_eq!(name_of(&buf), "worker-3");
+        unsafe {
+            (0 as *mut u8).write_volatile(0);
+        }
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+    println!("Test prctl OK!");
+    0
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, exit, fork, set_priority, waitpid, TimeSpec, CLOCK_MONOTONIC};
+
+/// 程序行为：低优先级的父进程忙等一段时间后 fork 出子进程并继续忙等，
+/// 子进程的 stride 远小于父进程，应在 fork 返回时立即抢占父进程，
+/// 而不是等到父进程的时间片用完；子进程以从 fork 到开始运行的微秒数作为退出码。
+
+/// 正确输出：（无报错信息）
+/// Test preempt latency OK!
+
+fn now_us() -> usize {
+    let mut ts = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut ts), 0);
+    ts.sec * 1_000_000 + ts.nsec / 1000
+}
+
+fn busy(us: usize) {
+    let start = now_us();
+    while now_us() - start < us {}
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    set_priority(2);
+    // run for a few time slices to build up the stride
+    busy(50_000);
+    let start = now_us();
+    let pid = fork();
+    if pid == 0 {
+        exit((now_us() - start) as i32);
+    }
+    busy(30_000);
+    let mut latency = 0;
+    assert_eq!(waitpid(pid as usize, &mut latency), pid);
+    println!("child started after {} us", latency);
+    assert!(latency < 2000);
+    println!("Test preempt latency OK!");
+    0
+}
@@ -0,0 +1,127 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exec_with_env, exit, fork, getenv, mmap, munmap, prlimit, sbrk, waitpid, RLimit, RLIMIT_AS,
+    RLIM_INFINITY,
+};
+
+/*
+程序行为：子进程把自己的 RLIMIT_AS 降到 16 页，mmap 16 页成功，第 17 页与 sbrk 都返回 -1，
+munmap 一页后又能再映射一页；限制会被 fork 出的子进程继承，exec 之后仍然保留；
+同时运行的兄弟进程不受影响，可以映射 32 页。软限制不能超过硬限制，硬限制不能再调高。
+理想结果：输出 Test prlimit OK!
+*/
+
+const PAGE: usize = 4096;
+const START: usize = 0x10000000;
+const LIMIT: RLimit = RLimit {
+    rlim_cur: 16 * PAGE,
+    rlim_max: 32 * PAGE,
+};
+
+fn current_limit() -> RLimit {
+    let mut limit = RLimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(0, prlimit(0, RLIMIT_AS, None, Some(&mut limit)));
+    limit
+}
+
+fn limited() -> i32 {
+    let mut old = LIMIT;
+    assert_eq!(0, prlimit(0, RLIMIT_AS, Some(&LIMIT), Some(&mut old)));
+    assert_eq!(old.rlim_max, RLIM_INFINITY);
+    assert_eq!(current_limit(), LIMIT);
+    for i in 0..16 {
+        let addr = START + i * PAGE;
+        assert_eq!(addr as isize, mmap(addr, PAGE, 3));
+    }
+    assert_eq!(-1, mmap(START + 16 * PAGE, PAGE, 3));
+    assert_eq!(-1, sbrk(PAGE as isize));
+    assert_eq!(0, munmap(START, PAGE));
+    assert_eq!(
+        (START + 16 * PAGE) as isize,
+        mmap(START + 16 * PAGE, PAGE, 3)
+    );
+    assert_eq!(-1, mmap(START, PAGE, 3));
+    // 软限制不能超过硬限制，硬限制也不能再调高
+    let above = RLimit {
+        rlim_cur: 64 * PAGE,
+        rlim_max: 32 * PAGE,
+    };
+    assert_eq!(-1, prlimit(0, RLIMIT_AS, Some(&above), None));
+    let raised = RLimit {
+        rlim_cur: 64 * PAGE,
+        rlim_max: 64 * PAGE,
+    };
+    assert_eq!(-1, prlimit(0, RLIMIT_AS, Some(&raised), None));
+    assert_eq!(current_limit(), LIMIT);
+    // fork 出的子进程继承限制和已经映射的区域
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(current_limit(), LIMIT);
+        assert_eq!(-1, mmap(START, PAGE, 3));
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(pid, waitpid(pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    // exec 之后限制仍然保留，新程序的地址空间是空的
+    let args = [
+        "ch5_prlimit\0".as_ptr(),
+        "exec\0".as_ptr(),
+        core::ptr::null(),
+    ];
+    let envs = ["PRLIMIT_EXEC=1\0".as_ptr(), core::ptr::null::<u8>()];
+    exec_with_env("ch5_prlimit\0", &args, &envs);
+    -2
+}
+
+fn after_exec() -> i32 {
+    assert_eq!(current_limit(), LIMIT);
+    for i in 0..16 {
+        let addr = START + i * PAGE;
+        assert_eq!(addr as isize, mmap(addr, PAGE, 3));
+    }
+    assert_eq!(-1, mmap(START + 16 * PAGE, PAGE, 3));
+    0
+}
+
+fn sibling() -> i32 {
+    assert_eq!(current_limit().rlim_max, RLIM_INFINITY);
+    assert_eq!(START as isize, mmap(START, 32 * PAGE, 3));
+    unsafe {
+        ((START + 31 * PAGE) as *mut usize).write_volatile(1);
+    }
+    0
+}
+
+#[no_mangle]
+pub fn main(argc: usize, argv: &[&str]) -> i32 {
+    // 环境变量不经过 a0，即使 argc 传错也能认出 exec 之后的程序
+    if getenv("PRLIMIT_EXEC").is_some() {
+        assert!(argc == 2 && argv[1] == "exec", "exec passed {} args", argc);
+        return after_exec();
+    }
+    assert_eq!(-1, prlimit(0, RLIMIT_AS + 1, None, None));
+    let limited_pid = fork();
+    if limited_pid == 0 {
+        exit(limited());
+    }
+    let sibling_pid = fork();
+    if sibling_pid == 0 {
+        exit(sibling());
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(limited_pid, waitpid(limited_pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    assert_eq!(sibling_pid, waitpid(sibling_pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    println!("Test prlimit OK!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{getpid, sched_getquantum, sched_stat, set_priority, SchedEntry, SchedStat};
+
+/// 程序行为：设置自己的优先级后获取调度器快照，像 ps 一样打印出来，
+/// 检查第一项是自己且优先级正确，时间片与 sched_getquantum 一致；
+/// 缓冲区不足时只写入部分项，但仍返回完整的数目。
+
+/// 正确输出：（无报错信息）
+/// Test ps OK!
+
+const PRIORITY: isize = 7;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_priority(PRIORITY), PRIORITY);
+    let mut stat = SchedStat::default();
+    let mut entries = [SchedEntry::default(); 16];
+    let count = sched_stat(&mut stat, &mut entries);
+    assert!(count >= 1);
+    println!(
+        "ready {} blocked {} switches {} quantum {} us",
+        stat.ready, stat.blocked, stat.context_switches, stat.quantum_us
+    );
+    println!("  PID  PRIO  STRIDE  NAME");
+    for entry in entries.iter().take(count as usize) {
+        let len = entry
+            .name
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(entry.name.len());
+        let name = core::str::from_utf8(&entry.name[..len]).unwrap_or("?");
+        println!(
+            "{:>5} {:>5} {:>7}  {}",
+            entry.pid, entry.priority, entry.stride, name
+        );
+    }
+    assert_eq!(count as usize, stat.ready + 1);
+    assert_eq!(entries[0].pid, getpid() as usize);
+    assert!(entries[0].name.starts_with(b"ch5_ps\0"));
+    assert_eq!(entries[0].priority, PRIORITY as usize);
+    assert_eq!(stat.quantum_us, sched_getquantum() as usize);
+    assert!(stat.context_switches > 0);
+    // a buffer too small for the snapshot gets only the current task
+    let mut one = [SchedEntry::default(); 1];
+    assert!(sched_stat(&mut stat, &mut one) >= 1);
+    assert_eq!(one[0].pid, getpid() as usize);
+    println!("Test ps OK!");
+    0
+}
@@ -0,0 +1,62 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, get_time, sched_getquantum, sched_setquantum, task_stats, waitpid, SchedStats,
+};
+
+/// 程序行为：分别在默认的 10 ms 时间片和 1 ms 时间片下运行两个忙等的子进程，
+/// 子进程以被调度的次数作为退出码，1 ms 时间片下的调度次数应明显增多（约十倍）。
+/// 时间片的长度会被限制在 [1 ms, 1 s] 之间。没有修改时间片的权限时测例直接通过。
+
+/// 正确输出：（无报错信息）
+/// Test quantum OK!
+
+const BUSY_MS: isize = 200;
+
+/// Total times two busy children are scheduled while running for `BUSY_MS`
+fn busy_switches() -> i32 {
+    let mut pids = [0; 2];
+    for pid in pids.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            let start = get_time();
+            while get_time() - start < BUSY_MS {}
+            let mut stats = SchedStats::default();
+            assert_eq!(task_stats(&mut stats), 0);
+            exit(stats.times_scheduled as i32);
+        }
+    }
+    let mut total = 0;
+    for &pid in pids.iter() {
+        let mut exit_code = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        total += exit_code;
+    }
+    total
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let default = sched_getquantum();
+    assert_eq!(default, 10_000);
+    if sched_setquantum(default as usize) == -1 {
+        println!("no permission to set the quantum, skip");
+        println!("Test quantum OK!");
+        return 0;
+    }
+    assert_eq!(sched_setquantum(10), 1_000);
+    assert_eq!(sched_setquantum(10_000_000), 1_000_000);
+    assert_eq!(sched_setquantum(default as usize), default);
+    let slow = busy_switches();
+    assert_eq!(sched_setquantum(1_000), 1_000);
+    let fast = busy_switches();
+    assert_eq!(sched_setquantum(default as usize), default);
+    println!("switches: {} with 10 ms, {} with 1 ms", slow, fast);
+    assert!(fast >= slow * 5);
+    println!("Test quantum OK!");
+    0
+}
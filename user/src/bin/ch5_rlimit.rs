@@ -0,0 +1,122 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    exit, fork, mmap, munmap, prlimit, spawn, wait, waitpid, RLimit, RLIMIT_NPROC, RLIMIT_STACK,
+    RLIM_INFINITY,
+};
+
+/// 程序行为：一个子进程把 RLIMIT_NPROC 降到 2，fork 两个子进程后第三次 fork 和 spawn 都返回 -1，
+/// 回收一个之后又能 fork；另一个子进程把 RLIMIT_STACK 降到 64 KiB，栈上 32 KiB 的数组仍能用，
+/// 128 KiB 的数组则使其因栈溢出退出（-11）。受限时 mmap 照常可用，fork 出的子进程继承限制。
+
+/// 正确输出：（无报错信息）
+/// Test rlimit OK!
+
+const PAGE: usize = 4096;
+const START: usize = 0x10000000;
+
+fn set_limit(resource: usize, rlim_cur: usize) {
+    let limit = RLimit {
+        rlim_cur,
+        rlim_max: RLIM_INFINITY,
+    };
+    assert_eq!(0, prlimit(0, resource, Some(&limit), None));
+}
+
+fn current_limit(resource: usize) -> RLimit {
+    let mut limit = RLimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(0, prlimit(0, resource, None, Some(&mut limit)));
+    limit
+}
+
+fn fork_exit(code: i32) -> isize {
+    let pid = fork();
+    if pid == 0 {
+        exit(code);
+    }
+    pid
+}
+
+fn nproc_limited() -> i32 {
+    set_limit(RLIMIT_NPROC, 2);
+    let first = fork_exit(0);
+    assert!(first > 0);
+    assert!(fork_exit(0) > 0);
+    // 退出但未被回收的子进程也计入
+    assert_eq!(fork_exit(0), -1);
+    assert_eq!(spawn("ch2b_hello_world\0"), -1);
+    // 其他资源不受影响
+    assert_eq!(START as isize, mmap(START, PAGE, 3));
+    assert_eq!(0, munmap(START, PAGE));
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(first as usize, &mut exit_code), first);
+    let pid = fork();
+    if pid == 0 {
+        // 子进程继承限制，它自己还没有子进程
+        assert_eq!(current_limit(RLIMIT_NPROC).rlim_cur, 2);
+        exit(0);
+    }
+    assert!(pid > 0);
+    assert!(wait(&mut exit_code) > 0);
+    assert!(wait(&mut exit_code) > 0);
+    assert!(wait(&mut exit_code) < 0);
+    0
+}
+
+#[inline(never)]
+fn fill_stack<const LEN: usize>() -> u8 {
+    let mut buf = [0u8; LEN];
+    for (i, x) in buf.iter_mut().enumerate() {
+        unsafe { core::ptr::write_volatile(x, i as u8) };
+    }
+    unsafe { core::ptr::read_volatile(&buf[LEN - 1]) }
+}
+
+fn stack_limited() -> i32 {
+    set_limit(RLIMIT_STACK, 64 * 1024);
+    assert_eq!(fill_stack::<{ 32 * 1024 }>(), 0xff);
+    let pid = fork();
+    if pid == 0 {
+        fill_stack::<{ 128 * 1024 }>();
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -11);
+    assert_eq!(START as isize, mmap(START, PAGE, 3));
+    0
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(current_limit(RLIMIT_NPROC).rlim_cur, RLIM_INFINITY);
+    // 栈的硬限制就是为它保留的区域大小，不能调高
+    let stack = current_limit(RLIMIT_STACK);
+    let raised = RLimit {
+        rlim_cur: stack.rlim_max * 2,
+        rlim_max: stack.rlim_max * 2,
+    };
+    assert_eq!(-1, prlimit(0, RLIMIT_STACK, Some(&raised), None));
+    let nproc_pid = fork();
+    if nproc_pid == 0 {
+        exit(nproc_limited());
+    }
+    let stack_pid = fork();
+    if stack_pid == 0 {
+        exit(stack_limited());
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(nproc_pid, waitpid(nproc_pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    assert_eq!(stack_pid, waitpid(stack_pid as usize, &mut exit_code));
+    assert_eq!(exit_code, 0);
+    println!("Test rlimit OK!");
+    0
+}
@@ -0,0 +1,62 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, munmap, sbrk};
+
+/// 程序行为：sbrk 扩展 1 MiB 堆并写遍每一页，再缩回；不能缩到初始 break 以下，
+/// 也不能扩展进已经 mmap 的区域；反复扩展、缩回不会耗尽物理页帧。
+/// munmap 不能移除堆和代码段，之后 sbrk 照常工作。
+
+/// 正确输出：（无报错信息）
+/// Test sbrk OK!
+
+const SIZE: isize = 1 << 20;
+const PAGE_SIZE: usize = 4096;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let bottom = sbrk(0);
+    assert!(bottom > 0);
+    assert_eq!(sbrk(-1), -1);
+    assert_eq!(sbrk(SIZE), bottom);
+    for addr in (bottom as usize..(bottom + SIZE) as usize).step_by(PAGE_SIZE) {
+        unsafe {
+            *(addr as *mut usize) = addr;
+        }
+    }
+    for addr in (bottom as usize..(bottom + SIZE) as usize).step_by(PAGE_SIZE) {
+        unsafe {
+            assert_eq!(*(addr as *const usize), addr);
+        }
+    }
+    assert_eq!(sbrk(-SIZE), bottom + SIZE);
+    assert_eq!(sbrk(0), bottom);
+    // 1 MiB is 256 frames, a leak would run out of memory long before this finishes
+    for _ in 0..200 {
+        assert_eq!(sbrk(SIZE), bottom);
+        assert_eq!(sbrk(-SIZE), bottom + SIZE);
+    }
+    // the heap cannot grow into an mmap region
+    let next_page = (bottom as usize + PAGE_SIZE - 1) / PAGE_SIZE * PAGE_SIZE + PAGE_SIZE;
+    assert_eq!(mmap(next_page, PAGE_SIZE, 3), next_page as isize);
+    assert_eq!(sbrk(SIZE), -1);
+    assert_eq!(sbrk(0), bottom);
+    assert_eq!(munmap(next_page, PAGE_SIZE), 0);
+    // only the areas created by mmap can be unmapped
+    let heap_page = (bottom as usize + PAGE_SIZE - 1) / PAGE_SIZE * PAGE_SIZE;
+    assert_eq!(sbrk(SIZE), bottom);
+    assert_eq!(munmap(heap_page, PAGE_SIZE), -1);
+    let code_page = main as usize / PAGE_SIZE * PAGE_SIZE;
+    assert_eq!(munmap(code_page, PAGE_SIZE), -1);
+    unsafe {
+        *(heap_page as *mut usize) = heap_page;
+    }
+    assert_eq!(sbrk(-SIZE), bottom + SIZE);
+    assert_eq!(sbrk(PAGE_SIZE as isize), bottom);
+    assert_eq!(sbrk(-(PAGE_SIZE as isize)), bottom + PAGE_SIZE as isize);
+    println!("Test sbrk OK!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+use user_lib::{fork, set_priority, wait};
+
+/// 程序行为：检查合法与非法优先级的返回值，fork 出的子进程可以继续修改继承来的优先级。
+
+/// 正确输出：（无报错信息）
+/// Test set_priority boundary OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    assert_eq!(set_priority(2), 2);
+    assert_eq!(set_priority(10), 10);
+    assert_eq!(set_priority(isize::MAX), isize::MAX);
+    assert_eq!(set_priority(1), -1);
+    assert_eq!(set_priority(-1), -1);
+    assert_eq!(set_priority(isize::MIN), -1);
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(set_priority(3), 3);
+        assert_eq!(set_priority(0), -1);
+        return 0;
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(wait(&mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test set_priority boundary OK!");
+    0
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, shmat, shmdt, shmget, waitpid, IPC_PRIVATE};
+
+/// 程序行为：父进程创建并映射共享内存段后写入数据，fork 出的子进程用同一个 key 找到该段并再映射一次，
+/// 两处映射都能看到父进程写入的数据，子进程写入的数据父进程也能看到（共享而不是写时复制）。
+/// 创建者退出且所有映射都解除后，共享内存段被释放，用原 key 再也找不到。
+
+/// 正确输出：（无报错信息）
+/// Test shm OK!
+
+const KEY: usize = 0x5348;
+const FREED_KEY: usize = 0x5349;
+const PAGE_SIZE: usize = 4096;
+const SIZE: usize = 2 * PAGE_SIZE;
+
+fn words(addr: usize) -> &'static mut [usize] {
+    unsafe { core::slice::from_raw_parts_mut(addr as *mut usize, SIZE / 8) }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let shmid = shmget(KEY, SIZE);
+    assert!(shmid >= 0);
+    // 再次获取得到同一个段，段不够大时失败，空的段不能创建
+    assert_eq!(shmget(KEY, 0), shmid);
+    assert_eq!(shmget(KEY, SIZE + 1), -1);
+    assert_eq!(shmget(IPC_PRIVATE, 0), -1);
+    let addr = shmat(shmid as usize, 0);
+    assert!(addr > 0);
+    let addr = addr as usize;
+    for (i, word) in words(addr).iter_mut().enumerate() {
+        *word = i * 3;
+    }
+
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(shmget(KEY, 0), shmid);
+        let child_addr = shmat(shmid as usize, 0);
+        assert!(child_addr > 0 && child_addr as usize != addr);
+        let child_addr = child_addr as usize;
+        for (i, word) in words(child_addr).iter().enumerate() {
+            assert_eq!(*word, i * 3);
+        }
+        // fork 继承的映射与新的映射是同一组页帧
+        words(addr)[0] = 0xdead;
+        assert_eq!(words(child_addr)[0], 0xdead);
+        words(child_addr)[SIZE / 8 - 1] = 0xbeef;
+        assert_eq!(shmdt(child_addr), 0);
+        assert_eq!(shmdt(child_addr), -1);
+        exit(0);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(words(addr)[0], 0xdead);
+    assert_eq!(words(addr)[SIZE / 8 - 1], 0xbeef);
+    assert_eq!(shmdt(addr + PAGE_SIZE), -1);
+    assert_eq!(shmdt(addr), 0);
+
+    // 子进程创建的段在它退出后释放
+    let pid = fork();
+    if pid == 0 {
+        let shmid = shmget(FREED_KEY, PAGE_SIZE);
+        assert!(shmid >= 0);
+        assert!(shmat(shmid as usize, 0) > 0);
+        exit(0);
+    }
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(shmget(FREED_KEY, 0), -1);
+    println!("Test shm OK!");
+    0
+}
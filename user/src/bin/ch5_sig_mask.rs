@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    getpid, kill, sigaction, sigprocmask, sigreturn, yield_, SignalAction, SignalFlags, SIGUSR1,
+    SIGUSR2,
+};
+
+/// 程序行为：屏蔽信号 10 后向自己发送该信号，多次 yield 后处理函数仍未执行；
+/// 解除屏蔽后处理函数恰好执行一次。SIGKILL 不能被屏蔽。
+/// 处理函数运行期间屏蔽信号本身和 SignalAction 的 mask，sigreturn 后恢复原来的屏蔽字。
+
+/// 正确输出：（无报错信息）
+/// Test sig_mask OK!
+
+static mut HANDLED: usize = 0;
+
+fn func(_signum: usize) {
+    unsafe {
+        HANDLED += 1;
+    }
+    sigreturn();
+}
+
+static mut MASK_IN_HANDLER: isize = -1;
+
+fn func2(_signum: usize) {
+    // sigprocmask 返回旧的屏蔽字，读出后立即恢复
+    let mask = sigprocmask(SignalFlags::empty());
+    sigprocmask(SignalFlags::from_bits_truncate(mask as u32));
+    unsafe {
+        MASK_IN_HANDLER = mask;
+    }
+    sigreturn();
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let action = SignalAction {
+        handler: func as usize,
+        ..Default::default()
+    };
+    assert_eq!(sigaction(SIGUSR1, Some(&action), None), 0);
+    assert_eq!(sigprocmask(SignalFlags::SIGUSR1 | SignalFlags::SIGKILL), 0);
+    assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+    for _ in 0..10 {
+        yield_();
+    }
+    assert_eq!(unsafe { HANDLED }, 0);
+    // SIGKILL has been dropped from the mask
+    assert_eq!(
+        sigprocmask(SignalFlags::empty()),
+        SignalFlags::SIGUSR1.bits() as isize
+    );
+    assert_eq!(unsafe { HANDLED }, 1);
+    for _ in 0..10 {
+        yield_();
+    }
+    assert_eq!(unsafe { HANDLED }, 1);
+
+    let action = SignalAction {
+        handler: func2 as usize,
+        mask: SignalFlags::SIGUSR1,
+    };
+    assert_eq!(sigaction(SIGUSR2, Some(&action), None), 0);
+    assert_eq!(kill(getpid() as usize, SIGUSR2), 0);
+    yield_();
+    assert_eq!(
+        unsafe { MASK_IN_HANDLER },
+        (SignalFlags::SIGUSR1 | SignalFlags::SIGUSR2).bits() as isize
+    );
+    assert_eq!(sigprocmask(SignalFlags::empty()), 0);
+    println!("Test sig_mask OK!");
+    0
+}
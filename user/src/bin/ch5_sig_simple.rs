@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{getpid, kill, sigaction, sigreturn, SignalAction, SIGKILL, SIGSTOP, SIGUSR1};
+
+/// 程序行为：为信号 10 注册处理函数，用 kill 向自己发送该信号，
+/// 检查处理函数执行了一次，且 sigreturn 后从 kill 之后继续执行；SIGKILL 和 SIGSTOP 不能注册处理函数。
+
+/// 正确输出：（无报错信息）
+/// Test sig_simple OK!
+
+static mut HANDLED: usize = 0;
+
+fn func(signum: usize) {
+    unsafe {
+        HANDLED += signum;
+    }
+    sigreturn();
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let action = SignalAction {
+        handler: func as usize,
+        ..Default::default()
+    };
+    assert_eq!(sigaction(SIGKILL, Some(&action), None), -1);
+    assert_eq!(sigaction(SIGSTOP, Some(&action), None), -1);
+    let mut old = SignalAction::default();
+    assert_eq!(sigaction(SIGUSR1, Some(&action), Some(&mut old)), 0);
+    assert_eq!(old.handler, 0);
+    assert_eq!(kill(getpid() as usize, SIGUSR1), 0);
+    // the handler runs right before kill returns to user space
+    assert_eq!(unsafe { HANDLED }, SIGUSR1);
+    assert_eq!(sigaction(SIGUSR1, None, Some(&mut old)), 0);
+    assert_eq!(old.handler, func as usize);
+    println!("Test sig_simple OK!");
+    0
+}
@@ -0,0 +1,26 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{get_time, sleep_blocking};
+
+/// 程序行为：调用阻塞的 sys_sleep 睡眠 500ms，检查实际经过的时间不少于 500ms，
+/// 且不超过请求时长的两倍（宿主机负载高时 QEMU 的时钟会变慢，上限放得很宽）。
+
+/// 正确输出：（无报错信息）
+/// Test sleep_blocking OK!
+
+const SLEEP_MS: usize = 500;
+
+#[no_mangle]
+fn main() -> i32 {
+    let start = get_time();
+    sleep_blocking(SLEEP_MS);
+    let elapsed = (get_time() - start) as usize;
+    assert!(elapsed >= SLEEP_MS, "woke up too early: {}ms", elapsed);
+    assert!(elapsed <= SLEEP_MS * 2, "woke up too late: {}ms", elapsed);
+    println!("Test sleep_blocking OK!");
+    0
+}
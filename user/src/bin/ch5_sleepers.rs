@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, exit, fork, get_time, nanosleep, wait, TimeSpec, CLOCK_MONOTONIC};
+
+/// 程序行为：fork 48 个子进程（连同其他进程不超过 NPROC），各自 nanosleep 5 ~ 300 ms 之间的随机时长，
+/// 每个子进程都应在截止时间之后的一个时钟周期（10 ms）内醒来，不早于截止时间。
+/// 子进程以迟到的微秒数作为退出码。需要单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test sleepers OK!
+
+const SLEEPERS: usize = 48;
+const TICK_US: i32 = 10_000;
+
+fn now_ns() -> usize {
+    let mut ts = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut ts), 0);
+    ts.sec * 1_000_000_000 + ts.nsec
+}
+
+fn sleeper(ms: usize) -> ! {
+    let req = TimeSpec {
+        sec: ms / 1000,
+        nsec: ms % 1000 * 1_000_000,
+    };
+    let start = now_ns();
+    assert_eq!(nanosleep(&req, None), 0);
+    let slept = now_ns() - start;
+    let deadline = ms * 1_000_000;
+    if slept < deadline {
+        println!("woke up {} ns early from {} ms", deadline - slept, ms);
+        exit(-1);
+    }
+    exit(((slept - deadline) / 1000).min(i32::MAX as usize) as i32);
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut seed = get_time() as usize;
+    for _ in 0..SLEEPERS {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let ms = 5 + (seed >> 33) % 296;
+        let pid = fork();
+        assert!(pid >= 0);
+        if pid == 0 {
+            sleeper(ms);
+        }
+    }
+    let mut max_late_us = 0;
+    for _ in 0..SLEEPERS {
+        let mut exit_code: i32 = 0;
+        assert!(wait(&mut exit_code) > 0);
+        assert!(
+            (0..=TICK_US).contains(&exit_code),
+            "a sleeper exited with {}",
+            exit_code
+        );
+        max_late_us = max_late_us.max(exit_code);
+    }
+    let mut exit_code: i32 = 0;
+    assert!(wait(&mut exit_code) < 0);
+    println!(
+        "{} sleepers woke up at most {} us late",
+        SLEEPERS, max_late_us
+    );
+    println!("Test sleepers OK!");
+    0
+}
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, sysinfo, waitpid, SysInfo};
+
+/*
+程序行为：先让一个子进程单独完成一份固定的计算，再让两个子进程同时各完成一份，比较两次的用时。
+多于一个 hart 时两个子进程并行，用时应与一个相近；只有一个 hart 时只输出用时。
+需要单独运行。
+理想结果：输出 Test smp OK!
+*/
+
+const ROUNDS: usize = 4_000_000;
+
+fn work() -> usize {
+    let mut x: usize = 0;
+    for i in 0..ROUNDS {
+        x = unsafe { core::ptr::read_volatile(&i) } + x;
+    }
+    x
+}
+
+/// 同时运行 n 个子进程，返回全部退出所用的毫秒数
+fn run(n: usize) -> isize {
+    let start = get_time();
+    let mut pids = [0; 2];
+    for pid in pids.iter_mut().take(n) {
+        *pid = fork();
+        if *pid == 0 {
+            assert!(work() > 0);
+            exit(0);
+        }
+    }
+    for &pid in pids.iter().take(n) {
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    get_time() - start
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    let one = run(1);
+    let two = run(2);
+    println!(
+        "{} harts: one task {} ms, two tasks {} ms",
+        info.harts, one, two
+    );
+    if info.harts > 1 {
+        assert!(two * 2 < one * 3);
+    }
+    println!("Test smp OK!");
+    0
+}
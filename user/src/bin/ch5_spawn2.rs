@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{spawn, waitpid};
+const MAX_CHILD: usize = 50;
+
+/*
+程序行为：循环 spawn MAX_CHILD 次，每次 spawn 后立即 waitpid 等待该子进程，检查子进程已挂到父进程的 children 上，
+且反复 spawn 不会耗尽物理页帧。
+理想结果：输出 Test spawn2 OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for _ in 0..MAX_CHILD {
+        let cpid = spawn("ch5_exit0\0");
+        assert!(cpid > 0, "child pid invalid");
+        let mut exit_code: i32 = -1;
+        assert_eq!(waitpid(cpid as usize, &mut exit_code), cpid);
+        assert_eq!(exit_code, 66778, "error exit code {}", exit_code);
+    }
+    println!("Test spawn2 OK!");
+    0
+}
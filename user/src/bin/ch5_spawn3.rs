@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{mmap, spawn_with_args, waitpid};
+
+/*
+程序行为：spawn 时传入参数，其中一个参数跨越页边界，子进程检查参数正确后返回 0；
+参数总长度超过 4 KiB 时 spawn 返回 -1。
+理想结果：输出 Test spawn3 OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start: usize = 0x10000000;
+    assert_eq!(start as isize, mmap(start, 4096 * 3, 3));
+    // "cross" starts 3 bytes before the end of the first page
+    let cross = start + 4096 - 3;
+    for (i, c) in b"cross\0".iter().enumerate() {
+        unsafe {
+            *((cross + i) as *mut u8) = *c;
+        }
+    }
+    let args = [
+        "ch5_argv\0".as_ptr(),
+        "hello\0".as_ptr(),
+        cross as *const u8,
+        core::ptr::null::<u8>(),
+    ];
+    let cpid = spawn_with_args("ch5_argv\0", &args);
+    assert!(cpid > 0, "child pid invalid");
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(cpid as usize, &mut exit_code), cpid);
+    assert_eq!(exit_code, 0, "child got wrong args");
+    // a single argument longer than 4 KiB
+    let long = start + 4096 + 8;
+    for i in long..(start + 4096 * 3 - 1) {
+        unsafe {
+            *(i as *mut u8) = b'a';
+        }
+    }
+    unsafe {
+        *((start + 4096 * 3 - 1) as *mut u8) = 0;
+    }
+    let args = [long as *const u8, core::ptr::null::<u8>()];
+    assert_eq!(spawn_with_args("ch5_argv\0", &args), -1);
+    println!("Test spawn3 OK!");
+    0
+}
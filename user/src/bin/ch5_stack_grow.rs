@@ -0,0 +1,47 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mprotect, munmap, waitpid};
+
+/// 程序行为：在栈上分配 128 KiB 的数组并写满，用户栈应自动向下增长。
+/// 之后 fork 的子进程应看到同样的内容，且 munmap、mprotect 不能在栈上打洞。
+
+/// 正确输出：（无报错信息）
+/// Test stack grow OK!
+
+const LEN: usize = 128 * 1024;
+
+#[inline(never)]
+fn fill_and_check() {
+    let mut buf = [0u8; LEN];
+    for (i, x) in buf.iter_mut().enumerate() {
+        unsafe { core::ptr::write_volatile(x, i as u8) };
+    }
+    // 数组最低处所在的页
+    let low = (buf.as_ptr() as usize + 4095) & !4095;
+    assert_eq!(munmap(low, 4096), -1);
+    assert_eq!(mprotect(low, 4096, 1), -1);
+    let pid = fork();
+    if pid == 0 {
+        for (i, x) in buf.iter().enumerate() {
+            assert_eq!(unsafe { core::ptr::read_volatile(x) }, i as u8);
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    for (i, x) in buf.iter().enumerate() {
+        assert_eq!(unsafe { core::ptr::read_volatile(x) }, i as u8);
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    fill_and_check();
+    println!("Test stack grow OK!");
+    0
+}
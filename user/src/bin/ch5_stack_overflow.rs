@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, waitpid};
+
+/// 程序行为：子进程无限递归直到栈溢出到用户栈下方的保护页，
+/// 内核应杀死子进程，父进程回收到的退出码为 -11。
+
+/// 正确输出：（内核输出 user stack overflow 的信息）
+/// Test stack overflow OK!
+
+fn recurse(depth: usize) -> usize {
+    // 栈远在此之前就会溢出
+    if depth == usize::MAX {
+        return 0;
+    }
+    let mut frame = [0u8; 256];
+    unsafe {
+        core::ptr::write_volatile(&mut frame[0], depth as u8);
+    }
+    let sum = recurse(depth + 1);
+    sum + unsafe { core::ptr::read_volatile(&frame[0]) } as usize
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let sum = recurse(0);
+        println!("unreachable {}", sum);
+        exit(0);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -11);
+    println!("Test stack overflow OK!");
+    0
+}
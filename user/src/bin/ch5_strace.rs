@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, exit, fork, getpid, getppid, pipe, read, strace, waitpid, write};
+
+/// 程序行为：父进程对子进程开启 strace 后经管道通知子进程，子进程依次调用 getppid、fork、waitpid，
+/// 孙进程继承跟踪后调用 getpid 并以 7 退出，最后子进程关闭自己的跟踪并以 3 退出。
+/// 内核应按顺序输出以下各行（C、G 为子进程和孙进程的 pid，P 为本进程的 pid，参数略）：
+/// [pid C] getppid(...) = P
+/// [pid C] fork(...) = G
+/// [pid G] getpid(...) = G
+/// [pid G] exit(0x7, ...) = ?
+/// [pid C] waitpid(...) = G
+/// [pid C] strace(...) = 0
+/// 之后不再有子进程的行。不是本进程或其子进程的 pid 不能开启跟踪。
+
+/// 正确输出：（无报错信息）
+/// Test strace OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let pid = fork();
+    if pid == 0 {
+        close(pipe_fd[1]);
+        let mut buffer = [0u8; 1];
+        assert_eq!(read(pipe_fd[0], &mut buffer), 1);
+        let ppid = getppid();
+        let child = fork();
+        if child == 0 {
+            getpid();
+            exit(7);
+        }
+        let mut exit_code: i32 = 0;
+        assert_eq!(waitpid(child as usize, &mut exit_code), child);
+        assert_eq!(exit_code, 7);
+        assert_eq!(strace(getpid() as usize, false), 0);
+        assert!(ppid > 0);
+        exit(3);
+    }
+    close(pipe_fd[0]);
+    assert_eq!(strace(getppid() as usize, true), -1);
+    assert_eq!(strace(pid as usize, true), 0);
+    assert_eq!(write(pipe_fd[1], b"x"), 1);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 3);
+    println!("Test strace OK!");
+    0
+}
@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, sched_getquantum, sleep_ms, task_stats, waitpid, SchedStats};
+
+/// 程序行为：两个优先级相同的子进程在同一段时间内运行，一个每次用掉大半个时间片后睡眠 1 ms，
+/// 另一个一直忙等。阻塞时也要按用掉的时间片累加 stride，否则睡眠的进程 stride 一直最小，
+/// 每次醒来都抢占忙等的进程。子进程以运行时间（ms）作为退出码，两者都应得到至少三分之一。
+/// 需要单独运行。
+
+/// 正确输出：（无报错信息）
+/// Test stride block OK!
+
+const RUN_MS: isize = 1000;
+
+fn running_ms() -> i32 {
+    let mut stats = SchedStats::default();
+    assert_eq!(task_stats(&mut stats), 0);
+    (stats.total_running_us / 1000) as i32
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let busy_ms = sched_getquantum() / 1000 * 8 / 10;
+    let deadline = get_time() + RUN_MS;
+    let blocker = fork();
+    if blocker == 0 {
+        while get_time() < deadline {
+            let start = get_time();
+            while get_time() - start < busy_ms {}
+            sleep_ms(1);
+        }
+        exit(running_ms());
+    }
+    let spinner = fork();
+    if spinner == 0 {
+        while get_time() < deadline {}
+        exit(running_ms());
+    }
+    let mut running = [0; 2];
+    assert_eq!(waitpid(blocker as usize, &mut running[0]), blocker);
+    assert_eq!(waitpid(spinner as usize, &mut running[1]), spinner);
+    println!(
+        "blocker ran {} ms, spinner ran {} ms",
+        running[0], running[1]
+    );
+    let total = running[0] + running[1];
+    assert!(running[0] * 3 >= total && running[1] * 3 >= total);
+    println!("Test stride block OK!");
+    0
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, exit, fork, waitpid, TimeSpec, CLOCK_MONOTONIC};
+
+/// 程序行为：父进程连续 fork 出若干 stride 相同的子进程后阻塞等待，
+/// 子进程以首次运行时距父进程开始的微秒数作为退出码，
+/// stride 相同的子进程应按入队顺序（FIFO）运行，退出码随 fork 顺序单调不减。
+
+/// 正确输出：（无报错信息）
+/// Test stride fifo OK!
+
+const NUM: usize = 8;
+
+fn now_us() -> usize {
+    let mut ts = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut ts), 0);
+    ts.sec * 1_000_000 + ts.nsec / 1000
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = now_us();
+    let mut pids = [0; NUM];
+    for pid in pids.iter_mut() {
+        *pid = fork();
+        if *pid == 0 {
+            exit((now_us() - start) as i32);
+        }
+    }
+    let mut last = 0;
+    for &pid in pids.iter() {
+        let mut exit_code = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert!(last <= exit_code);
+        last = exit_code;
+    }
+    println!("Test stride fifo OK!");
+    0
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, set_priority, task_stats, waitpid, SchedStats};
+
+/*
+程序行为：fork 出优先级分别为 5 和 10 的两个子进程，在同一段时间内空转，
+子进程以运行时间（ms）作为退出码，两者之比应接近优先级之比。需要单独运行。
+理想结果：输出 Test stride stats OK!
+*/
+
+const PRIORITIES: [isize; 2] = [5, 10];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut stats = SchedStats::default();
+    assert_eq!(task_stats(&mut stats), 0);
+    assert!(stats.times_scheduled > 0);
+    let deadline = get_time() + 1000;
+    let mut pids = [0; 2];
+    for (i, &prio) in PRIORITIES.iter().enumerate() {
+        let pid = fork();
+        if pid == 0 {
+            let mut stats = SchedStats::default();
+            task_stats(&mut stats);
+            // fork 之后的统计从零开始
+            assert!(stats.times_scheduled <= 1);
+            set_priority(prio);
+            while get_time() < deadline {}
+            task_stats(&mut stats);
+            println!(
+                "priority {}: scheduled {} times, running {} us, waiting {} us",
+                prio, stats.times_scheduled, stats.total_running_us, stats.total_ready_wait_us
+            );
+            exit((stats.total_running_us / 1000) as i32);
+        }
+        pids[i] = pid;
+    }
+    let mut running = [0; 2];
+    for i in 0..2 {
+        assert_eq!(waitpid(pids[i] as usize, &mut running[i]), pids[i]);
+    }
+    // 比值在 1.5 到 2.5 之间
+    assert!(
+        running[1] * 2 > running[0] * 3 && running[1] * 2 < running[0] * 5,
+        "running time {} ms vs {} ms",
+        running[0],
+        running[1]
+    );
+    println!("Test stride stats OK!");
+    0
+}
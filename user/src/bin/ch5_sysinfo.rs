@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, munmap, sleep, sysinfo, waitpid, SysInfo};
+
+/// 程序行为：映射并逐页写入 100 页后，sysinfo 报告的空闲物理页帧减少约 100，解除映射后恢复；
+/// 子进程退出后、被回收前计为僵尸进程。
+
+/// 正确输出：（无报错信息）
+/// Test sysinfo OK!
+
+const PAGES: usize = 100;
+const PAGE_SIZE: usize = 4096;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut info = SysInfo::default();
+    assert_eq!(sysinfo(&mut info), 0);
+    assert!(info.free_frames <= info.total_frames);
+    assert!(info.free_heap <= info.total_heap);
+    assert!(info.peak_heap >= info.total_heap - info.free_heap);
+    assert!(info.heap_allocations > 0);
+    assert!(info.procs >= 1);
+    assert!(info.harts >= 1);
+    let before = info;
+
+    let start: usize = 0x20000000;
+    assert_eq!(mmap(start, PAGES * PAGE_SIZE, 3), start as isize);
+    for i in 0..PAGES {
+        unsafe {
+            ((start + i * PAGE_SIZE) as *mut usize).write_volatile(i);
+        }
+    }
+    assert_eq!(sysinfo(&mut info), 0);
+    let used = before.free_frames - info.free_frames;
+    // 数据页之外还会分配少量页表页
+    assert!((PAGES..PAGES + 4).contains(&used));
+    assert_eq!(munmap(start, PAGES * PAGE_SIZE), 0);
+    assert_eq!(sysinfo(&mut info), 0);
+    assert!(before.free_frames - info.free_frames < 4);
+
+    let pid = fork();
+    if pid == 0 {
+        exit(0);
+    }
+    sleep(10);
+    assert_eq!(sysinfo(&mut info), 0);
+    assert_eq!(info.zombies, before.zombies + 1);
+    assert_eq!(info.procs, before.procs + 1);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(sysinfo(&mut info), 0);
+    assert_eq!(info.zombies, before.zombies);
+    assert_eq!(info.procs, before.procs);
+    assert!(info.uptime >= before.uptime);
+    println!("Test sysinfo OK!");
+    0
+}
@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{clock_gettime, task_stats, yield_, SchedStats, TimeSpec, CLOCK_MONOTONIC};
+
+/*
+程序行为：1000 次 yield 前后读取的 CLOCK_MONOTONIC 纳秒时间不会倒退；
+之后单独在用户态空转，默认时间片下每个时钟中断都会让本进程重新被调度一次，
+数出 100 次调度所用的时间应为 1.00 ± 0.01 秒，即时钟中断的间隔没有累积误差。
+需要单独运行。
+理想结果：输出 Test timer ticks OK!
+*/
+
+const TICKS: usize = 100;
+
+fn now_ns() -> usize {
+    let mut ts = TimeSpec::default();
+    assert_eq!(clock_gettime(CLOCK_MONOTONIC, &mut ts), 0);
+    ts.sec * 1_000_000_000 + ts.nsec
+}
+
+fn times_scheduled() -> usize {
+    let mut stats = SchedStats::default();
+    assert_eq!(task_stats(&mut stats), 0);
+    stats.times_scheduled
+}
+
+/// 空转到本进程再被调度一次，返回被调度的总次数
+fn wait_for_tick(last: usize) -> usize {
+    loop {
+        let scheduled = times_scheduled();
+        if scheduled != last {
+            return scheduled;
+        }
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut last = now_ns();
+    for _ in 0..1000 {
+        yield_();
+        let now = now_ns();
+        assert!(now >= last, "monotonic clock goes backwards");
+        last = now;
+    }
+
+    let mut scheduled = wait_for_tick(times_scheduled());
+    let start = now_ns();
+    for _ in 0..TICKS {
+        scheduled = wait_for_tick(scheduled);
+    }
+    let elapsed = now_ns() - start;
+    println!("{} ticks in {} ns", TICKS, elapsed);
+    assert!((990_000_000..=1_010_000_000).contains(&elapsed));
+    println!("Test timer ticks OK!");
+    0
+}
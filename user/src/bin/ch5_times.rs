@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, times, waitpid, yield_, Tms};
+
+/*
+程序行为：先在用户态空转约 100 ms，再反复 yield 约 100 ms，
+前者应主要计入用户态时间，后者主要计入内核态时间；回收的子进程的时间计入 cutime。
+需要单独运行。
+理想结果：输出 Test times OK!
+*/
+
+/// 在用户态空转，每隔一段时间才用系统调用看一次时间
+fn spin(ms: isize) {
+    let start = get_time();
+    let mut x: usize = 0;
+    while get_time() < start + ms {
+        for i in 0..10000 {
+            x = unsafe { core::ptr::read_volatile(&i) } + x;
+        }
+    }
+    assert!(x > 0);
+}
+
+fn hammer_yield(ms: isize) {
+    let start = get_time();
+    while get_time() < start + ms {
+        yield_();
+    }
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut t0 = Tms::default();
+    assert!(times(&mut t0) >= 0);
+    spin(100);
+    let mut t1 = Tms::default();
+    times(&mut t1);
+    let (utime, stime) = (t1.utime - t0.utime, t1.stime - t0.stime);
+    println!("spin: utime = {}, stime = {}", utime, stime);
+    assert!((5..=15).contains(&utime) && stime < utime);
+    hammer_yield(100);
+    let mut t2 = Tms::default();
+    times(&mut t2);
+    let (utime, stime) = (t2.utime - t1.utime, t2.stime - t1.stime);
+    println!("yield: utime = {}, stime = {}", utime, stime);
+    assert!((3..=15).contains(&stime) && utime < stime);
+    assert_eq!(t2.cutime, 0);
+    let pid = fork();
+    if pid == 0 {
+        spin(50);
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    let mut t3 = Tms::default();
+    times(&mut t3);
+    println!("child: cutime = {}, cstime = {}", t3.cutime, t3.cstime);
+    assert!(t3.cutime >= 2);
+    println!("Test times OK!");
+    0
+}
@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{uname, utsname_str, Utsname};
+
+/// 程序行为：uname 返回内核的名字、版本和机器类型，机器类型为 riscv64；
+/// 跨页的结构体也能被完整写入，非法地址返回 -1。
+
+/// 正确输出：（无报错信息）
+/// Test uname OK!
+
+static mut PAGES: [u8; 8192] = [0; 8192];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut buf = Utsname::default();
+    assert_eq!(uname(&mut buf), 0);
+    println!(
+        "{} {} {} {} {}",
+        utsname_str(&buf.sysname),
+        utsname_str(&buf.nodename),
+        utsname_str(&buf.release),
+        utsname_str(&buf.version),
+        utsname_str(&buf.machine)
+    );
+    assert_eq!(utsname_str(&buf.machine), "riscv64");
+    assert_eq!(utsname_str(&buf.sysname), "rCore-lab");
+
+    // the struct crosses a page boundary in the middle of a field
+    let pages = unsafe { &mut PAGES };
+    let boundary = 4096 - pages.as_ptr() as usize % 4096;
+    let start = if boundary >= 100 {
+        boundary - 100
+    } else {
+        boundary + 4096 - 100
+    };
+    let crossing = unsafe { &mut *(pages[start..].as_mut_ptr() as *mut Utsname) };
+    assert_eq!(uname(crossing), 0);
+    assert_eq!(utsname_str(&crossing.machine), "riscv64");
+    assert_eq!(utsname_str(&crossing.version), utsname_str(&buf.version));
+
+    let bad = unsafe { &mut *(0x10 as *mut Utsname) };
+    assert_eq!(uname(bad), -1);
+    println!("Test uname OK!");
+    0
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{spawn, waitpid};
+
+/// 程序行为：运行访问非法地址、执行非法指令的测例，内核应杀死它们而不是崩溃，
+/// 父进程回收到的退出码分别为 -2 和 -3。
+
+/// 正确输出：（内核输出 PageFault 和 IllegalInstruction 的信息）
+/// Test user fault OK!
+
+static TESTS: &[(&str, i32)] = &[
+    ("ch2b_bad_address\0", -2),
+    ("ch2b_bad_instructions\0", -3),
+    ("ch2b_bad_register\0", -3),
+];
+
+#[no_mangle]
+pub fn main() -> i32 {
+    for &(test, code) in TESTS.iter() {
+        let pid = spawn(test);
+        assert!(pid > 0);
+        let mut exit_code: i32 = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, code);
+    }
+    println!("Test user fault OK!");
+    0
+}
@@ -14,48 +14,146 @@ static TESTS: &[&str] = &[
     "ch3b_yield2\0",
     "ch3b_sleep\0",
     "ch3b_sleep1\0",
+    "ch3_taskinfo\0",
+    "ch3_taskinfo_count\0",
     "ch4_mmap0\0",
     "ch4_mmap1\0",
     "ch4_mmap2\0",
     "ch4_mmap3\0",
+    "ch4_mmap_lazy\0",
+    "ch4_mmap_anon\0",
+    "ch4_mmap_overflow\0",
+    "ch4_mmap_wonly\0",
+    "ch4_mmap_wx\0",
+    "ch4_mmap_overrun\0",
+    "ch4_bad_pointer\0",
+    "ch4_copy_user\0",
+    "ch4_gettime_cross\0",
+    "ch4_clock_gettime\0",
+    "ch4_gettime_tz\0",
+    "ch4_mprotect\0",
     "ch4_unmap\0",
     "ch4_unmap2\0",
+    "ch4_unmap_split\0",
+    "ch4_tlb_stale\0",
     "ch5_spawn0\0",
     "ch5_spawn1\0",
+    "ch5_spawn2\0",
+    "ch5_spawn3\0",
+    "ch5_exec_argv\0",
+    "ch5_exec_env\0",
+    "ch5_exec_stats\0",
+    "ch5_list_apps\0",
+    "ch5_cow\0",
+    "ch5_user_fault\0",
+    "ch5_stack_grow\0",
+    "ch5_stack_overflow\0",
+    "ch5_getppid\0",
+    "ch5_fork_stride\0",
     "ch5_setprio\0",
+    "ch5_setprio1\0",
+    "ch5_sleep\0",
+    "ch5_nanosleep\0",
+    "ch5_waitpid_block\0",
+    "ch5_waitpid_nohang\0",
+    "ch5_waitpid_held\0",
+    "ch5_wait_nostatus\0",
+    "ch5_wait4\0",
+    "ch5_getrusage\0",
+    "ch5_kill\0",
+    "ch5_sig_simple\0",
+    "ch5_sig_mask\0",
+    "ch5_pause\0",
+    "ch5_pgid\0",
+    "ch5_kill_sibling\0",
+    "ch5_sbrk\0",
+    "ch5_ps\0",
+    "ch5_prctl\0",
+    "ch5_pipetest\0",
+    "ch5_pipe_eof\0",
+    "ch5_dup\0",
+    "ch5_close\0",
+    "ch5_writev\0",
+    "ch5_shm\0",
+    "ch5_mem_stats\0",
+    "ch5_prlimit\0",
+    "ch5_rlimit\0",
+    "ch5_strace\0",
+    "ch5_dmesg\0",
+    "ch5_getrandom\0",
+    "ch5_uname\0",
     // "ch5_stride\0",
 ];
-static STEST: &str = "ch5_stride\0";
+/// 需要单独运行的测例
+static STESTS: &[&str] = &[
+    "ch5_stride\0",
+    "ch4_mmap_oom\0",
+    "ch5_orphan\0",
+    "ch5_times\0",
+    "ch5_alarm\0",
+    "ch5_stride_stats\0",
+    "ch5_stride_fifo\0",
+    "ch5_stride_block\0",
+    "ch5_yield_latency\0",
+    "ch5_mlfq\0",
+    "ch5_quantum\0",
+    "ch5_idle_sleep\0",
+    "ch5_preempt_latency\0",
+    "ch5_pid_reuse\0",
+    "ch5_fork_bomb\0",
+    "ch5_sysinfo\0",
+    "ch5_frame_stats\0",
+    "ch5_exec_loop\0",
+    "ch5_fork_share\0",
+    "ch4_mmap_zero\0",
+    "ch5_oom_kill\0",
+    "ch5_smp\0",
+    "ch5_klogctl\0",
+    "ch5_timer_ticks\0",
+    "ch5_sleepers\0",
+    "ch5_exit_group\0",
+    "ch5_bad_elf\0",
+];
+
+use user_lib::{get_time, spawn, waitpid};
 
-use user_lib::{spawn, waitpid};
+/// 同时运行的测例数，测例自己还会 fork 子进程，不能超出进程数上限
+const BATCH: usize = 16;
 
 /// 辅助测例，运行所有其他测例。
 
 #[no_mangle]
 pub fn main() -> i32 {
-    let mut pid = [0; 20];
-    for (i, &test) in TESTS.iter().enumerate() {
-        println!("Usertests: Running {}", test);
-        pid[i] = spawn(test);
-    }
+    let start = get_time();
     let mut xstate: i32 = Default::default();
-    for (i, &test) in TESTS.iter().enumerate() {
-        let wait_pid = waitpid(pid[i] as usize, &mut xstate);
-        assert_eq!(pid[i], wait_pid);
+    for tests in TESTS.chunks(BATCH) {
+        let mut pid = [0; BATCH];
+        for (i, &test) in tests.iter().enumerate() {
+            println!("Usertests: Running {}", test);
+            pid[i] = spawn(test);
+        }
+        for (i, &test) in tests.iter().enumerate() {
+            let wait_pid = waitpid(pid[i] as usize, &mut xstate);
+            assert_eq!(pid[i], wait_pid);
+            println!(
+                "\x1b[32mUsertests: Test {} in Process {} exited with code {}\x1b[0m",
+                test, pid[i], xstate
+            );
+        }
+    }
+    for &test in STESTS.iter() {
+        println!("Usertests: Running {}", test);
+        let spid = spawn(test);
+        xstate = Default::default();
+        let wait_pid = waitpid(spid as usize, &mut xstate);
+        assert_eq!(spid, wait_pid);
         println!(
             "\x1b[32mUsertests: Test {} in Process {} exited with code {}\x1b[0m",
-            test, pid[i], xstate
+            test, spid, xstate
         );
     }
-    println!("Usertests: Running {}", STEST);
-    let spid = spawn(STEST);
-    xstate = Default::default();
-    let wait_pid = waitpid(spid as usize, &mut xstate);
-    assert_eq!(spid, wait_pid);
-    println!(
-        "\x1b[32mUsertests: Test {} in Process {} exited with code {}\x1b[0m",
-        STEST, spid, xstate
-    );
+    // 用于比较内核改动前后跑完全部测例的时间
+    println!("Usertests: all tests done in {} ms", get_time() - start);
     println!("ch5 Usertests passed!");
     0
 }
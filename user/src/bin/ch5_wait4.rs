@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, mmap, sleep_ms, wait4, Rusage};
+
+/// 程序行为：先后 fork 两个子进程，都先睡眠 10 ms，再写一段新映射区域的前若干页，
+/// 第一个写 0 页，第二个写 PAGES 页，页帧在第一次写时才分配。两次 wait4 报告的缺页
+/// 次数之差应恰好是 PAGES，常驻页峰值不少于 PAGES，睡眠计入主动切换。
+
+/// 正确输出：（无报错信息）
+/// Test wait4 OK!
+
+const PAGES: usize = 16;
+const PAGE_SIZE: usize = 4096;
+const START: usize = 0x20000000;
+
+/// Fork a child which sleeps then writes the first `pages` pages of a fresh area,
+/// return its resource usage
+fn child_usage(pages: usize) -> Rusage {
+    let pid = fork();
+    assert!(pid >= 0);
+    if pid == 0 {
+        assert_eq!(START as isize, mmap(START, PAGES * PAGE_SIZE, 3));
+        sleep_ms(10);
+        for i in 0..pages {
+            unsafe {
+                ((START + i * PAGE_SIZE) as *mut usize).write_volatile(i);
+            }
+        }
+        exit(0);
+    }
+    let mut exit_code: i32 = -1;
+    let mut rusage = Rusage::default();
+    assert_eq!(wait4(pid, &mut exit_code, 0, &mut rusage), pid);
+    assert_eq!(exit_code, 0);
+    rusage
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let base = child_usage(0);
+    let usage = child_usage(PAGES);
+    println!(
+        "child took {} faults, {} pages at most, {} voluntary switches",
+        usage.minflt, usage.maxrss, usage.nvcsw
+    );
+    assert_eq!(usage.minflt - base.minflt, PAGES);
+    assert_eq!(usage.majflt, 0);
+    assert!(usage.maxrss >= PAGES);
+    assert!(base.nvcsw >= 1 && usage.nvcsw >= 1);
+    println!("Test wait4 OK!");
+    0
+}
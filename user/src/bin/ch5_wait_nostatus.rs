@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, sleep, sys_waitpid, wait_nostatus, waitpid};
+
+/*
+程序行为：用空指针等待子进程时内核不写入退出码，仍回收子进程并返回其 pid；
+退出码指针非法时 waitpid 返回 -1，子进程仍可以被等待。
+理想结果：输出 Test wait nostatus OK!
+*/
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        exit(3);
+    }
+    assert_eq!(wait_nostatus(pid), pid);
+    assert_eq!(wait_nostatus(pid), -1);
+    let pid = fork();
+    if pid == 0 {
+        exit(4);
+    }
+    // 等待子进程成为僵尸进程
+    sleep(50);
+    assert_eq!(sys_waitpid(pid, 0x80200000 as *mut i32), -1);
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 4);
+    println!("Test wait nostatus OK!");
+    0
+}
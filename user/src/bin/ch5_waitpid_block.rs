@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, sleep_blocking, task_info, waitpid, TaskInfo, SYSCALL_YIELD};
+
+/// 程序行为：子进程睡眠 100ms 后退出，父进程调用 waitpid 阻塞等待，
+/// 期间父进程几乎不应调用 yield。
+
+/// 正确输出：（无报错信息）
+/// Test waitpid blocking OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let start = get_time();
+    let pid = fork();
+    if pid == 0 {
+        sleep_blocking(100);
+        exit(7);
+    }
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 7);
+    assert!(get_time() - start >= 100);
+    let info = TaskInfo::new();
+    assert_eq!(0, task_info(&info));
+    assert!(
+        info.syscall_times[SYSCALL_YIELD] <= 2,
+        "parent yielded while waiting"
+    );
+    println!("Test waitpid blocking OK!");
+    0
+}
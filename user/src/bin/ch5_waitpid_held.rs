@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, getpid, hold_task, waitpid};
+
+/*
+程序行为：内核额外持有子进程的引用时，waitpid 仍能正常回收子进程，内核不会崩溃；
+持有者退出时内核丢弃它持有的引用。hold_task 只在内核开启 selftest 特性时可用，
+否则总是返回 -1，测例只检查 waitpid。
+理想结果：输出 Test waitpid held OK!
+*/
+
+fn wait_for(pid: isize) -> i32 {
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    exit_code
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    // 只能持有自己的子进程
+    assert_eq!(hold_task(getpid() as usize, true), -1);
+    let pid = fork();
+    if pid == 0 {
+        exit(7);
+    }
+    let supported = hold_task(pid as usize, true) == 0;
+    assert_eq!(wait_for(pid), 7);
+    if !supported {
+        println!("hold_task needs the selftest feature of the kernel");
+        println!("Test waitpid held OK!");
+        return 0;
+    }
+    assert_eq!(hold_task(pid as usize, false), 0);
+    assert_eq!(hold_task(pid as usize, false), -1);
+    // 子进程持有孙进程后直接退出，不释放
+    let pid = fork();
+    if pid == 0 {
+        let grandchild = fork();
+        if grandchild == 0 {
+            exit(0);
+        }
+        assert_eq!(hold_task(grandchild as usize, true), 0);
+        exit(8);
+    }
+    assert_eq!(wait_for(pid), 8);
+    println!("Test waitpid held OK!");
+    0
+}
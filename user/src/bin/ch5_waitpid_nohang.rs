@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, sleep_blocking, sys_waitpid, waitpid_nohang};
+
+/// 程序行为：没有子进程时 WNOHANG 返回 -1；子进程未退出时 WNOHANG 返回 0；
+/// exit_code_ptr 为空指针时 waitpid 仍能正常回收子进程。
+
+/// 正确输出：（无报错信息）
+/// Test waitpid WNOHANG OK!
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut exit_code: i32 = 0;
+    assert_eq!(waitpid_nohang(-1, &mut exit_code), -1);
+    let pid = fork();
+    if pid == 0 {
+        sleep_blocking(50);
+        exit(1);
+    }
+    assert_eq!(waitpid_nohang(pid, &mut exit_code), 0);
+    assert_eq!(waitpid_nohang(-1, &mut exit_code), 0);
+    assert_eq!(sys_waitpid(pid, core::ptr::null_mut()), pid);
+    assert_eq!(waitpid_nohang(-1, &mut exit_code), -1);
+    println!("Test waitpid WNOHANG OK!");
+    0
+}
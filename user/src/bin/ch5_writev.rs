@@ -0,0 +1,118 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+#[macro_use]
+extern crate user_lib;
+
+use alloc::format;
+use alloc::vec::Vec;
+use user_lib::{close, dup, exit, fork, pipe, read, waitpid, write, writev, IoVec, WRITEV_MAX};
+
+/// 程序行为：writev 一次写出多个缓冲区，任一缓冲区地址非法时返回 -1 且什么都不写，
+/// 一次至多写出 WRITEV_MAX 字节；4 个子进程把标准输出换成同一个管道的写端后并发 println，
+/// 父进程从管道读出的每一行都完整、不交错。
+
+/// 正确输出：（无报错信息）
+/// Test writev OK!
+
+const WRITERS: usize = 4;
+const LINES: usize = 50;
+
+fn expected_line(writer: usize, line: usize) -> alloc::string::String {
+    format!(
+        "writer {} line {}: {} {} {}",
+        writer, line, "alpha", "beta", "gamma"
+    )
+}
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let (read_end, write_end) = (pipe_fd[0], pipe_fd[1]);
+    let mut buffer = [0u8; 64];
+
+    // 各缓冲区依次写出
+    let iov = [IoVec::new(b"ab"), IoVec::new(b""), IoVec::new(b"cde")];
+    assert_eq!(writev(write_end, &iov), 5);
+    assert_eq!(read(read_end, &mut buffer), 5);
+    assert_eq!(&buffer[..5], b"abcde");
+    // 非法地址使整次调用失败，前面合法的缓冲区也不会写出
+    let bad = IoVec { base: 0, len: 8 };
+    assert_eq!(writev(write_end, &[IoVec::new(b"lost"), bad]), -1);
+    assert_eq!(write(write_end, b"ok"), 2);
+    assert_eq!(read(read_end, &mut buffer), 2);
+    assert_eq!(&buffer[..2], b"ok");
+    // 超过 WRITEV_MAX 的部分不写，由子进程读出并计数
+    let pid = fork();
+    if pid == 0 {
+        assert_eq!(close(write_end), 0);
+        let mut count = 0;
+        loop {
+            let len = read(read_end, &mut buffer);
+            if len <= 0 {
+                break;
+            }
+            count += len as usize;
+        }
+        exit(if count == WRITEV_MAX { 0 } else { 1 });
+    }
+    assert_eq!(close(read_end), 0);
+    let big = [b'x'; WRITEV_MAX];
+    let iov = [IoVec::new(b"head"), IoVec::new(&big)];
+    assert_eq!(writev(write_end, &iov), WRITEV_MAX as isize);
+    assert_eq!(close(write_end), 0);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    let (read_end, write_end) = (pipe_fd[0], pipe_fd[1]);
+    let mut pids = [0; WRITERS];
+    for (writer, pid) in pids.iter_mut().enumerate() {
+        *pid = fork();
+        if *pid == 0 {
+            assert_eq!(close(read_end), 0);
+            assert_eq!(close(1), 0);
+            assert_eq!(dup(write_end), 1);
+            assert_eq!(close(write_end), 0);
+            for line in 0..LINES {
+                println!(
+                    "writer {} line {}: {} {} {}",
+                    writer, line, "alpha", "beta", "gamma"
+                );
+            }
+            exit(0);
+        }
+    }
+    assert_eq!(close(write_end), 0);
+    let mut output = Vec::new();
+    loop {
+        let len = read(read_end, &mut buffer);
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..len as usize]);
+    }
+    assert_eq!(close(read_end), 0);
+    let mut next_line = [0; WRITERS];
+    let text = core::str::from_utf8(&output).unwrap();
+    for line in text.lines() {
+        let writer = line.as_bytes()[7] - b'0';
+        let writer = writer as usize;
+        assert!(writer < WRITERS);
+        assert_eq!(line, expected_line(writer, next_line[writer]));
+        next_line[writer] += 1;
+    }
+    assert_eq!(next_line, [LINES; WRITERS]);
+    for pid in pids {
+        let mut exit_code = -1;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+    }
+    println!("Test writev OK!");
+    0
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exit, fork, get_time, waitpid, yield_};
+
+/// 程序行为：子进程忙等 300 ms，父进程同时反复 yield。
+/// yield 只按已用掉的时间片比例增加 stride，父进程在每次 yield 后应很快被再次调度，
+/// 200 次 yield 的总耗时远小于 200 个时间片；忙等的子进程仍能正常运行结束。
+
+/// 正确输出：（无报错信息）
+/// Test yield latency OK!
+
+const YIELDS: usize = 200;
+const BUSY_MS: isize = 300;
+
+#[no_mangle]
+pub fn main() -> i32 {
+    let pid = fork();
+    if pid == 0 {
+        let start = get_time();
+        while get_time() - start < BUSY_MS {}
+        exit(0);
+    }
+    let start = get_time();
+    for _ in 0..YIELDS {
+        yield_();
+    }
+    let elapsed = get_time() - start;
+    println!("{} yields took {} ms", YIELDS, elapsed);
+    assert!(elapsed < 100);
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test yield latency OK!");
+    0
+}
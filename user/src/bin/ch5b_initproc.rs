@@ -4,7 +4,7 @@
 #[macro_use]
 extern crate user_lib;
 
-use user_lib::{exec, fork, wait, yield_};
+use user_lib::{exec, fork, shutdown, wait};
 
 #[no_mangle]
 fn main() -> i32 {
@@ -14,9 +14,9 @@ fn main() -> i32 {
         loop {
             let mut exit_code: i32 = 0;
             let pid = wait(&mut exit_code);
+            // the shell and every orphan have exited
             if pid == -1 {
-                yield_();
-                continue;
+                shutdown(false);
             }
             println!(
                 "[initproc] Released a zombie process, pid={}, exit_code={}",
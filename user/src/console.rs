@@ -8,7 +8,7 @@ pub const STDOUT: usize = 1;
 
 const CONSOLE_BUFFER_SIZE: usize = 256 * 10;
 
-use super::{read, write};
+use super::{read, writev, IoVec};
 use lazy_static::*;
 
 struct ConsoleBuffer(VecDeque<u8>);
@@ -21,9 +21,10 @@ lazy_static! {
 }
 
 impl ConsoleBuffer {
+    /// 用一次 writev 写出缓冲区环形队列的两段，输出不会与其他进程交错
     fn flush(&mut self) -> isize {
-        let s: &[u8] = self.0.make_contiguous();
-        let ret = write(STDOUT, s);
+        let (front, back) = self.0.as_slices();
+        let ret = writev(STDOUT, &[IoVec::new(front), IoVec::new(back)]);
         self.0.clear();
         ret
     }
@@ -33,7 +34,7 @@ impl Write for ConsoleBuffer {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for c in s.as_bytes().iter() {
             self.0.push_back(*c);
-            if (*c == b'\n' || self.0.len() == CONSOLE_BUFFER_SIZE) && -1 == self.flush() {
+            if self.0.len() == CONSOLE_BUFFER_SIZE && -1 == self.flush() {
                 return Err(fmt::Error);
             }
         }
@@ -47,6 +48,10 @@ pub fn print(args: fmt::Arguments) {
     // buf.write_fmt(args).unwrap();
     // BUG FIX: 关闭 stdout 后，本函数不能触发 panic，否则会造成死锁
     buf.write_fmt(args);
+    // 一次 println 的所有片段一起写出
+    if buf.0.contains(&b'\n') {
+        buf.flush();
+    }
 }
 
 #[macro_export]
@@ -65,7 +70,8 @@ macro_rules! println {
 
 pub fn getchar() -> u8 {
     let mut c = [0u8; 1];
-    read(STDIN, &mut c);
+    // a signal handled while waiting for input makes the read fail
+    while read(STDIN, &mut c) < 0 {}
     c[0]
 }
 
@@ -44,13 +44,17 @@ fn clear_bss() {
     }
 }
 
+/// The null-terminated array of `KEY=VALUE` strings passed by exec or spawn
+static mut ENVP: usize = 0;
+
 #[no_mangle]
 #[link_section = ".text.entry"]
-pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
+pub extern "C" fn _start(argc: usize, argv: usize, envp: usize) -> ! {
     clear_bss();
     unsafe {
         HEAP.lock()
             .init(HEAP_SPACE.as_ptr() as usize, USER_HEAP_SIZE);
+        ENVP = envp;
     }
     let mut v: Vec<&'static str> = Vec::new();
     for i in 0..argc {
@@ -69,6 +73,32 @@ pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
     exit(main(argc, v.as_slice()));
 }
 
+/// Read a nul-terminated string at `ptr`
+unsafe fn c_str(ptr: usize) -> &'static str {
+    let len = (0usize..)
+        .find(|i| ((ptr + *i) as *const u8).read_volatile() == 0)
+        .unwrap();
+    core::str::from_utf8(core::slice::from_raw_parts(ptr as *const u8, len)).unwrap_or("")
+}
+
+/// The value of the environment variable `key`
+pub fn getenv(key: &str) -> Option<&'static str> {
+    let envp = unsafe { ENVP };
+    if envp == 0 {
+        return None;
+    }
+    (0..)
+        .map(|i| unsafe {
+            ((envp + i * core::mem::size_of::<usize>()) as *const usize).read_volatile()
+        })
+        .take_while(|&ptr| ptr != 0)
+        .map(|ptr| unsafe { c_str(ptr) })
+        .find_map(|env| {
+            env.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+}
+
 #[linkage = "weak"]
 #[no_mangle]
 fn main(_argc: usize, _argv: &[&str]) -> i32 {
@@ -98,6 +128,174 @@ impl TimeVal {
     }
 }
 
+/// Filled by get_time with UTC and no DST
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZone {
+    pub minuteswest: i32,
+    pub dsttime: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeSpec {
+    pub sec: usize,
+    pub nsec: usize,
+}
+
+/// Wall-clock time, which can be set by settimeofday
+pub const CLOCK_REALTIME: usize = 0;
+/// Time since boot, which never goes backwards
+pub const CLOCK_MONOTONIC: usize = 1;
+
+/// CPU time in clock ticks, `CLOCKS_PER_SEC` per second
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tms {
+    /// user time of the process
+    pub utime: usize,
+    /// kernel time of the process
+    pub stime: usize,
+    /// user time of the reaped children
+    pub cutime: usize,
+    /// kernel time of the reaped children
+    pub cstime: usize,
+}
+
+pub const CLOCKS_PER_SEC: usize = 100;
+
+/// Resource usage of a reaped child filled by wait4, or of the process itself or its
+/// reaped children filled by getrusage
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Rusage {
+    /// user time
+    pub utime: TimeVal,
+    /// kernel time
+    pub stime: TimeVal,
+    /// largest number of pages owned at once
+    pub maxrss: usize,
+    /// page faults served without reading a disk
+    pub minflt: usize,
+    /// page faults which read a disk, always 0
+    pub majflt: usize,
+    /// voluntary context switches, by yielding or blocking
+    pub nvcsw: usize,
+    /// involuntary context switches, by the timer
+    pub nivcsw: usize,
+}
+
+/// Length of each field of `Utsname`, the terminating zero included
+pub const UTSNAME_LEN: usize = 65;
+
+/// Identification of the kernel filled by uname, each field ends with `\0`
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Utsname {
+    pub sysname: [u8; UTSNAME_LEN],
+    pub nodename: [u8; UTSNAME_LEN],
+    pub release: [u8; UTSNAME_LEN],
+    pub version: [u8; UTSNAME_LEN],
+    pub machine: [u8; UTSNAME_LEN],
+}
+
+impl Default for Utsname {
+    fn default() -> Self {
+        Self {
+            sysname: [0; UTSNAME_LEN],
+            nodename: [0; UTSNAME_LEN],
+            release: [0; UTSNAME_LEN],
+            version: [0; UTSNAME_LEN],
+            machine: [0; UTSNAME_LEN],
+        }
+    }
+}
+
+/// The text of a `Utsname` field up to its terminating zero
+pub fn utsname_str(field: &[u8; UTSNAME_LEN]) -> &str {
+    let len = field.iter().position(|&b| b == 0).unwrap_or(UTSNAME_LEN);
+    core::str::from_utf8(&field[..len]).unwrap_or("")
+}
+
+/// System-wide statistics
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SysInfo {
+    /// seconds since boot
+    pub uptime: usize,
+    /// number of physical frames managed by the kernel
+    pub total_frames: usize,
+    /// number of free physical frames
+    pub free_frames: usize,
+    /// highest number of physical frames allocated at the same time since boot
+    pub peak_frames: usize,
+    /// number of frame allocations failed since boot
+    pub frame_failures: usize,
+    /// size of the kernel heap in bytes
+    pub total_heap: usize,
+    /// bytes of the kernel heap not allocated
+    pub free_heap: usize,
+    /// highest number of bytes of the kernel heap allocated at the same time since boot
+    pub peak_heap: usize,
+    /// number of kernel heap allocations since boot
+    pub heap_allocations: usize,
+    /// number of live processes, zombies included
+    pub procs: usize,
+    /// number of zombies not reaped yet
+    pub zombies: usize,
+    /// number of harts running tasks
+    pub harts: usize,
+}
+
+/// Memory usage of the current process in pages, a frame shared with other
+/// processes counts as resident in each of them
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemStats {
+    /// number of physical frames mapped
+    pub resident_pages: usize,
+    /// number of pages reserved by mmap but not written yet
+    pub lazy_pages: usize,
+    /// number of resident frames shared copy-on-write or through shared memory
+    pub shared_pages: usize,
+}
+
+/// Soft and hard limit of a resource
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RLimit {
+    /// the limit enforced
+    pub rlim_cur: usize,
+    /// ceiling of `rlim_cur`, which cannot be raised again
+    pub rlim_max: usize,
+}
+
+/// Bytes the user stack may grow to, 1 MiB by default and at most
+pub const RLIMIT_STACK: usize = 3;
+/// Children a process may have until they are reaped, unlimited by default
+pub const RLIMIT_NPROC: usize = 6;
+/// Bytes a process may map through mmap and its heap, 1024 pages by default
+pub const RLIMIT_AS: usize = 9;
+/// No limit at all
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// Maximum number of live processes
+pub const NPROC: usize = 64;
+
+/// Copy the newest lines of the kernel log that fit into the buffer
+pub const SYSLOG_ACTION_READ_ALL: usize = 3;
+/// Same as `SYSLOG_ACTION_READ_ALL`, then empty the log
+pub const SYSLOG_ACTION_READ_CLEAR: usize = 4;
+/// Empty the kernel log
+pub const SYSLOG_ACTION_CLEAR: usize = 5;
+/// Number of bytes in the kernel log
+pub const SYSLOG_ACTION_SIZE_UNREAD: usize = 9;
+/// Size of the kernel log buffer
+pub const SYSLOG_ACTION_SIZE_BUFFER: usize = 10;
+
+/// Maximum number of file descriptors of a process
+pub const MAX_FDS: usize = 64;
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum TaskStatus {
     UnInit,
@@ -106,6 +304,43 @@ pub enum TaskStatus {
     Exited,
 }
 
+/// How the scheduler has treated the current process, times in us
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SchedStats {
+    pub times_scheduled: usize,
+    pub total_running_us: usize,
+    pub total_ready_wait_us: usize,
+    pub last_enqueue_time: usize,
+    pub last_schedule_time: usize,
+}
+
+/// Snapshot of the scheduler
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SchedStat {
+    pub ready: usize,
+    pub blocked: usize,
+    pub context_switches: usize,
+    pub quantum_us: usize,
+}
+
+/// A task in the scheduler snapshot
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SchedEntry {
+    pub pid: usize,
+    pub priority: usize,
+    pub stride: usize,
+    /// nul-padded name of the process
+    pub name: [u8; TASK_NAME_LEN],
+}
+
+/// Length of the name of a process, including the trailing nul
+pub const TASK_NAME_LEN: usize = 16;
+pub const PR_SET_NAME: usize = 15;
+pub const PR_GET_NAME: usize = 16;
+
 #[derive(Copy, Clone, Debug)]
 pub struct SyscallInfo {
     pub id: usize,
@@ -195,6 +430,31 @@ pub fn write(fd: usize, buf: &[u8]) -> isize {
     sys_write(fd, buf)
 }
 
+/// A buffer written by writev
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IoVec {
+    pub base: usize,
+    pub len: usize,
+}
+
+impl IoVec {
+    pub fn new(buf: &[u8]) -> Self {
+        Self {
+            base: buf.as_ptr() as usize,
+            len: buf.len(),
+        }
+    }
+}
+
+/// Upper bound of the bytes written by one writev
+pub const WRITEV_MAX: usize = 4096;
+
+/// Write the buffers of `iov` with a single write, return the number of bytes written
+pub fn writev(fd: usize, iov: &[IoVec]) -> isize {
+    sys_writev(fd, iov)
+}
+
 pub fn link(old_path: &str, new_path: &str) -> isize {
     sys_linkat(AT_FDCWD as usize, old_path, AT_FDCWD as usize, new_path, 0)
 }
@@ -220,10 +480,121 @@ pub fn exit(exit_code: i32) -> ! {
     sys_exit(exit_code);
 }
 
+/// Kill every descendant of the process, then exit with `exit_code`
+pub fn exit_group(exit_code: i32) -> ! {
+    console::flush();
+    sys_exit_group(exit_code);
+}
+
 pub fn yield_() -> isize {
     sys_yield()
 }
 
+pub const SIGKILL: usize = 9;
+pub const SIGUSR1: usize = 10;
+pub const SIGUSR2: usize = 12;
+pub const SIGALRM: usize = 14;
+pub const SIGCHLD: usize = 17;
+pub const SIGCONT: usize = 18;
+pub const SIGSTOP: usize = 19;
+
+bitflags! {
+    pub struct SignalFlags: u32 {
+        const SIGKILL = 1 << 9;
+        const SIGUSR1 = 1 << 10;
+        const SIGUSR2 = 1 << 12;
+        const SIGALRM = 1 << 14;
+        const SIGCHLD = 1 << 17;
+        const SIGCONT = 1 << 18;
+        const SIGSTOP = 1 << 19;
+    }
+}
+
+/// The handler is entered with the signal number in a0 and must end with `sigreturn`
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SignalAction {
+    pub handler: usize,
+    pub mask: SignalFlags,
+}
+
+impl Default for SignalAction {
+    fn default() -> Self {
+        Self {
+            handler: 0,
+            mask: SignalFlags::empty(),
+        }
+    }
+}
+
+pub fn kill(pid: usize, signum: usize) -> isize {
+    sys_kill(pid, signum)
+}
+
+/// Send `signum` to every process in the group `pgid`
+pub fn killpg(pgid: usize, signum: usize) -> isize {
+    sys_kill(-(pgid as isize) as usize, signum)
+}
+
+/// Move `pid`, or the process itself if 0, into the group `pgid`, or into a new group
+/// led by itself if `pgid` is 0
+pub fn setpgid(pid: usize, pgid: usize) -> isize {
+    sys_setpgid(pid, pgid)
+}
+
+/// The process group of `pid`, or of the process itself if 0
+pub fn getpgid(pid: usize) -> isize {
+    sys_getpgid(pid)
+}
+
+pub fn sigaction(
+    signum: usize,
+    action: Option<&SignalAction>,
+    old_action: Option<&mut SignalAction>,
+) -> isize {
+    sys_sigaction(
+        signum,
+        action.map_or(core::ptr::null(), |a| a as *const _),
+        old_action.map_or(core::ptr::null_mut(), |a| a as *mut _),
+    )
+}
+
+/// Read the limit of `resource` into `old_limit` and replace it with `new_limit`.
+/// `pid` must be 0 or the caller itself.
+pub fn prlimit(
+    pid: usize,
+    resource: usize,
+    new_limit: Option<&RLimit>,
+    old_limit: Option<&mut RLimit>,
+) -> isize {
+    sys_prlimit(
+        pid,
+        resource,
+        new_limit.map_or(core::ptr::null(), |l| l as *const _),
+        old_limit.map_or(core::ptr::null_mut(), |l| l as *mut _),
+    )
+}
+
+/// Return the previously blocked signals
+pub fn sigprocmask(mask: SignalFlags) -> isize {
+    sys_sigprocmask(mask.bits())
+}
+
+/// Raise SIGALRM in `seconds`, or cancel the alarm if it is 0.
+/// Return the seconds left before the previous alarm.
+pub fn alarm(seconds: usize) -> isize {
+    sys_alarm(seconds)
+}
+
+pub fn sigreturn() -> isize {
+    sys_sigreturn()
+}
+
+/// Block until a signal runs its handler or kills the process, always return -1
+pub fn pause() -> isize {
+    sys_pause()
+}
+
 pub fn get_time() -> isize {
     let time = TimeVal::new();
     match sys_get_time(&time, 0) {
@@ -232,16 +603,65 @@ pub fn get_time() -> isize {
     }
 }
 
+pub fn clock_gettime(clock_id: usize, ts: &mut TimeSpec) -> isize {
+    sys_clock_gettime(clock_id, ts)
+}
+
+/// Set the wall-clock time, only allowed for initproc
+pub fn settimeofday(time: &TimeVal) -> isize {
+    sys_settimeofday(time, 0)
+}
+
+/// Fill `tms` with the CPU time used so far and return the clock ticks since boot
+pub fn times(tms: &mut Tms) -> isize {
+    sys_times(tms)
+}
+
+/// Power the machine off, QEMU exits with a non-zero status on `failure`.
+/// Only initproc may do it, return -1 otherwise
+pub fn shutdown(failure: bool) -> isize {
+    sys_shutdown(failure as usize)
+}
+
+/// Fill `buf` with the names of the kernel and the machine
+pub fn uname(buf: &mut Utsname) -> isize {
+    sys_uname(buf)
+}
+
+/// Fill `info` with the system-wide statistics
+pub fn sysinfo(info: &mut SysInfo) -> isize {
+    sys_sysinfo(info)
+}
+
+/// Set the name of the current process to the nul-terminated `name`, truncated to 15 bytes
+pub fn set_name(name: &str) -> isize {
+    sys_prctl(PR_SET_NAME, name.as_ptr() as usize)
+}
+
+/// Store the nul-padded name of the current process into `name`
+pub fn get_name(name: &mut [u8; TASK_NAME_LEN]) -> isize {
+    sys_prctl(PR_GET_NAME, name.as_mut_ptr() as usize)
+}
+
 pub fn getpid() -> isize {
     sys_getpid()
 }
 
+pub fn getppid() -> isize {
+    sys_getppid()
+}
+
 pub fn fork() -> isize {
     sys_fork()
 }
 
 pub fn exec(path: &str, args: &[*const u8]) -> isize {
-    sys_exec(path, args)
+    sys_exec(path, args, &[core::ptr::null::<u8>()])
+}
+
+/// `args` and `envs` must be terminated by a null pointer, each env is like `KEY=VALUE`
+pub fn exec_with_env(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
+    sys_exec(path, args, envs)
 }
 
 pub fn set_priority(prio: isize) -> isize {
@@ -274,6 +694,56 @@ pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
     }
 }
 
+/// Wait for the child `pid` (or any child if `pid` is -1) without storing its exit code
+pub fn wait_nostatus(pid: isize) -> isize {
+    loop {
+        match sys_waitpid(pid, core::ptr::null_mut()) {
+            -2 => {
+                sys_yield();
+            }
+            n => {
+                return n;
+            }
+        }
+    }
+}
+
+/// Return 0 at once from waitpid if no child has exited yet
+pub const WNOHANG: usize = 1;
+
+/// Poll a child (or any child if `pid` is -1) without blocking
+pub fn waitpid_nohang(pid: isize, exit_code: &mut i32) -> isize {
+    sys_waitpid_options(pid, exit_code as *mut _, WNOHANG)
+}
+
+/// Wait for a child like waitpid and store its resource usage into `rusage`
+pub fn wait4(pid: isize, exit_code: &mut i32, options: usize, rusage: &mut Rusage) -> isize {
+    sys_wait4(pid, exit_code as *mut _, options, rusage as *mut _)
+}
+
+/// getrusage reports the calling process itself
+pub const RUSAGE_SELF: isize = 0;
+/// getrusage reports the totals of the reaped children, with the largest of their peaks
+pub const RUSAGE_CHILDREN: isize = -1;
+
+/// Store the resource usage of the process or of its reaped children into `usage`
+pub fn getrusage(who: isize, usage: &mut Rusage) -> isize {
+    sys_getrusage(who, usage as *mut _)
+}
+
+/// Sleep for `req`, return -1 and store the unslept time into `rem` if woken up by a signal
+pub fn nanosleep(req: &TimeSpec, rem: Option<&mut TimeSpec>) -> isize {
+    sys_nanosleep(req, rem.map_or(core::ptr::null_mut(), |rem| rem as *mut _))
+}
+
+pub fn sleep_ms(ms: usize) -> isize {
+    let req = TimeSpec {
+        sec: ms / 1000,
+        nsec: ms % 1000 * 1_000_000,
+    };
+    nanosleep(&req, None)
+}
+
 pub fn sleep_blocking(sleep_ms: usize) {
     sys_sleep(sleep_ms);
 }
@@ -284,6 +754,18 @@ pub fn sleep(period_ms: usize) {
         sys_yield();
     }
 }
+/// Move the program break by `size` bytes, return the old break or -1
+pub fn sbrk(size: isize) -> isize {
+    sys_sbrk(size)
+}
+
+/// `mmap` failed because of a bad argument or an overlapping mapping
+pub const MMAP_EINVAL: isize = -1;
+/// `mmap` failed because there is not enough memory or address space
+pub const MMAP_ENOMEM: isize = -12;
+
+/// Map `len` bytes at `start` (or anywhere if `start` is 0) with the permission `prot`,
+/// return the start address of the mapping, or [`MMAP_EINVAL`] or [`MMAP_ENOMEM`]
 pub fn mmap(start: usize, len: usize, prot: usize) -> isize {
     sys_mmap(start, len, prot)
 }
@@ -292,21 +774,131 @@ pub fn munmap(start: usize, len: usize) -> isize {
     sys_munmap(start, len)
 }
 
+/// A key which always creates a new shared memory segment
+pub const IPC_PRIVATE: usize = 0;
+
+/// Id of the shared memory segment of `key`, created with `size` bytes if there is none
+pub fn shmget(key: usize, size: usize) -> isize {
+    sys_shmget(key, size)
+}
+
+/// Map the shared memory segment `shmid`, at `addr` if it is free or anywhere if it is 0,
+/// and return where it is mapped
+pub fn shmat(shmid: usize, addr: usize) -> isize {
+    sys_shmat(shmid, addr)
+}
+
+/// Unmap the shared memory segment attached at `addr`
+pub fn shmdt(addr: usize) -> isize {
+    sys_shmdt(addr)
+}
+
+pub fn mprotect(start: usize, len: usize, prot: usize) -> isize {
+    sys_mprotect(start, len, prot)
+}
+
 pub fn spawn(path: &str) -> isize {
-    sys_spawn(path)
+    sys_spawn(path, &[core::ptr::null::<u8>()], &[core::ptr::null::<u8>()])
+}
+
+/// `args` must be terminated by a null pointer
+pub fn spawn_with_args(path: &str, args: &[*const u8]) -> isize {
+    sys_spawn(path, args, &[core::ptr::null::<u8>()])
+}
+
+/// `args` and `envs` must be terminated by a null pointer, each env is like `KEY=VALUE`
+pub fn spawn_with_env(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
+    sys_spawn(path, args, envs)
 }
 
+/// Make the lowest closed descriptor refer to the same object as `fd` and return it
 pub fn dup(fd: usize) -> isize {
     sys_dup(fd)
 }
+/// Create a pipe, storing the descriptor of its read end into `pipe_fd[0]`
+/// and that of its write end into `pipe_fd[1]`
 pub fn pipe(pipe_fd: &mut [usize]) -> isize {
-    sys_pipe(pipe_fd)
+    let mut fds = [0u32; 2];
+    let ret = sys_pipe(&mut fds);
+    if ret == 0 {
+        pipe_fd[0] = fds[0] as usize;
+        pipe_fd[1] = fds[1] as usize;
+    }
+    ret
 }
 
 pub fn task_info(info: &TaskInfo) -> isize {
     sys_task_info(info)
 }
 
+pub fn task_stats(stats: &mut SchedStats) -> isize {
+    sys_task_stats(stats)
+}
+
+/// Memory usage of the current process
+pub fn mem_stats(stats: &mut MemStats) -> isize {
+    sys_mem_stats(stats)
+}
+
+/// The MLFQ queue the current task is in, -1 with the stride scheduler
+pub fn sched_queue() -> isize {
+    sys_sched_queue()
+}
+
+/// Set the scheduling time slice in microseconds, clamped to [1 ms, 1 s]
+pub fn sched_setquantum(us: usize) -> isize {
+    sys_sched_setquantum(us)
+}
+
+pub fn sched_getquantum() -> isize {
+    sys_sched_getquantum()
+}
+
+/// Take a snapshot of the scheduler, `entries` is filled with the current process followed by
+/// the ready ones. Return the number of processes in the snapshot, which may exceed `entries`.
+pub fn sched_stat(stat: &mut SchedStat, entries: &mut [SchedEntry]) -> isize {
+    sys_sched_stat(stat, entries)
+}
+
+/// Make the kernel log every syscall of `pid`, the current process or a child, or stop it.
+/// The children forked or spawned afterwards are traced too
+pub fn strace(pid: usize, on: bool) -> isize {
+    sys_strace(pid, on as usize)
+}
+
+/// Set the kernel log level, from 0 (off) to 5 (trace), of the modules under `target`, which
+/// must end with `\0`, or globally if `None`. Return the previous level, -1 unless allowed
+pub fn klogctl(level: usize, target: Option<&str>) -> isize {
+    sys_klogctl(level, target.map_or(core::ptr::null(), str::as_ptr))
+}
+
+/// Act on the kernel log with one of the `SYSLOG_ACTION_*`, the read actions fill `buf`
+/// and return the number of bytes read
+pub fn syslog(action: usize, buf: &mut [u8]) -> isize {
+    sys_syslog(action, buf.as_mut_ptr(), buf.len())
+}
+
+/// getrandom flags, the kernel generator never blocks either way
+pub const GRND_NONBLOCK: usize = 1;
+pub const GRND_RANDOM: usize = 2;
+
+/// Fill `buf` with random bytes, at most 256 per call, return the number of bytes written
+pub fn getrandom(buf: &mut [u8], flags: usize) -> isize {
+    sys_getrandom(buf.as_mut_ptr(), buf.len(), flags)
+}
+
+/// Copy the names of the apps, each followed by a newline, into `buf` as far as it goes,
+/// return the length of the whole list
+pub fn list_apps(buf: &mut [u8]) -> isize {
+    sys_list_apps(buf.as_mut_ptr(), buf.len())
+}
+
+/// Make the kernel keep an extra reference to the child `pid`, or drop it, for debugging.
+/// Only a kernel built with the `selftest` feature supports it, -1 otherwise
+pub fn hold_task(pid: usize, hold: bool) -> isize {
+    sys_hold_task(pid, hold as usize)
+}
+
 pub fn thread_create(entry: usize, arg: usize) -> isize {
     sys_thread_create(entry, arg)
 }
@@ -1,32 +1,74 @@
 use crate::TaskInfo;
 
-use super::{Stat, TimeVal};
+use super::{
+    IoVec, MemStats, RLimit, Rusage, SchedEntry, SchedStat, SchedStats, SignalAction, Stat,
+    SysInfo, TimeSpec, TimeVal, Tms, Utsname,
+};
 
 pub const SYSCALL_OPENAT: usize = 56;
 pub const SYSCALL_CLOSE: usize = 57;
 pub const SYSCALL_READ: usize = 63;
 pub const SYSCALL_WRITE: usize = 64;
+pub const SYSCALL_WRITEV: usize = 66;
 pub const SYSCALL_UNLINKAT: usize = 35;
 pub const SYSCALL_LINKAT: usize = 37;
 pub const SYSCALL_FSTAT: usize = 80;
 pub const SYSCALL_EXIT: usize = 93;
 pub const SYSCALL_SLEEP: usize = 101;
+pub const SYSCALL_NANOSLEEP: usize = 115;
 pub const SYSCALL_YIELD: usize = 124;
+pub const SYSCALL_KILL: usize = 129;
+pub const SYSCALL_SIGACTION: usize = 134;
+pub const SYSCALL_SIGPROCMASK: usize = 135;
+pub const SYSCALL_SIGRETURN: usize = 139;
+pub const SYSCALL_TIMES: usize = 153;
 pub const SYSCALL_GETTIMEOFDAY: usize = 169;
+pub const SYSCALL_SETTIMEOFDAY: usize = 170;
+pub const SYSCALL_CLOCK_GETTIME: usize = 113;
 pub const SYSCALL_GETPID: usize = 172;
+pub const SYSCALL_GETPPID: usize = 173;
+pub const SYSCALL_SYSINFO: usize = 179;
+pub const SYSCALL_PRCTL: usize = 167;
 pub const SYSCALL_GETTID: usize = 178;
+pub const SYSCALL_SHMGET: usize = 194;
+pub const SYSCALL_SHMAT: usize = 196;
+pub const SYSCALL_SHMDT: usize = 197;
 pub const SYSCALL_FORK: usize = 220;
 pub const SYSCALL_EXEC: usize = 221;
 pub const SYSCALL_WAITPID: usize = 260;
+pub const SYSCALL_PRLIMIT: usize = 261;
 pub const SYSCALL_SET_PRIORITY: usize = 140;
+pub const SYSCALL_SBRK: usize = 214;
 pub const SYSCALL_MUNMAP: usize = 215;
 pub const SYSCALL_MMAP: usize = 222;
+pub const SYSCALL_MPROTECT: usize = 226;
 pub const SYSCALL_SPAWN: usize = 400;
 pub const SYSCALL_MAIL_READ: usize = 401;
 pub const SYSCALL_MAIL_WRITE: usize = 402;
 pub const SYSCALL_DUP: usize = 24;
 pub const SYSCALL_PIPE: usize = 59;
 pub const SYSCALL_TASK_INFO: usize = 410;
+pub const SYSCALL_HOLD_TASK: usize = 411;
+pub const SYSCALL_ALARM: usize = 412;
+pub const SYSCALL_TASK_STATS: usize = 413;
+pub const SYSCALL_SCHED_QUEUE: usize = 414;
+pub const SYSCALL_SCHED_SETQUANTUM: usize = 415;
+pub const SYSCALL_SCHED_GETQUANTUM: usize = 416;
+pub const SYSCALL_SCHED_STAT: usize = 417;
+pub const SYSCALL_MEM_STATS: usize = 418;
+pub const SYSCALL_STRACE: usize = 419;
+pub const SYSCALL_KLOGCTL: usize = 420;
+pub const SYSCALL_SYSLOG: usize = 116;
+pub const SYSCALL_PAUSE: usize = 421;
+pub const SYSCALL_GETRANDOM: usize = 278;
+pub const SYSCALL_UNAME: usize = 160;
+pub const SYSCALL_SHUTDOWN: usize = 422;
+pub const SYSCALL_EXIT_GROUP: usize = 94;
+pub const SYSCALL_SETPGID: usize = 154;
+pub const SYSCALL_GETPGID: usize = 155;
+pub const SYSCALL_WAIT4: usize = 423;
+pub const SYSCALL_GETRUSAGE: usize = 165;
+pub const SYSCALL_LIST_APPS: usize = 424;
 pub const SYSCALL_THREAD_CREATE: usize = 460;
 pub const SYSCALL_WAITTID: usize = 462;
 pub const SYSCALL_MUTEX_CREATE: usize = 463;
@@ -99,6 +141,10 @@ pub fn sys_write(fd: usize, buffer: &[u8]) -> isize {
     syscall(SYSCALL_WRITE, [fd, buffer.as_ptr() as usize, buffer.len()])
 }
 
+pub fn sys_writev(fd: usize, iov: &[IoVec]) -> isize {
+    syscall(SYSCALL_WRITEV, [fd, iov.as_ptr() as usize, iov.len()])
+}
+
 pub fn sys_linkat(
     old_dirfd: usize,
     old_path: &str,
@@ -150,10 +196,72 @@ pub fn sys_sleep(sleep_ms: usize) -> isize {
     syscall(SYSCALL_SLEEP, [sleep_ms, 0, 0])
 }
 
+pub fn sys_nanosleep(req: &TimeSpec, rem: *mut TimeSpec) -> isize {
+    syscall(
+        SYSCALL_NANOSLEEP,
+        [req as *const _ as usize, rem as usize, 0],
+    )
+}
+
 pub fn sys_yield() -> isize {
     syscall(SYSCALL_YIELD, [0, 0, 0])
 }
 
+pub fn sys_kill(pid: usize, signum: usize) -> isize {
+    syscall(SYSCALL_KILL, [pid, signum, 0])
+}
+
+pub fn sys_sigaction(
+    signum: usize,
+    action: *const SignalAction,
+    old_action: *mut SignalAction,
+) -> isize {
+    syscall(
+        SYSCALL_SIGACTION,
+        [signum, action as usize, old_action as usize],
+    )
+}
+
+pub fn sys_prlimit(
+    pid: usize,
+    resource: usize,
+    new_limit: *const RLimit,
+    old_limit: *mut RLimit,
+) -> isize {
+    syscall6(
+        SYSCALL_PRLIMIT,
+        [pid, resource, new_limit as usize, old_limit as usize, 0, 0],
+    )
+}
+
+pub fn sys_sigprocmask(mask: u32) -> isize {
+    syscall(SYSCALL_SIGPROCMASK, [mask as usize, 0, 0])
+}
+
+pub fn sys_sigreturn() -> isize {
+    syscall(SYSCALL_SIGRETURN, [0, 0, 0])
+}
+
+pub fn sys_settimeofday(time: &TimeVal, tz: usize) -> isize {
+    syscall(SYSCALL_SETTIMEOFDAY, [time as *const _ as usize, tz, 0])
+}
+
+pub fn sys_clock_gettime(clock_id: usize, ts: &mut TimeSpec) -> isize {
+    syscall(SYSCALL_CLOCK_GETTIME, [clock_id, ts as *mut _ as usize, 0])
+}
+
+pub fn sys_times(tms: &mut Tms) -> isize {
+    syscall(SYSCALL_TIMES, [tms as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_prctl(option: usize, arg: usize) -> isize {
+    syscall(SYSCALL_PRCTL, [option, arg, 0])
+}
+
+pub fn sys_sysinfo(info: &mut SysInfo) -> isize {
+    syscall(SYSCALL_SYSINFO, [info as *mut _ as usize, 0, 0])
+}
+
 pub fn sys_get_time(time: &TimeVal, tz: usize) -> isize {
     syscall(SYSCALL_GETTIMEOFDAY, [time as *const _ as usize, tz, 0])
 }
@@ -162,19 +270,31 @@ pub fn sys_getpid() -> isize {
     syscall(SYSCALL_GETPID, [0, 0, 0])
 }
 
+pub fn sys_getppid() -> isize {
+    syscall(SYSCALL_GETPPID, [0, 0, 0])
+}
+
 pub fn sys_fork() -> isize {
     syscall(SYSCALL_FORK, [0, 0, 0])
 }
 
-pub fn sys_exec(path: &str, args: &[*const u8]) -> isize {
+pub fn sys_exec(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
     syscall(
         SYSCALL_EXEC,
-        [path.as_ptr() as usize, args.as_ptr() as usize, 0],
+        [
+            path.as_ptr() as usize,
+            args.as_ptr() as usize,
+            envs.as_ptr() as usize,
+        ],
     )
 }
 
 pub fn sys_waitpid(pid: isize, xstatus: *mut i32) -> isize {
-    syscall(SYSCALL_WAITPID, [pid as usize, xstatus as usize, 0])
+    sys_waitpid_options(pid, xstatus, 0)
+}
+
+pub fn sys_waitpid_options(pid: isize, xstatus: *mut i32, options: usize) -> isize {
+    syscall(SYSCALL_WAITPID, [pid as usize, xstatus as usize, options])
 }
 
 pub fn sys_set_priority(prio: isize) -> isize {
@@ -185,19 +305,34 @@ pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
     syscall(SYSCALL_MMAP, [start, len, prot])
 }
 
+pub fn sys_sbrk(size: isize) -> isize {
+    syscall(SYSCALL_SBRK, [size as usize, 0, 0])
+}
+
 pub fn sys_munmap(start: usize, len: usize) -> isize {
     syscall(SYSCALL_MUNMAP, [start, len, 0])
 }
 
-pub fn sys_spawn(path: &str) -> isize {
-    syscall(SYSCALL_SPAWN, [path.as_ptr() as usize, 0, 0])
+pub fn sys_mprotect(start: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MPROTECT, [start, len, prot])
+}
+
+pub fn sys_spawn(path: &str, args: &[*const u8], envs: &[*const u8]) -> isize {
+    syscall(
+        SYSCALL_SPAWN,
+        [
+            path.as_ptr() as usize,
+            args.as_ptr() as usize,
+            envs.as_ptr() as usize,
+        ],
+    )
 }
 
 pub fn sys_dup(fd: usize) -> isize {
     syscall(SYSCALL_DUP, [fd, 0, 0])
 }
 
-pub fn sys_pipe(pipe: &mut [usize]) -> isize {
+pub fn sys_pipe(pipe: &mut [u32; 2]) -> isize {
     syscall(SYSCALL_PIPE, [pipe.as_mut_ptr() as usize, 0, 0])
 }
 
@@ -205,6 +340,108 @@ pub fn sys_task_info(info: &TaskInfo) -> isize {
     syscall(SYSCALL_TASK_INFO, [info as *const _ as usize, 0, 0])
 }
 
+pub fn sys_hold_task(pid: usize, hold: usize) -> isize {
+    syscall(SYSCALL_HOLD_TASK, [pid, hold, 0])
+}
+
+pub fn sys_task_stats(stats: &mut SchedStats) -> isize {
+    syscall(SYSCALL_TASK_STATS, [stats as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_sched_queue() -> isize {
+    syscall(SYSCALL_SCHED_QUEUE, [0, 0, 0])
+}
+
+pub fn sys_sched_setquantum(us: usize) -> isize {
+    syscall(SYSCALL_SCHED_SETQUANTUM, [us, 0, 0])
+}
+
+pub fn sys_sched_getquantum() -> isize {
+    syscall(SYSCALL_SCHED_GETQUANTUM, [0, 0, 0])
+}
+
+pub fn sys_mem_stats(stats: &mut MemStats) -> isize {
+    syscall(SYSCALL_MEM_STATS, [stats as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_strace(pid: usize, on: usize) -> isize {
+    syscall(SYSCALL_STRACE, [pid, on, 0])
+}
+
+pub fn sys_klogctl(level: usize, target: *const u8) -> isize {
+    syscall(SYSCALL_KLOGCTL, [level, target as usize, 0])
+}
+
+pub fn sys_syslog(action: usize, buf: *mut u8, len: usize) -> isize {
+    syscall(SYSCALL_SYSLOG, [action, buf as usize, len])
+}
+
+pub fn sys_pause() -> isize {
+    syscall(SYSCALL_PAUSE, [0, 0, 0])
+}
+
+pub fn sys_getrandom(buf: *mut u8, len: usize, flags: usize) -> isize {
+    syscall(SYSCALL_GETRANDOM, [buf as usize, len, flags])
+}
+
+pub fn sys_uname(buf: &mut Utsname) -> isize {
+    syscall(SYSCALL_UNAME, [buf as *mut _ as usize, 0, 0])
+}
+
+pub fn sys_shutdown(failure: usize) -> isize {
+    syscall(SYSCALL_SHUTDOWN, [failure, 0, 0])
+}
+
+pub fn sys_exit_group(exit_code: i32) -> ! {
+    syscall(SYSCALL_EXIT_GROUP, [exit_code as usize, 0, 0]);
+    panic!("sys_exit_group never returns!");
+}
+
+pub fn sys_setpgid(pid: usize, pgid: usize) -> isize {
+    syscall(SYSCALL_SETPGID, [pid, pgid, 0])
+}
+
+pub fn sys_getpgid(pid: usize) -> isize {
+    syscall(SYSCALL_GETPGID, [pid, 0, 0])
+}
+
+pub fn sys_wait4(pid: isize, xstatus: *mut i32, options: usize, rusage: *mut Rusage) -> isize {
+    syscall6(
+        SYSCALL_WAIT4,
+        [
+            pid as usize,
+            xstatus as usize,
+            options,
+            rusage as usize,
+            0,
+            0,
+        ],
+    )
+}
+
+pub fn sys_getrusage(who: isize, usage: *mut Rusage) -> isize {
+    syscall(SYSCALL_GETRUSAGE, [who as usize, usage as usize, 0])
+}
+
+pub fn sys_list_apps(buf: *mut u8, len: usize) -> isize {
+    syscall(SYSCALL_LIST_APPS, [buf as usize, len, 0])
+}
+
+pub fn sys_sched_stat(stat: &mut SchedStat, entries: &mut [SchedEntry]) -> isize {
+    syscall(
+        SYSCALL_SCHED_STAT,
+        [
+            stat as *mut _ as usize,
+            entries.as_mut_ptr() as usize,
+            entries.len(),
+        ],
+    )
+}
+
+pub fn sys_alarm(seconds: usize) -> isize {
+    syscall(SYSCALL_ALARM, [seconds, 0, 0])
+}
+
 pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
     syscall(SYSCALL_THREAD_CREATE, [entry, arg, 0])
 }
@@ -256,3 +493,15 @@ pub fn sys_condvar_signal(condvar_id: usize) -> isize {
 pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
     syscall(SYSCALL_CONDVAR_WAIT, [condvar_id, mutex_id, 0])
 }
+
+pub fn sys_shmget(key: usize, size: usize) -> isize {
+    syscall(SYSCALL_SHMGET, [key, size, 0])
+}
+
+pub fn sys_shmat(shmid: usize, addr: usize) -> isize {
+    syscall(SYSCALL_SHMAT, [shmid, addr, 0])
+}
+
+pub fn sys_shmdt(addr: usize) -> isize {
+    syscall(SYSCALL_SHMDT, [addr, 0, 0])
+}